@@ -0,0 +1,52 @@
+use serde::{Deserialize, Serialize};
+
+/// グラフ描画用に区間ごとに集計した系列。
+/// `x` は各区間の下限値、`y` はその区間に入った戦闘の回数を表し、Chart.js などにそのまま渡せる。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct BinnedSeries {
+    /// 区間の幅
+    pub bin_width: u32,
+    pub x: Vec<u32>,
+    pub y: Vec<u32>,
+}
+
+impl BinnedSeries {
+    /// 値ごとの発生回数 (インデックスが値に対応する) を、最大 `bins` 個の等幅の区間にまとめる。
+    pub fn from_counts(counts: &[f64], bins: usize) -> Self {
+        if counts.is_empty() {
+            return Self::default();
+        }
+        let bin_width = counts.len().div_ceil(bins.max(1)).max(1);
+        let (x, y) = counts
+            .chunks(bin_width)
+            .enumerate()
+            .map(|(i, chunk)| ((i * bin_width) as u32, chunk.iter().sum::<f64>() as u32))
+            .unzip();
+        Self {
+            bin_width: bin_width as u32,
+            x,
+            y,
+        }
+    }
+}
+
+/// 戦闘ごとの整数値の発生回数を、値ごとに数える。
+#[derive(Debug, Clone, Default)]
+pub struct ValueCounts {
+    counts: Vec<f64>,
+}
+
+impl ValueCounts {
+    pub fn record(&mut self, value: u32) {
+        let value = value as usize;
+        if self.counts.len() <= value {
+            self.counts.resize(value + 1, 0.0);
+        }
+        self.counts[value] += 1.0;
+    }
+
+    pub fn binned(&self, bins: usize) -> BinnedSeries {
+        BinnedSeries::from_counts(&self.counts, bins)
+    }
+}
@@ -0,0 +1,130 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::Battle;
+use crate::interface::BootstrapOptions;
+use crate::rng;
+
+/// 再標本化に使う、戦闘1回分の簡易な記録。
+#[derive(Debug, Clone, Copy)]
+struct BattleRecord {
+    /// 敵旗艦を撃沈したか
+    flagship_sunk: bool,
+    /// 戦闘後の修理に使う高速修復材の数
+    buckets: u32,
+}
+
+/// 戦闘ごとの記録を蓄積し、派生指標の信頼区間をブートストラップ法で推定する。
+#[derive(Debug, Clone)]
+pub struct BootstrapCollector {
+    options: BootstrapOptions,
+    /// 再標本化に使う乱数のシード。`None` の場合は実行ごとに異なる
+    seed: Option<u64>,
+    records: Vec<BattleRecord>,
+}
+
+impl BootstrapCollector {
+    pub fn new(options: BootstrapOptions, seed: Option<u64>) -> Self {
+        Self {
+            options,
+            seed,
+            records: Vec::new(),
+        }
+    }
+
+    /// 終了した戦闘1回分の記録を蓄積する。
+    pub fn record(&mut self, battle: &Battle) {
+        let log = battle.log();
+        self.records.push(BattleRecord {
+            flagship_sunk: log.enemy_snapshots.first().is_some_and(|s| !s.is_alive()),
+            buckets: log
+                .friend_snapshots
+                .iter()
+                .filter(|s| crate::sortie::needs_bucket(s.hp(), s.max_hp(), false))
+                .count() as u32,
+        });
+    }
+
+    /// 蓄積した記録を再標本化し、各指標の推定値と 95% 信頼区間を計算する。
+    pub fn results(&self) -> BootstrapIntervals {
+        let gauge_kills = self.options.gauge_kills as f64;
+        let sorties_to_clear = |records: &mut dyn Iterator<Item = &BattleRecord>| {
+            let (n, kills) = records.fold((0.0, 0.0), |(n, kills), r| {
+                (n + 1.0, kills + r.flagship_sunk as u32 as f64)
+            });
+            (kills > 0.0).then(|| gauge_kills * n / kills)
+        };
+        let buckets_per_kill = |records: &mut dyn Iterator<Item = &BattleRecord>| {
+            let (buckets, kills) = records.fold((0.0, 0.0), |(buckets, kills), r| {
+                (
+                    buckets + r.buckets as f64,
+                    kills + r.flagship_sunk as u32 as f64,
+                )
+            });
+            (kills > 0.0).then(|| buckets / kills)
+        };
+
+        BootstrapIntervals {
+            resamples: self.options.resamples,
+            sorties_to_clear: self.interval(&sorties_to_clear),
+            buckets_per_kill: self.interval(&buckets_per_kill),
+        }
+    }
+
+    /// 全記録から計算した推定値と、再標本化した記録から計算した値のパーセンタイルによる信頼区間。
+    /// 推定値を計算できない場合 (敵旗艦を一度も撃沈していない場合など) は `None` を返す。
+    /// 再標本化で計算できなかった回は、信頼区間の計算から除外する。
+    fn interval(
+        &self,
+        statistic: &dyn Fn(&mut dyn Iterator<Item = &BattleRecord>) -> Option<f64>,
+    ) -> Option<ConfidenceInterval> {
+        let estimate = statistic(&mut self.records.iter())?;
+        let n = self.records.len();
+        let mut rng = rng::from_seed(self.seed);
+        let mut samples: Vec<f64> = (0..self.options.resamples)
+            .filter_map(|_| {
+                let mut resampled = (0..n).map(|_| &self.records[rng::index(&mut rng, n)]);
+                statistic(&mut resampled)
+            })
+            .collect();
+        if samples.is_empty() {
+            return Some(ConfidenceInterval {
+                estimate,
+                lower: estimate,
+                upper: estimate,
+            });
+        }
+        samples.sort_by(|a, b| a.total_cmp(b));
+        let quantile = |q: f64| {
+            let rank = (q * samples.len() as f64).ceil() as usize;
+            samples[rank.saturating_sub(1).min(samples.len() - 1)]
+        };
+        Some(ConfidenceInterval {
+            estimate,
+            lower: quantile(0.025),
+            upper: quantile(0.975),
+        })
+    }
+}
+
+/// ブートストラップ法で推定した派生指標の信頼区間。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapIntervals {
+    /// 再標本化の回数
+    pub resamples: u32,
+    /// ゲージを削り切るまでに必要な戦闘回数の期待値。敵旗艦を一度も撃沈していない場合は `null`
+    pub sorties_to_clear: Option<ConfidenceInterval>,
+    /// 敵旗艦1隻を撃沈するあたりの高速修復材の使用数。敵旗艦を一度も撃沈していない場合は `null`
+    pub buckets_per_kill: Option<ConfidenceInterval>,
+}
+
+/// 推定値と 95% 信頼区間。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ConfidenceInterval {
+    pub estimate: f64,
+    /// 信頼区間の下限 (2.5 パーセンタイル)
+    pub lower: f64,
+    /// 信頼区間の上限 (97.5 パーセンタイル)
+    pub upper: f64,
+}
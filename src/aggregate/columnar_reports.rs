@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{Battle, BattleDirection, BattleResult};
+
+/// 戦闘ごとの結果を、項目ごとの配列に並べて保持する構造体。
+/// どの配列も同じインデックスが同じ戦闘を表す。
+/// `BattleReport` の配列に比べてシリアライズが軽く、列単位の分析にそのまま使える。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ColumnarReports {
+    /// 戦闘の回数 (各配列の長さ)
+    pub battles: u32,
+    /// 入力された敵編成のうち、各戦闘で選ばれたもののインデックス
+    pub enemy_indices: Vec<usize>,
+    /// 各戦闘の戦闘評価
+    pub ranks: Vec<BattleResult>,
+    /// 各戦闘の交戦形態
+    pub directions: Vec<BattleDirection>,
+    /// 味方艦ごとの、各戦闘の終了時のHP (`friendHp[艦][戦闘]`)
+    pub friend_hp: Vec<Vec<u16>>,
+    /// 敵艦ごとの、各戦闘の終了時のHP (`enemyHp[艦][戦闘]`)。
+    /// 選ばれた敵編成にその位置の艦がいない戦闘では `null`。
+    pub enemy_hp: Vec<Vec<Option<u16>>>,
+}
+
+impl ColumnarReports {
+    /// 終了した戦闘1回分の結果を各配列の末尾に加える。
+    pub fn record(&mut self, battle: &Battle, enemy_index: usize) {
+        let log = battle.log();
+        if self.friend_hp.is_empty() {
+            self.friend_hp = vec![Vec::new(); log.friend_snapshots.len()];
+        }
+        for (column, snapshot) in self.friend_hp.iter_mut().zip(&log.friend_snapshots) {
+            column.push(snapshot.hp());
+        }
+        // 敵編成によって艦数が異なるため、これまでより艦数が多ければ列を追加する
+        while self.enemy_hp.len() < log.enemy_snapshots.len() {
+            self.enemy_hp.push(vec![None; self.battles as usize]);
+        }
+        for (i, column) in self.enemy_hp.iter_mut().enumerate() {
+            column.push(log.enemy_snapshots.get(i).map(|s| s.hp()));
+        }
+
+        self.battles += 1;
+        self.enemy_indices.push(enemy_index);
+        self.ranks.push(BattleResult::calculate(battle));
+        self.directions.push(*battle.setup().direction());
+    }
+}
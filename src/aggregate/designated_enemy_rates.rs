@@ -0,0 +1,61 @@
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::HpDistribution;
+use crate::battle::{DamagedLevel, ShipSnapshot};
+use crate::fleet::Ship;
+
+/// 指定された敵艦の戦闘終了時の状態の発生率を表す構造体。
+/// 母数は、その敵艦を含む敵編成と戦闘した回数。
+/// 集計中は発生回数を、集計結果では確率を保持する。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignatedEnemyRates {
+    /// 艦名
+    pub name: String,
+    /// 母数となった戦闘の回数
+    pub battles: u32,
+    /// 撃沈した確率
+    pub sunk: f64,
+    /// 大破させた (撃沈には至らなかった) 確率
+    pub heavy: f64,
+    /// 戦闘終了時のHPが `hpThreshold` を超えていた確率
+    pub above_threshold: f64,
+    /// 戦闘終了時の残りHPの分布。ゲージの残量と組み合わせて撃破率の計算に使う。
+    pub remaining_hp: HpDistribution,
+}
+
+impl DesignatedEnemyRates {
+    /// 戦闘終了時の艦の状態を1回分計上する。
+    pub fn record(&mut self, ship: &Ship, snapshot: &ShipSnapshot, hp_threshold: u16) {
+        if self.battles == 0 {
+            self.name = ship.name();
+        }
+        self.battles += 1;
+        match ship.damaged_level(snapshot) {
+            DamagedLevel::Sunk => self.sunk += 1.0,
+            DamagedLevel::Heavy => self.heavy += 1.0,
+            _ => {}
+        }
+        if snapshot.hp() > hp_threshold {
+            self.above_threshold += 1.0;
+        }
+        self.remaining_hp.record(snapshot.hp());
+    }
+
+    /// 発生回数を母数で割り、確率に変換した新しいインスタンスを返す。
+    pub fn normalized(&self) -> Self {
+        let factor = if self.battles == 0 {
+            0.0
+        } else {
+            1.0 / self.battles as f64
+        };
+        Self {
+            name: self.name.clone(),
+            battles: self.battles,
+            sunk: self.sunk * factor,
+            heavy: self.heavy * factor,
+            above_threshold: self.above_threshold * factor,
+            remaining_hp: self.remaining_hp.normalized(),
+        }
+    }
+}
@@ -0,0 +1,204 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{ActionLog, Battle, DamagedLevel, Phase};
+use crate::fleet::FleetLike;
+
+/// 1回の戦闘中に注目すべき事象が発生したかどうかを表す構造体。
+/// 行動ログを先頭から再生し、各時点での艦のHPを復元して判定する。
+#[derive(Debug, Clone, Default)]
+pub struct BattleEvents {
+    /// 砲撃戦開始前に敵旗艦が撃沈された
+    pub enemy_flagship_sunk_before_artillery: bool,
+    /// 航空戦終了時点で敵空母がすべて撃沈されていた。敵艦隊に空母がいない場合は `None`
+    pub enemy_carriers_sunk_after_air: Option<bool>,
+    /// 味方旗艦が最初に行動する前に大破していた
+    pub friend_flagship_heavy_before_acting: bool,
+    /// 味方旗艦が大破で戦闘を終えたか、轟沈ストッパーにより撃沈を免れた
+    pub friend_flagship_heavy_or_stopped: bool,
+}
+
+impl BattleEvents {
+    pub fn detect(battle: &Battle) -> Self {
+        let friend_ships = battle.setup().friend_fleet.ships();
+        let enemy_ships = battle.setup().enemy_fleet.ships();
+        let mut friend_hp = friend_ships.iter().map(|s| s.hp()).collect::<Vec<_>>();
+        let mut enemy_hp = enemy_ships.iter().map(|s| s.hp()).collect::<Vec<_>>();
+        let enemy_has_carriers = enemy_ships.iter().any(|s| s.is_carrier_class());
+
+        let mut events = Self::default();
+        let mut artillery_started = false;
+        let mut after_air = false;
+        let mut friend_flagship_acted = false;
+
+        for action in battle.log().actions() {
+            match action {
+                ActionLog::PhaseStart(phase) => {
+                    // 航空戦の次のフェーズの開始時点を「航空戦終了時点」とみなす
+                    if after_air && enemy_has_carriers {
+                        Self::check_enemy_carriers(&mut events, battle, &enemy_hp);
+                    }
+                    after_air = *phase == Phase::AirCombat;
+
+                    if !artillery_started && *phase == Phase::FirstArtillery {
+                        artillery_started = true;
+                        events.enemy_flagship_sunk_before_artillery =
+                            enemy_hp.first().is_some_and(|hp| *hp == 0);
+                    }
+                }
+                ActionLog::Attack(attack) => {
+                    if attack.is_stopped() && attack.target_idx == 0 {
+                        events.friend_flagship_heavy_or_stopped = true;
+                    }
+                    if attack.to_enemy && !attack.is_land_base_attack() && attack.actor_idx == 0 {
+                        Self::check_friend_flagship(
+                            &mut events,
+                            &mut friend_flagship_acted,
+                            &friend_hp,
+                            battle,
+                        );
+                    }
+                    let hp = if attack.to_enemy {
+                        &mut enemy_hp[attack.target_idx]
+                    } else {
+                        &mut friend_hp[attack.target_idx]
+                    };
+                    *hp = hp.saturating_sub(attack.applied_damage);
+                }
+                ActionLog::TurnSkip {
+                    is_friend: true,
+                    ship_idx: 0,
+                    ..
+                } => {
+                    Self::check_friend_flagship(
+                        &mut events,
+                        &mut friend_flagship_acted,
+                        &friend_hp,
+                        battle,
+                    );
+                }
+                _ => {}
+            }
+        }
+
+        // 空襲戦などで航空戦のまま戦闘が終わった場合は、戦闘終了時点を航空戦終了時点とする
+        if after_air && enemy_has_carriers {
+            Self::check_enemy_carriers(&mut events, battle, &enemy_hp);
+        }
+
+        if let Some(flagship) = friend_ships.first() {
+            events.friend_flagship_heavy_or_stopped |=
+                DamagedLevel::from_hp(friend_hp[0], flagship.max_hp()) == DamagedLevel::Heavy;
+        }
+
+        events
+    }
+
+    /// 航空戦終了時点で、敵空母がすべて撃沈されているかを記録する。最初の航空戦の結果だけを使う。
+    fn check_enemy_carriers(events: &mut Self, battle: &Battle, enemy_hp: &[u16]) {
+        if events.enemy_carriers_sunk_after_air.is_some() {
+            return;
+        }
+        events.enemy_carriers_sunk_after_air = Some(
+            battle
+                .setup()
+                .enemy_fleet
+                .ships()
+                .iter()
+                .zip(enemy_hp.iter())
+                .filter(|(s, _)| s.is_carrier_class())
+                .all(|(_, hp)| *hp == 0),
+        );
+    }
+
+    /// 味方旗艦の最初の手番で、その時点の損傷状態を確認する。
+    fn check_friend_flagship(
+        events: &mut Self,
+        acted: &mut bool,
+        friend_hp: &[u16],
+        battle: &Battle,
+    ) {
+        if *acted {
+            return;
+        }
+        *acted = true;
+        let Some(flagship) = battle.setup().friend_fleet.ships().first() else {
+            return;
+        };
+        events.friend_flagship_heavy_before_acting =
+            DamagedLevel::from_hp(friend_hp[0], flagship.max_hp()) >= DamagedLevel::Heavy;
+    }
+}
+
+/// 注目すべき事象の発生率を表す構造体。
+/// 集計中は発生回数を、集計結果では確率を保持する。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct EventRates {
+    /// 砲撃戦開始前に敵旗艦が撃沈されている確率
+    pub enemy_flagship_sunk_before_artillery: f64,
+    /// 航空戦終了時点で敵空母がすべて撃沈されている確率。
+    /// 敵艦隊に空母がいた戦闘のみを母数とし、該当する戦闘がなければ `None`
+    pub enemy_carriers_sunk_after_air: Option<f64>,
+    /// 味方旗艦が最初に行動する前に大破している確率
+    pub friend_flagship_heavy_before_acting: f64,
+    /// 味方旗艦が大破で戦闘を終えるか、轟沈ストッパーにより撃沈を免れる確率。
+    /// 撤退判断や入渠計画の目安になる。
+    pub friend_flagship_heavy_or_stopped: f64,
+}
+
+/// `EventRates` を作成するための発生回数のカウンタ。
+#[derive(Debug, Clone, Default)]
+pub struct EventCounts {
+    enemy_flagship_sunk_before_artillery: u32,
+    enemy_carriers_sunk_after_air: u32,
+    battles_with_enemy_carriers: u32,
+    friend_flagship_heavy_before_acting: u32,
+    friend_flagship_heavy_or_stopped: u32,
+}
+
+impl EventCounts {
+    pub fn record(&mut self, events: &BattleEvents) {
+        if events.enemy_flagship_sunk_before_artillery {
+            self.enemy_flagship_sunk_before_artillery += 1;
+        }
+        if let Some(sunk) = events.enemy_carriers_sunk_after_air {
+            self.battles_with_enemy_carriers += 1;
+            if sunk {
+                self.enemy_carriers_sunk_after_air += 1;
+            }
+        }
+        if events.friend_flagship_heavy_before_acting {
+            self.friend_flagship_heavy_before_acting += 1;
+        }
+        if events.friend_flagship_heavy_or_stopped {
+            self.friend_flagship_heavy_or_stopped += 1;
+        }
+    }
+
+    pub fn rates(&self, battles: u32) -> EventRates {
+        let ratio = |count: u32, total: u32| {
+            if total == 0 {
+                0.0
+            } else {
+                count as f64 / total as f64
+            }
+        };
+        EventRates {
+            enemy_flagship_sunk_before_artillery: ratio(
+                self.enemy_flagship_sunk_before_artillery,
+                battles,
+            ),
+            enemy_carriers_sunk_after_air: (self.battles_with_enemy_carriers > 0).then(|| {
+                ratio(
+                    self.enemy_carriers_sunk_after_air,
+                    self.battles_with_enemy_carriers,
+                )
+            }),
+            friend_flagship_heavy_before_acting: ratio(
+                self.friend_flagship_heavy_before_acting,
+                battles,
+            ),
+            friend_flagship_heavy_or_stopped: ratio(self.friend_flagship_heavy_or_stopped, battles),
+        }
+    }
+}
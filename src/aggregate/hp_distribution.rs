@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+/// 戦闘終了時の残りHPの分布を表す構造体。
+/// 集計中は残りHPごとの発生回数を、集計結果では確率を保持する。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HpDistribution {
+    /// 残りHPごとの発生率。インデックスが残りHPに対応する (0 は撃沈)。
+    pub histogram: Vec<f64>,
+    /// 残りHPのパーセンタイル
+    pub percentiles: HpPercentiles,
+}
+
+/// 残りHPのパーセンタイル。`p10` は、10% の戦闘で残りHPがこの値以下になることを表す。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct HpPercentiles {
+    pub p5: u16,
+    pub p10: u16,
+    pub p25: u16,
+    pub p50: u16,
+    pub p75: u16,
+    pub p90: u16,
+    pub p95: u16,
+}
+
+impl HpDistribution {
+    /// 残りHPを1回分計上する。
+    pub fn record(&mut self, hp: u16) {
+        let hp = hp as usize;
+        if self.histogram.len() <= hp {
+            self.histogram.resize(hp + 1, 0.0);
+        }
+        self.histogram[hp] += 1.0;
+    }
+
+    /// 発生回数を確率に変換し、パーセンタイルを計算した新しいインスタンスを返す。
+    pub fn normalized(&self) -> Self {
+        let total: f64 = self.histogram.iter().sum();
+        if total == 0.0 {
+            return Self::default();
+        }
+        let histogram = self
+            .histogram
+            .iter()
+            .map(|count| count / total)
+            .collect::<Vec<_>>();
+
+        let percentile = |p: f64| {
+            let mut cumulative = 0.0;
+            for (hp, rate) in histogram.iter().enumerate() {
+                cumulative += rate;
+                // 浮動小数点の誤差で最後まで到達しないことがないように、わずかに余裕を持たせる
+                if cumulative + 1e-9 >= p {
+                    return hp as u16;
+                }
+            }
+            histogram.len().saturating_sub(1) as u16
+        };
+        let percentiles = HpPercentiles {
+            p5: percentile(0.05),
+            p10: percentile(0.10),
+            p25: percentile(0.25),
+            p50: percentile(0.50),
+            p75: percentile(0.75),
+            p90: percentile(0.90),
+            p95: percentile(0.95),
+        };
+
+        Self {
+            histogram,
+            percentiles,
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::battle::{ActionLog, Battle, FleetSide};
+use crate::interface::{Comparison, MetricQuery, MetricValue, Statistic};
+
+/// `MetricQuery` ごとに戦闘ごとの値を蓄積し、統計量を計算する。
+#[derive(Debug, Clone, Default)]
+pub struct MetricCollector {
+    queries: Vec<MetricQuery>,
+    samples: Vec<Vec<f64>>,
+}
+
+impl MetricCollector {
+    pub fn new(queries: Vec<MetricQuery>) -> Self {
+        let samples = vec![Vec::new(); queries.len()];
+        Self { queries, samples }
+    }
+
+    /// 終了した戦闘1回分の値を各問い合わせについて取り出し、蓄積する。
+    pub fn record(&mut self, battle: &Battle) {
+        for (query, samples) in self.queries.iter().zip(self.samples.iter_mut()) {
+            if let Some(value) = Self::extract(query, battle) {
+                samples.push(value);
+            }
+        }
+    }
+
+    /// 各問い合わせの統計量を、問い合わせと同じ順に返す。
+    /// 対象の艦が存在する戦闘が1回もなかった問い合わせは `None` になる。
+    pub fn results(&self) -> Vec<Option<f64>> {
+        self.queries
+            .iter()
+            .zip(self.samples.iter())
+            .map(|(query, samples)| Self::evaluate(&query.statistic, samples))
+            .collect()
+    }
+
+    /// 戦闘から問い合わせの対象となる値を取り出す。対象の艦がいない場合は `None` を返す。
+    fn extract(query: &MetricQuery, battle: &Battle) -> Option<f64> {
+        let snapshots = match query.side {
+            FleetSide::Friend => &battle.log().friend_snapshots,
+            FleetSide::Enemy => &battle.log().enemy_snapshots,
+        };
+        if query.ship.is_some_and(|idx| idx >= snapshots.len()) {
+            return None;
+        }
+        let selected = |idx: usize| query.ship.is_none_or(|ship| ship == idx);
+
+        let value = match query.value {
+            MetricValue::RemainingHp => snapshots
+                .iter()
+                .enumerate()
+                .filter(|(idx, _)| selected(*idx))
+                .map(|(_, s)| s.hp() as f64)
+                .sum(),
+            MetricValue::DamageTaken | MetricValue::DamageDealt => {
+                let side_is_friend = query.side == FleetSide::Friend;
+                let mut current_phase = None;
+                let mut total = 0.0;
+                for action in battle.log().actions() {
+                    match action {
+                        ActionLog::PhaseStart(phase) => current_phase = Some(phase),
+                        ActionLog::Attack(attack) => {
+                            if query.phase.is_some() && query.phase.as_ref() != current_phase {
+                                continue;
+                            }
+                            // `to_enemy` は攻撃側が味方であることを表す
+                            let actor_is_friend = attack.to_enemy;
+                            let matched = if query.value == MetricValue::DamageDealt {
+                                // 基地航空隊の攻撃は艦を指定しない場合だけ味方艦隊の与ダメージに含める
+                                let by_ship = query.ship.is_none() || !attack.is_land_base_attack();
+                                actor_is_friend == side_is_friend
+                                    && by_ship
+                                    && selected(attack.actor_idx)
+                            } else {
+                                actor_is_friend != side_is_friend && selected(attack.target_idx)
+                            };
+                            if matched {
+                                total += attack.applied_damage as f64;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                total
+            }
+        };
+        Some(value)
+    }
+
+    fn evaluate(statistic: &Statistic, samples: &[f64]) -> Option<f64> {
+        if samples.is_empty() {
+            return None;
+        }
+        let n = samples.len() as f64;
+        let value = match statistic {
+            Statistic::Mean => samples.iter().sum::<f64>() / n,
+            Statistic::Quantile { q } => {
+                let mut sorted = samples.to_vec();
+                sorted.sort_by(|a, b| a.total_cmp(b));
+                let rank = (q.clamp(0.0, 1.0) * n).ceil() as usize;
+                sorted[rank.saturating_sub(1)]
+            }
+            Statistic::Probability { op, threshold } => {
+                let matched = samples
+                    .iter()
+                    .filter(|v| match op {
+                        Comparison::Lt => **v < *threshold,
+                        Comparison::Le => **v <= *threshold,
+                        Comparison::Eq => **v == *threshold,
+                        Comparison::Ge => **v >= *threshold,
+                        Comparison::Gt => **v > *threshold,
+                    })
+                    .count();
+                matched as f64 / n
+            }
+        };
+        Some(value)
+    }
+}
@@ -0,0 +1,258 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{ActionLog, Battle, BattleResult};
+use crate::fleet::FleetLike;
+use crate::interface::{AppliedDefault, DesignatedEnemy, SimulationOptions};
+
+mod binned_series;
+pub use binned_series::BinnedSeries;
+use binned_series::ValueCounts;
+
+mod bootstrap;
+use bootstrap::BootstrapCollector;
+pub use bootstrap::{BootstrapIntervals, ConfidenceInterval};
+
+mod columnar_reports;
+pub use columnar_reports::ColumnarReports;
+
+mod designated_enemy_rates;
+pub use designated_enemy_rates::DesignatedEnemyRates;
+
+mod event_rates;
+use event_rates::EventCounts;
+pub use event_rates::{BattleEvents, EventRates};
+
+mod hp_distribution;
+pub use hp_distribution::{HpDistribution, HpPercentiles};
+
+mod metric_collector;
+use metric_collector::MetricCollector;
+
+mod phase_damage;
+pub use phase_damage::PhaseDamage;
+
+mod rank_distribution;
+pub use rank_distribution::{RankByDirection, RankByFinalForm, RankDistribution, RankRates};
+
+mod report;
+
+mod resource_usage;
+use resource_usage::ResourceCounts;
+pub use resource_usage::ResourceUsage;
+
+mod setup_comparison;
+pub use setup_comparison::{DifferenceTest, SetupComparison, SetupCounts, Z_95};
+
+mod ship_damage_rates;
+pub use ship_damage_rates::ShipDamageRates;
+
+/// 複数回の戦闘結果を逐次集計するための構造体。
+/// 個々の `BattleReport` を保持せずに統計量だけを蓄積するため、大量の試行でもメモリを圧迫しない。
+#[derive(Debug, Default)]
+pub struct Aggregator {
+    battles: u32,
+    ranks: RankDistribution,
+    ranks_by_direction: RankByDirection,
+    ranks_by_final_form: RankByFinalForm,
+    phase_damage_total: PhaseDamage,
+    friend_damage_counts: Vec<ShipDamageRates>,
+    event_counts: EventCounts,
+    resource_counts: ResourceCounts,
+    designated_enemy: Option<DesignatedEnemy>,
+    designated_enemy_counts: DesignatedEnemyRates,
+    metrics: MetricCollector,
+    bootstrap: Option<BootstrapCollector>,
+    chart_bins: usize,
+    damage_dealt_counts: ValueCounts,
+    damage_taken_counts: ValueCounts,
+    friend_hp_counts: Vec<ValueCounts>,
+    applied_defaults: Vec<AppliedDefault>,
+}
+
+impl Aggregator {
+    /// `applied_defaults` は入力の補完で使った既定値で、統計結果にそのまま添える。
+    pub fn new(options: &SimulationOptions, applied_defaults: Vec<AppliedDefault>) -> Self {
+        Self {
+            applied_defaults,
+            designated_enemy: options.designated_enemy.clone(),
+            metrics: MetricCollector::new(options.metrics.clone()),
+            bootstrap: options
+                .bootstrap
+                .clone()
+                .map(|bootstrap| BootstrapCollector::new(bootstrap, options.seed)),
+            chart_bins: options.chart_bins,
+            ..Self::default()
+        }
+    }
+
+    /// 終了した戦闘1回分の結果を集計に加える。
+    /// `enemy_index` は、入力された敵編成のうち何番目と戦ったかを表す。
+    pub fn record(&mut self, battle: &Battle, enemy_index: usize) {
+        self.battles += 1;
+
+        let result = BattleResult::calculate(battle);
+        self.ranks.record(&result);
+        self.ranks_by_direction
+            .record(battle.setup().direction(), &result);
+        self.ranks_by_final_form
+            .record(battle.setup().enemy_fleet.is_final_form(), &result);
+
+        // 行動ログを先頭から走査し、直前の PhaseStart が示すフェーズに敵艦隊へのダメージを計上する
+        // 併せて、轟沈ストッパーが発動した味方艦と、両艦隊の被ダメージの合計を記録する
+        let friend_ships = battle.setup().friend_fleet.ships();
+        let mut stopped = vec![false; friend_ships.len()];
+        let mut current_phase = None;
+        let mut damage_dealt = 0;
+        let mut damage_taken = 0;
+        for action in battle.log().actions() {
+            match action {
+                ActionLog::PhaseStart(phase) => current_phase = Some(phase),
+                ActionLog::Attack(attack) if attack.to_enemy => {
+                    damage_dealt += attack.applied_damage as u32;
+                    if let Some(phase) = current_phase {
+                        *self.phase_damage_total.get_mut(phase) += attack.applied_damage as f64;
+                    }
+                }
+                ActionLog::Attack(attack) => {
+                    damage_taken += attack.applied_damage as u32;
+                    if attack.is_stopped() {
+                        stopped[attack.target_idx] = true;
+                    }
+                }
+                _ => {}
+            }
+        }
+        self.damage_dealt_counts.record(damage_dealt);
+        self.damage_taken_counts.record(damage_taken);
+
+        if self.friend_damage_counts.is_empty() {
+            self.friend_damage_counts = friend_ships.iter().map(ShipDamageRates::new).collect();
+            self.friend_hp_counts = vec![ValueCounts::default(); friend_ships.len()];
+        }
+        for (i, (ship, snapshot)) in friend_ships
+            .iter()
+            .zip(battle.log().friend_snapshots.iter())
+            .enumerate()
+        {
+            self.friend_damage_counts[i].record(ship, snapshot, stopped[i]);
+            self.friend_hp_counts[i].record(snapshot.hp() as u32);
+        }
+
+        self.event_counts.record(&BattleEvents::detect(battle));
+        self.resource_counts.record(battle);
+        self.metrics.record(battle);
+        if let Some(bootstrap) = &mut self.bootstrap {
+            bootstrap.record(battle);
+        }
+
+        if let Some(designated) = &self.designated_enemy {
+            let enemy_ships = battle.setup().enemy_fleet.ships();
+            let snapshots = &battle.log().enemy_snapshots;
+            if designated.fleet_index == enemy_index {
+                if let (Some(ship), Some(snapshot)) = (
+                    enemy_ships.get(designated.ship_index),
+                    snapshots.get(designated.ship_index),
+                ) {
+                    self.designated_enemy_counts
+                        .record(ship, snapshot, designated.hp_threshold);
+                }
+            }
+        }
+    }
+
+    /// これまでに集計した結果から `AggregateSummary` を作成する。
+    pub fn summary(&self) -> AggregateSummary {
+        let factor = if self.battles == 0 {
+            0.0
+        } else {
+            1.0 / self.battles as f64
+        };
+        AggregateSummary {
+            battles: self.battles,
+            ranks: self.ranks.normalized(),
+            ranks_by_direction: self.ranks_by_direction.normalized(),
+            ranks_by_final_form: self.ranks_by_final_form.normalized(),
+            average_phase_damage: self.phase_damage_total.scaled(factor),
+            friend_damage_rates: self
+                .friend_damage_counts
+                .iter()
+                .map(|c| c.scaled(factor))
+                .collect(),
+            event_rates: self.event_counts.rates(self.battles),
+            resources: self.resource_counts.usage(self.battles, self.chart_bins),
+            designated_enemy: self
+                .designated_enemy
+                .as_ref()
+                .map(|_| self.designated_enemy_counts.normalized()),
+            metrics: self.metrics.results(),
+            bootstrap: self.bootstrap.as_ref().map(BootstrapCollector::results),
+            charts: Charts {
+                damage_dealt: self.damage_dealt_counts.binned(self.chart_bins),
+                damage_taken: self.damage_taken_counts.binned(self.chart_bins),
+                friend_remaining_hp: self
+                    .friend_hp_counts
+                    .iter()
+                    .map(|c| c.binned(self.chart_bins))
+                    .collect(),
+                designated_enemy_remaining_hp: self.designated_enemy.as_ref().map(|_| {
+                    BinnedSeries::from_counts(
+                        &self.designated_enemy_counts.remaining_hp.histogram,
+                        self.chart_bins,
+                    )
+                }),
+            },
+            applied_defaults: self.applied_defaults.clone(),
+        }
+    }
+}
+
+/// 集計モードでフロントエンドに返す統計結果。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct AggregateSummary {
+    /// 集計した戦闘の回数
+    battles: u32,
+    /// 戦闘評価の分布
+    ranks: RankDistribution,
+    /// 交戦形態ごとの戦闘評価の分布
+    ranks_by_direction: RankByDirection,
+    /// 敵編成が最終形態かどうかで分けた戦闘評価の分布
+    ranks_by_final_form: RankByFinalForm,
+    /// 各フェーズで敵艦隊に与えた1戦あたりの平均ダメージ
+    average_phase_damage: PhaseDamage,
+    /// 味方艦ごとの戦闘終了時の損傷状態の発生率 (艦隊内の並び順)
+    friend_damage_rates: Vec<ShipDamageRates>,
+    /// 注目すべき事象の発生率
+    event_rates: EventRates,
+    /// 1戦あたりの資源の消費量
+    resources: ResourceUsage,
+    /// `SimulationOptions.designated_enemy` で指定された敵艦の状態の発生率
+    #[serde(skip_serializing_if = "Option::is_none")]
+    designated_enemy: Option<DesignatedEnemyRates>,
+    /// `SimulationOptions.metrics` の各問い合わせの結果。該当する戦闘がなかった場合は `null`
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    metrics: Vec<Option<f64>>,
+    /// `SimulationOptions.bootstrap` を指定した場合の、派生指標の信頼区間
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    bootstrap: Option<BootstrapIntervals>,
+    /// グラフ描画用の分布
+    charts: Charts,
+    /// 入力になかったために既定値で補った値
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    applied_defaults: Vec<AppliedDefault>,
+}
+
+/// グラフ描画用に区間ごとに集計した分布をまとめた構造体。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct Charts {
+    /// 敵艦隊に与えたダメージの合計の分布
+    pub damage_dealt: BinnedSeries,
+    /// 味方艦隊が受けたダメージの合計の分布
+    pub damage_taken: BinnedSeries,
+    /// 味方艦ごとの戦闘終了時の残りHPの分布 (艦隊内の並び順)
+    pub friend_remaining_hp: Vec<BinnedSeries>,
+    /// 指定された敵艦の戦闘終了時の残りHPの分布
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub designated_enemy_remaining_hp: Option<BinnedSeries>,
+}
@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::Phase;
+
+/// フェーズごとのダメージ量を表す構造体。
+/// 集計中は合計値を、集計結果では1戦あたりの平均値を保持する。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseDamage {
+    /// 基地航空隊の攻撃
+    #[serde(default)]
+    pub land_base: f64,
+    pub air_combat: f64,
+    pub opening_torpedo: f64,
+    pub first_artillery: f64,
+    pub second_artillery: f64,
+    pub closing_torpedo: f64,
+    pub night: f64,
+}
+
+impl PhaseDamage {
+    /// 指定されたフェーズに対応する値への可変参照を取得する。
+    pub fn get_mut(&mut self, phase: &Phase) -> &mut f64 {
+        match phase {
+            Phase::LandBase => &mut self.land_base,
+            Phase::AirCombat => &mut self.air_combat,
+            Phase::OpeningTorpedo => &mut self.opening_torpedo,
+            Phase::FirstArtillery => &mut self.first_artillery,
+            Phase::SecondArtillery => &mut self.second_artillery,
+            Phase::ClosingTorpedo => &mut self.closing_torpedo,
+            Phase::Night => &mut self.night,
+        }
+    }
+
+    /// 全フェーズの値を `factor` 倍した新しいインスタンスを返す。
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            land_base: self.land_base * factor,
+            air_combat: self.air_combat * factor,
+            opening_torpedo: self.opening_torpedo * factor,
+            first_artillery: self.first_artillery * factor,
+            second_artillery: self.second_artillery * factor,
+            closing_torpedo: self.closing_torpedo * factor,
+            night: self.night * factor,
+        }
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{BattleDirection, BattleResult};
+
+/// 戦闘評価ごとの発生率を表す構造体。
+/// 集計中は発生回数を、集計結果では確率を保持する。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RankDistribution {
+    /// 母数となった戦闘の回数
+    pub battles: u32,
+    pub ss: f64,
+    pub s: f64,
+    pub a: f64,
+    pub b: f64,
+    pub c: f64,
+    pub d: f64,
+    pub e: f64,
+}
+
+impl RankDistribution {
+    /// 戦闘評価を1回分計上する。
+    pub fn record(&mut self, result: &BattleResult) {
+        self.battles += 1;
+        let count = match result {
+            BattleResult::SS => &mut self.ss,
+            BattleResult::S => &mut self.s,
+            BattleResult::A => &mut self.a,
+            BattleResult::B => &mut self.b,
+            BattleResult::C => &mut self.c,
+            BattleResult::D => &mut self.d,
+            BattleResult::E => &mut self.e,
+        };
+        *count += 1.0;
+    }
+
+    /// 発生回数から、一定以上の戦闘評価を得る確率を計算する。
+    pub fn cumulative_rates(&self) -> RankRates {
+        let rates = self.normalized();
+        let s_or_better = rates.ss + rates.s;
+        let a_or_better = s_or_better + rates.a;
+        RankRates {
+            s_or_better,
+            a_or_better,
+            b_or_better: a_or_better + rates.b,
+        }
+    }
+
+    /// 発生回数を母数で割り、確率に変換した新しいインスタンスを返す。
+    pub fn normalized(&self) -> Self {
+        let factor = if self.battles == 0 {
+            0.0
+        } else {
+            1.0 / self.battles as f64
+        };
+        Self {
+            battles: self.battles,
+            ss: self.ss * factor,
+            s: self.s * factor,
+            a: self.a * factor,
+            b: self.b * factor,
+            c: self.c * factor,
+            d: self.d * factor,
+            e: self.e * factor,
+        }
+    }
+}
+
+/// 一定以上の戦闘評価を得る確率を表す構造体。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RankRates {
+    /// S勝利以上 (完全勝利Sを含む)
+    pub s_or_better: f64,
+    /// A勝利以上
+    pub a_or_better: f64,
+    /// B勝利以上
+    pub b_or_better: f64,
+}
+
+/// 交戦形態ごとの戦闘評価の分布を表す構造体。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RankByDirection {
+    pub same: RankDistribution,
+    pub against: RankDistribution,
+    pub t_advantage: RankDistribution,
+    pub t_disadvantage: RankDistribution,
+}
+
+impl RankByDirection {
+    pub fn record(&mut self, direction: &BattleDirection, result: &BattleResult) {
+        let distribution = match direction {
+            BattleDirection::Same => &mut self.same,
+            BattleDirection::Against => &mut self.against,
+            BattleDirection::TAdvantage => &mut self.t_advantage,
+            BattleDirection::TDisadvantage => &mut self.t_disadvantage,
+        };
+        distribution.record(result);
+    }
+
+    pub fn normalized(&self) -> Self {
+        Self {
+            same: self.same.normalized(),
+            against: self.against.normalized(),
+            t_advantage: self.t_advantage.normalized(),
+            t_disadvantage: self.t_disadvantage.normalized(),
+        }
+    }
+}
+
+/// 敵編成が最終形態かどうかで分けた戦闘評価の分布を表す構造体。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct RankByFinalForm {
+    /// 最終形態以外の編成との戦闘
+    pub pre_final: RankDistribution,
+    /// 最終形態の編成との戦闘
+    pub final_form: RankDistribution,
+}
+
+impl RankByFinalForm {
+    pub fn record(&mut self, final_form: bool, result: &BattleResult) {
+        let distribution = if final_form {
+            &mut self.final_form
+        } else {
+            &mut self.pre_final
+        };
+        distribution.record(result);
+    }
+
+    pub fn normalized(&self) -> Self {
+        Self {
+            pre_final: self.pre_final.normalized(),
+            final_form: self.final_form.normalized(),
+        }
+    }
+}
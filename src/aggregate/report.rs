@@ -0,0 +1,307 @@
+//! 集計結果を共有用の Markdown / HTML 文書に整形する。
+use serde_json::json;
+
+use crate::aggregate::{AggregateSummary, RankDistribution};
+
+/// 文書中の1つの表。
+struct Table {
+    title: &'static str,
+    headers: Vec<String>,
+    rows: Vec<Vec<String>>,
+}
+
+fn percent(rate: f64) -> String {
+    format!("{:.1}%", rate * 100.0)
+}
+
+fn rank_cells(ranks: &RankDistribution) -> Vec<String> {
+    [
+        ranks.ss, ranks.s, ranks.a, ranks.b, ranks.c, ranks.d, ranks.e,
+    ]
+    .iter()
+    .map(|r| percent(*r))
+    .collect()
+}
+
+fn rank_headers(first: &str) -> Vec<String> {
+    [first, "試行", "SS", "S", "A", "B", "C", "D", "E"]
+        .iter()
+        .map(|h| h.to_string())
+        .collect()
+}
+
+impl AggregateSummary {
+    /// 集計結果を Markdown 文書として出力する。
+    pub fn to_markdown(&self) -> String {
+        let mut out = format!("# シミュレーション結果\n\n試行回数: {}\n", self.battles);
+        for table in self.tables() {
+            out.push_str(&format!("\n## {}\n\n", table.title));
+            out.push_str(&format!("| {} |\n", table.headers.join(" | ")));
+            out.push_str(&format!(
+                "|{}\n",
+                table.headers.iter().map(|_| " --- |").collect::<String>()
+            ));
+            for row in &table.rows {
+                out.push_str(&format!("| {} |\n", row.join(" | ")));
+            }
+        }
+        out.push_str("\n## チャート用データ\n\n```json\n");
+        out.push_str(&self.chart_data());
+        out.push_str("\n```\n");
+        out
+    }
+
+    /// 集計結果を、外部のリソースに依存しない単一の HTML 文書として出力する。
+    /// チャート用データは `<script type="application/json" id="chart-data">` に埋め込まれる。
+    pub fn to_html(&self) -> String {
+        let mut body = format!(
+            "<h1>シミュレーション結果</h1>\n<p>試行回数: {}</p>\n",
+            self.battles
+        );
+        for table in self.tables() {
+            body.push_str(&format!("<h2>{}</h2>\n<table>\n<tr>", escape(table.title)));
+            for header in &table.headers {
+                body.push_str(&format!("<th>{}</th>", escape(header)));
+            }
+            body.push_str("</tr>\n");
+            for row in &table.rows {
+                body.push_str("<tr>");
+                for cell in row {
+                    body.push_str(&format!("<td>{}</td>", escape(cell)));
+                }
+                body.push_str("</tr>\n");
+            }
+            body.push_str("</table>\n");
+        }
+        // JSON 中の `</script>` で要素が閉じられないようにする
+        let chart_data = self.chart_data().replace("</", "<\\/");
+        format!(
+            "<!DOCTYPE html>\n<html lang=\"ja\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>シミュレーション結果</title>\n<style>\n\
+             body {{ font-family: sans-serif; margin: 2em; }}\n\
+             table {{ border-collapse: collapse; margin-bottom: 1.5em; }}\n\
+             th, td {{ border: 1px solid #999; padding: 0.25em 0.75em; text-align: right; }}\n\
+             th:first-child, td:first-child {{ text-align: left; }}\n\
+             </style>\n</head>\n<body>\n{}\
+             <script type=\"application/json\" id=\"chart-data\">\n{}\n</script>\n\
+             </body>\n</html>\n",
+            body, chart_data
+        )
+    }
+
+    /// 編成の変更前後を見比べるための主要な指標を、(名前, 値) の組で返す。発生率は百分率で表す。
+    pub fn key_metrics(&self) -> Vec<(String, f64)> {
+        // `ranks` は発生率に変換済みのため、そのまま足し合わせる
+        let ranks = &self.ranks;
+        let s_or_better = ranks.ss + ranks.s;
+        let damage = &self.average_phase_damage;
+        let mut metrics = vec![
+            ("S勝利以上 (%)".to_string(), s_or_better * 100.0),
+            ("A勝利以上 (%)".to_string(), (s_or_better + ranks.a) * 100.0),
+            (
+                "平均与ダメージ".to_string(),
+                damage.air_combat
+                    + damage.opening_torpedo
+                    + damage.first_artillery
+                    + damage.second_artillery
+                    + damage.closing_torpedo
+                    + damage.night,
+            ),
+        ];
+        metrics.extend(self.friend_damage_rates.iter().enumerate().map(|(i, r)| {
+            (
+                format!("{}番艦 {} 大破以上 (%)", i + 1, r.name),
+                (r.heavy + r.sunk) * 100.0,
+            )
+        }));
+        metrics
+    }
+
+    fn tables(&self) -> Vec<Table> {
+        let mut tables = Vec::new();
+
+        let mut rank_row = vec!["全体".to_string(), self.ranks.battles.to_string()];
+        rank_row.extend(rank_cells(&self.ranks));
+        tables.push(Table {
+            title: "戦闘評価",
+            headers: rank_headers(""),
+            rows: vec![rank_row],
+        });
+
+        let directions = [
+            ("同航戦", &self.ranks_by_direction.same),
+            ("反航戦", &self.ranks_by_direction.against),
+            ("T字有利", &self.ranks_by_direction.t_advantage),
+            ("T字不利", &self.ranks_by_direction.t_disadvantage),
+        ];
+        tables.push(Table {
+            title: "交戦形態別の戦闘評価",
+            headers: rank_headers("交戦形態"),
+            rows: directions
+                .iter()
+                .map(|(name, ranks)| {
+                    let mut row = vec![name.to_string(), ranks.battles.to_string()];
+                    row.extend(rank_cells(ranks));
+                    row
+                })
+                .collect(),
+        });
+
+        // 最終形態の編成がある場合のみ、形態別の表を出力する
+        let forms = &self.ranks_by_final_form;
+        if forms.final_form.battles > 0 {
+            let forms = [
+                ("最終形態前", &forms.pre_final),
+                ("最終形態", &forms.final_form),
+            ];
+            tables.push(Table {
+                title: "最終形態別の戦闘評価",
+                headers: rank_headers("形態"),
+                rows: forms
+                    .iter()
+                    .map(|(name, ranks)| {
+                        let mut row = vec![name.to_string(), ranks.battles.to_string()];
+                        row.extend(rank_cells(ranks));
+                        row
+                    })
+                    .collect(),
+            });
+        }
+
+        let damage = &self.average_phase_damage;
+        let phases = [
+            ("航空戦", damage.air_combat),
+            ("開幕雷撃", damage.opening_torpedo),
+            ("砲撃戦1巡目", damage.first_artillery),
+            ("砲撃戦2巡目", damage.second_artillery),
+            ("閉幕雷撃", damage.closing_torpedo),
+            ("夜戦", damage.night),
+        ];
+        tables.push(Table {
+            title: "フェーズ別の平均与ダメージ",
+            headers: vec!["フェーズ".to_string(), "平均ダメージ".to_string()],
+            rows: phases
+                .iter()
+                .map(|(name, value)| vec![name.to_string(), format!("{:.1}", value)])
+                .collect(),
+        });
+
+        tables.push(Table {
+            title: "味方艦の損傷",
+            headers: ["艦名", "撃沈", "大破", "中破", "小破", "無傷", "ストッパー"]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+            rows: self
+                .friend_damage_rates
+                .iter()
+                .map(|r| {
+                    vec![
+                        r.name.clone(),
+                        percent(r.sunk),
+                        percent(r.heavy),
+                        percent(r.moderate),
+                        percent(r.minor),
+                        percent(r.untouched),
+                        percent(r.stopped),
+                    ]
+                })
+                .collect(),
+        });
+
+        let events = &self.event_rates;
+        let mut event_rows = vec![
+            vec![
+                "砲撃戦開始前に敵旗艦を撃沈".to_string(),
+                percent(events.enemy_flagship_sunk_before_artillery),
+            ],
+            vec![
+                "味方旗艦が行動前に大破".to_string(),
+                percent(events.friend_flagship_heavy_before_acting),
+            ],
+            vec![
+                "味方旗艦が大破またはストッパー発動".to_string(),
+                percent(events.friend_flagship_heavy_or_stopped),
+            ],
+        ];
+        if let Some(rate) = events.enemy_carriers_sunk_after_air {
+            event_rows.push(vec![
+                "航空戦終了時に敵空母を全滅".to_string(),
+                percent(rate),
+            ]);
+        }
+        tables.push(Table {
+            title: "注目すべき事象",
+            headers: vec!["事象".to_string(), "発生率".to_string()],
+            rows: event_rows,
+        });
+
+        tables.push(Table {
+            title: "資源の消費 (1戦あたり)",
+            headers: vec!["資源".to_string(), "平均".to_string()],
+            rows: vec![vec![
+                "ボーキサイト".to_string(),
+                format!("{:.1}", self.resources.average_bauxite),
+            ]],
+        });
+
+        if let Some(designated) = &self.designated_enemy {
+            let p = &designated.remaining_hp.percentiles;
+            tables.push(Table {
+                title: "指定した敵艦",
+                headers: [
+                    "艦名",
+                    "戦闘回数",
+                    "撃沈",
+                    "大破",
+                    "閾値超え",
+                    "残りHP (10/50/90%)",
+                ]
+                .iter()
+                .map(|h| h.to_string())
+                .collect(),
+                rows: vec![vec![
+                    designated.name.clone(),
+                    designated.battles.to_string(),
+                    percent(designated.sunk),
+                    percent(designated.heavy),
+                    percent(designated.above_threshold),
+                    format!("{} / {} / {}", p.p10, p.p50, p.p90),
+                ]],
+            });
+        }
+
+        tables
+    }
+
+    /// チャート描画用のデータを JSON 文字列として出力する。
+    fn chart_data(&self) -> String {
+        let ranks = &self.ranks;
+        let damage = &self.average_phase_damage;
+        let remaining_hp = self
+            .designated_enemy
+            .as_ref()
+            .map(|d| &d.remaining_hp.histogram);
+        let data = json!({
+            "ranks": {
+                "labels": ["SS", "S", "A", "B", "C", "D", "E"],
+                "values": [ranks.ss, ranks.s, ranks.a, ranks.b, ranks.c, ranks.d, ranks.e],
+            },
+            "phaseDamage": {
+                "labels": ["air_combat", "opening_torpedo", "first_artillery",
+                           "second_artillery", "closing_torpedo", "night"],
+                "values": [damage.air_combat, damage.opening_torpedo, damage.first_artillery,
+                           damage.second_artillery, damage.closing_torpedo, damage.night],
+            },
+            "designatedEnemyRemainingHp": remaining_hp,
+        });
+        serde_json::to_string(&data).unwrap_or_default()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::{BinnedSeries, ValueCounts};
+use crate::battle::Battle;
+use crate::fleet::FleetLike;
+
+/// 撃墜された艦載機1機あたりの補充に必要なボーキサイト。
+const BAUXITE_PER_PLANE: u32 = 5;
+
+/// 戦闘1回あたりの資源の消費量。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ResourceUsage {
+    /// 撃墜された艦載機の補充に必要なボーキサイトの平均
+    pub average_bauxite: f64,
+    /// 撃墜された艦載機の補充に必要なボーキサイトの分布
+    pub bauxite: BinnedSeries,
+}
+
+/// 資源の消費量を戦闘ごとに集計する。
+#[derive(Debug, Default)]
+pub(super) struct ResourceCounts {
+    total_bauxite: u64,
+    bauxite_counts: ValueCounts,
+}
+
+impl ResourceCounts {
+    pub fn record(&mut self, battle: &Battle) {
+        let bauxite = lost_planes(battle) * BAUXITE_PER_PLANE;
+        self.total_bauxite += bauxite as u64;
+        self.bauxite_counts.record(bauxite);
+    }
+
+    pub fn usage(&self, battles: u32, bins: usize) -> ResourceUsage {
+        let average_bauxite = if battles == 0 {
+            0.0
+        } else {
+            self.total_bauxite as f64 / battles as f64
+        };
+        ResourceUsage {
+            average_bauxite,
+            bauxite: self.bauxite_counts.binned(bins),
+        }
+    }
+}
+
+/// 味方艦隊が戦闘中に失った艦載機の数。戦闘開始時と終了時の搭載数の差から求める。
+fn lost_planes(battle: &Battle) -> u32 {
+    battle
+        .setup()
+        .friend_fleet
+        .ships()
+        .iter()
+        .zip(&battle.log().friend_snapshots)
+        .flat_map(|(ship, snapshot)| ship.airplane_slots().iter().zip(snapshot.slots()))
+        .map(|(before, after)| before.saturating_sub(*after) as u32)
+        .sum()
+}
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{ActionLog, Battle, BattleResult};
+
+/// 両側 95% 信頼区間に対応する標準正規分布の分位点
+pub const Z_95: f64 = 1.959_963_984_540_054;
+
+/// 同じ敵編成・同じシードで戦った2つの編成 (A, B) の戦闘結果を、組ごとに逐次集計する構造体。
+#[derive(Debug, Default)]
+pub struct SetupCounts {
+    battles: u32,
+    s_or_better: PairedSums,
+    boss_damage: PairedSums,
+}
+
+/// 組になった標本 (A, B) の合計と、組ごとの差 `A - B` の合計・二乗和。
+#[derive(Debug, Default)]
+struct PairedSums {
+    a: f64,
+    b: f64,
+    difference: f64,
+    difference_sq: f64,
+}
+
+impl PairedSums {
+    fn add(&mut self, a: f64, b: f64) {
+        let d = a - b;
+        self.a += a;
+        self.b += b;
+        self.difference += d;
+        self.difference_sq += d * d;
+    }
+
+    /// 組の数が `n` のときの、差の平均に対する検定。
+    /// 同じシードによる A と B の相関を活かすため、組ごとの差の分散 `var(d) / n` を標準誤差に使う。
+    fn test(&self, n: u32) -> DifferenceTest {
+        if n == 0 {
+            return DifferenceTest::new(0.0, 0.0, 0.0);
+        }
+        let n_f = n as f64;
+        let mean = self.difference / n_f;
+        let variance = if n > 1 {
+            (self.difference_sq - n_f * mean * mean).max(0.0) / (n_f - 1.0)
+        } else {
+            0.0
+        };
+        DifferenceTest::new(self.a / n_f, self.b / n_f, variance / n_f)
+    }
+}
+
+impl SetupCounts {
+    /// 同じ条件で戦った A と B の戦闘1回分の結果を組として計上する。
+    /// ボスへのダメージは敵旗艦へのダメージの合計とする。
+    pub fn record(&mut self, a: &Battle, b: &Battle) {
+        let (s_a, damage_a) = Self::outcome(a);
+        let (s_b, damage_b) = Self::outcome(b);
+        self.add(s_a, damage_a, s_b, damage_b);
+    }
+
+    fn add(&mut self, s_a: f64, damage_a: f64, s_b: f64, damage_b: f64) {
+        self.battles += 1;
+        self.s_or_better.add(s_a, s_b);
+        self.boss_damage.add(damage_a, damage_b);
+    }
+
+    /// 戦闘1回分の、S勝利以上かどうか (1.0 または 0.0) と敵旗艦へのダメージの合計。
+    fn outcome(battle: &Battle) -> (f64, f64) {
+        let s = matches!(
+            BattleResult::calculate(battle),
+            BattleResult::SS | BattleResult::S
+        );
+        let damage: f64 = battle
+            .log()
+            .actions()
+            .filter_map(|action| match action {
+                ActionLog::Attack(attack) if attack.to_enemy && attack.target_idx == 0 => {
+                    Some(attack.applied_damage as f64)
+                }
+                _ => None,
+            })
+            .sum();
+        (if s { 1.0 } else { 0.0 }, damage)
+    }
+}
+
+/// 2つの編成 (A, B) の結果の差の検定結果。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SetupComparison {
+    /// A の戦闘回数
+    pub battles_a: u32,
+    /// B の戦闘回数
+    pub battles_b: u32,
+    /// S勝利以上の発生率の差
+    pub s_rate: DifferenceTest,
+    /// 敵旗艦に与えた1戦あたりの平均ダメージの差
+    pub boss_damage: DifferenceTest,
+}
+
+impl SetupComparison {
+    pub fn new(counts: &SetupCounts) -> Self {
+        Self {
+            battles_a: counts.battles,
+            battles_b: counts.battles,
+            s_rate: counts.s_or_better.test(counts.battles),
+            boss_damage: counts.boss_damage.test(counts.battles),
+        }
+    }
+}
+
+/// 2つの推定値の差 (A - B) に対する正規近似の検定結果。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DifferenceTest {
+    pub a: f64,
+    pub b: f64,
+    /// A - B
+    pub difference: f64,
+    /// 差の 95% 信頼区間の下限
+    pub lower: f64,
+    /// 差の 95% 信頼区間の上限
+    pub upper: f64,
+    /// 差がないという帰無仮説に対する両側 p 値
+    pub p_value: f64,
+}
+
+impl DifferenceTest {
+    /// それぞれの推定値と、差の推定値の分散から差を検定する。
+    fn new(a: f64, b: f64, variance: f64) -> Self {
+        let difference = a - b;
+        let se = variance.sqrt();
+        let p_value = if se > 0.0 {
+            2.0 * (1.0 - normal_cdf((difference / se).abs()))
+        } else if difference == 0.0 {
+            1.0
+        } else {
+            0.0
+        };
+        Self {
+            a,
+            b,
+            difference,
+            lower: difference - Z_95 * se,
+            upper: difference + Z_95 * se,
+            p_value: p_value.clamp(0.0, 1.0),
+        }
+    }
+}
+
+/// 標準正規分布の累積分布関数。
+fn normal_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// 誤差関数の近似 (Abramowitz and Stegun 7.1.26、最大誤差 1.5e-7)。
+fn erf(x: f64) -> f64 {
+    let sign = x.signum();
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t
+        * (0.254_829_592
+            + t * (-0.284_496_736
+                + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// 組ごとの差を使わない、独立な標本とみなした場合の差の標準誤差。
+    fn unpaired_se(a: &[f64], b: &[f64]) -> f64 {
+        let variance_of_mean = |xs: &[f64]| {
+            let n = xs.len() as f64;
+            let mean = xs.iter().sum::<f64>() / n;
+            xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / (n - 1.0) / n
+        };
+        (variance_of_mean(a) + variance_of_mean(b)).sqrt()
+    }
+
+    #[test]
+    fn identical_setups_have_no_difference() {
+        let mut counts = SetupCounts::default();
+        for i in 0..100 {
+            let s = (i % 3 == 0) as u8 as f64;
+            let damage = (i * 37 % 150) as f64;
+            counts.add(s, damage, s, damage);
+        }
+        let comparison = SetupComparison::new(&counts);
+        for test in [&comparison.s_rate, &comparison.boss_damage] {
+            assert_eq!(test.difference, 0.0);
+            assert_eq!((test.lower, test.upper), (0.0, 0.0));
+            assert_eq!(test.p_value, 1.0);
+        }
+    }
+
+    #[test]
+    fn shared_seeds_tighten_the_interval() {
+        // 同じシードでは、乱数による大きなばらつきが A と B に共通してかかる
+        let shared = (0..200).map(|i| (i * 73 % 200) as f64).collect::<Vec<_>>();
+        let a = shared.iter().map(|x| x + 5.0).collect::<Vec<_>>();
+        let b = shared
+            .iter()
+            .enumerate()
+            .map(|(i, x)| x + (i % 3) as f64)
+            .collect::<Vec<_>>();
+        let mut counts = SetupCounts::default();
+        for (x_a, x_b) in a.iter().zip(&b) {
+            counts.add(0.0, *x_a, 0.0, *x_b);
+        }
+        let paired = SetupComparison::new(&counts).boss_damage;
+        let unpaired = unpaired_se(&a, &b);
+        assert!((paired.difference - 4.0).abs() < 0.01);
+        assert!(paired.upper - paired.lower < 0.1 * 2.0 * Z_95 * unpaired);
+        assert!(paired.p_value < 1e-6);
+    }
+}
@@ -0,0 +1,72 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{DamagedLevel, ShipSnapshot};
+use crate::fleet::Ship;
+
+/// 艦ごとの戦闘終了時の損傷状態の発生率を表す構造体。
+/// 集計中は発生回数を、集計結果では確率を保持する。
+/// `sunk`, `heavy`, `moderate`, `minor` は互いに排他だが、`stopped` と `heavy_or_stopped` はそれらと重複し得る。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipDamageRates {
+    /// 艦名
+    pub name: String,
+    /// 撃沈された確率
+    pub sunk: f64,
+    /// 轟沈ストッパーにより撃沈を免れた確率
+    pub stopped: f64,
+    /// 大破で戦闘を終えた確率
+    pub heavy: f64,
+    /// 大破で戦闘を終えたか、轟沈ストッパーにより撃沈を免れた確率
+    pub heavy_or_stopped: f64,
+    /// 中破で戦闘を終えた確率
+    pub moderate: f64,
+    /// 小破で戦闘を終えた確率
+    pub minor: f64,
+    /// 一切ダメージを受けずに戦闘を終えた確率
+    pub untouched: f64,
+}
+
+impl ShipDamageRates {
+    pub fn new(ship: &Ship) -> Self {
+        Self {
+            name: ship.name(),
+            ..Default::default()
+        }
+    }
+
+    /// 戦闘終了時の艦の状態を1回分計上する。
+    pub fn record(&mut self, ship: &Ship, snapshot: &ShipSnapshot, stopped: bool) {
+        let level = ship.damaged_level(snapshot);
+        if stopped || level == DamagedLevel::Heavy {
+            self.heavy_or_stopped += 1.0;
+        }
+        match level {
+            DamagedLevel::Sunk => self.sunk += 1.0,
+            DamagedLevel::Heavy => self.heavy += 1.0,
+            DamagedLevel::Moderate => self.moderate += 1.0,
+            DamagedLevel::Minor => self.minor += 1.0,
+            DamagedLevel::NoDamage => {}
+        }
+        if stopped {
+            self.stopped += 1.0;
+        }
+        if snapshot.hp() == ship.hp() {
+            self.untouched += 1.0;
+        }
+    }
+
+    /// 全項目の値を `factor` 倍した新しいインスタンスを返す。
+    pub fn scaled(&self, factor: f64) -> Self {
+        Self {
+            name: self.name.clone(),
+            sunk: self.sunk * factor,
+            stopped: self.stopped * factor,
+            heavy: self.heavy * factor,
+            heavy_or_stopped: self.heavy_or_stopped * factor,
+            moderate: self.moderate * factor,
+            minor: self.minor * factor,
+            untouched: self.untouched * factor,
+        }
+    }
+}
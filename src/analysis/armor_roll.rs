@@ -0,0 +1,46 @@
+use serde::{Deserialize, Serialize};
+
+/// 防御力 `装甲 × 0.7 + floor(装甲 × 乱数) × 0.6` の確率分布。乱数は [0, 1)。
+/// `floor(装甲 × 乱数)` は 0 から 装甲 - 1 までの整数を等確率でとる。装甲が 0 の場合は防御力 0 のみをとる。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ArmorRoll {
+    pub armor: u16,
+    /// 防御力の最小値
+    pub min: f64,
+    /// 防御力の最大値
+    pub max: f64,
+    /// 防御力のとりうる値と確率の組 (昇順)
+    pub pmf: Vec<DefenseProbability>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DefenseProbability {
+    pub defense: f64,
+    pub probability: f64,
+}
+
+impl ArmorRoll {
+    pub fn new(armor: u16) -> Self {
+        let rolls = armor.max(1);
+        let probability = 1.0 / rolls as f64;
+        let pmf = (0..rolls)
+            .map(|roll| DefenseProbability {
+                defense: Self::defense(armor, roll),
+                probability,
+            })
+            .collect();
+        Self {
+            armor,
+            min: Self::defense(armor, 0),
+            max: Self::defense(armor, rolls - 1),
+            pmf,
+        }
+    }
+
+    /// `floor(装甲 × 乱数)` が `roll` の場合の防御力。
+    pub fn defense(armor: u16, roll: u16) -> f64 {
+        armor as f64 * 0.7 + roll as f64 * 0.6
+    }
+}
@@ -0,0 +1,178 @@
+use crate::analysis::ArmorRoll;
+use crate::battle::{self, BattleDirection, ShipSnapshot, CRITICAL_MULTIPLIER};
+use crate::fleet::Ship;
+use crate::formula::{FormationFactor, FormulaConstants};
+
+/// 攻撃1回で与えるダメージの確率分布。インデックスがダメージ、値がその確率を表す。
+/// 交戦形態、命中、クリティカル、装甲乱数、カスダメを考慮し、ダメージは攻撃対象の残りHPで打ち切る。
+/// 外れた攻撃はダメージ 0 として数える。陣形の補正は考慮しない。
+#[derive(Debug, Clone)]
+pub struct DamageDistribution {
+    probabilities: Vec<f64>,
+}
+
+impl DamageDistribution {
+    /// 戦闘開始時の状態の `actor` が、昼砲撃戦で `target` を攻撃した場合の分布を計算する。
+    pub fn day_attack(actor: &Ship, target: &Ship, constants: &FormulaConstants) -> Self {
+        let hp = target.hp();
+        let mut distribution = Self {
+            probabilities: vec![0.0; hp as usize + 1],
+        };
+        let scratch = scratch_damages(hp, constants);
+        for (direction, direction_rate) in BattleDirection::distribution() {
+            let outcomes = AttackOutcomes::day_attack(actor, target, &direction, constants);
+            for (damage, p) in outcomes.damages(hp, &scratch) {
+                distribution.probabilities[damage as usize] += direction_rate * p;
+            }
+        }
+        distribution
+    }
+
+    /// ダメージの期待値。
+    pub fn mean(&self) -> f64 {
+        self.probabilities
+            .iter()
+            .enumerate()
+            .map(|(damage, p)| damage as f64 * p)
+            .sum()
+    }
+
+    /// ダメージが `damage` 以上になる確率。
+    pub fn at_least(&self, damage: u16) -> f64 {
+        // 丸め誤差で 1 を超えないようにする
+        self.probabilities
+            .iter()
+            .skip(damage as usize)
+            .sum::<f64>()
+            .min(1.0)
+    }
+}
+
+/// 昼砲撃戦で `actor` が `target` を攻撃する場合の命中率 (0.0〜1.0)。陣形の補正は考慮しない。
+pub fn day_attack_hit_rate(actor: &Ship, target: &Ship) -> f64 {
+    battle::hit_rate(day_attack_hit_value(actor, target))
+}
+
+fn day_attack_hit_value(actor: &Ship, target: &Ship) -> f64 {
+    battle::hit_value(
+        battle::day_attack_accuracy(actor, target, 1.0),
+        battle::evasion(target.evasion(), 1.0),
+    )
+}
+
+/// 交戦形態を固定した場合の、攻撃1回の結果の分布。
+/// カスダメの値は攻撃対象の残りHPに依存するため、カスダメになる確率のみを持つ。
+#[derive(Debug, Clone)]
+pub struct AttackOutcomes {
+    /// カスダメにならない場合のダメージと確率の組 (残りHPで打ち切る前の値)
+    damages: Vec<(u16, f64)>,
+    /// カスダメになる確率
+    scratch_rate: f64,
+    /// 外れる確率
+    miss_rate: f64,
+}
+
+impl AttackOutcomes {
+    /// 戦闘開始時の状態の `actor` が、昼砲撃戦で `target` を攻撃した場合の結果の分布を計算する。
+    pub fn day_attack(
+        actor: &Ship,
+        target: &Ship,
+        direction: &BattleDirection,
+        constants: &FormulaConstants,
+    ) -> Self {
+        let actor_snapshot = ShipSnapshot::from(actor);
+        let hit_value = day_attack_hit_value(actor, target);
+        let hit_rate = battle::hit_rate(hit_value);
+        let critical_rate = battle::critical_rate(hit_value);
+        let (power, _) = battle::day_attack_power::<f64>(
+            actor,
+            &actor_snapshot,
+            target,
+            direction,
+            &FormationFactor::default(),
+            1.0,
+            constants,
+        );
+        let critical_power = (power * CRITICAL_MULTIPLIER).floor();
+
+        let mut probabilities = Vec::<f64>::new();
+        let mut scratch_rate = 0.0;
+        let armor_roll = ArmorRoll::new(target.armor());
+        for (power, rate) in [
+            (power, hit_rate - critical_rate),
+            (critical_power, critical_rate),
+        ] {
+            for roll in &armor_roll.pmf {
+                let weight = rate * roll.probability;
+                let diff = (power - roll.defense).floor();
+                if diff <= 0.0 {
+                    scratch_rate += weight;
+                    continue;
+                }
+                let damage = diff as usize;
+                if probabilities.len() <= damage {
+                    probabilities.resize(damage + 1, 0.0);
+                }
+                probabilities[damage] += weight;
+            }
+        }
+        let damages = probabilities
+            .into_iter()
+            .enumerate()
+            .filter(|(_, p)| *p > 0.0)
+            .map(|(damage, p)| (damage as u16, p))
+            .collect();
+        Self {
+            damages,
+            scratch_rate,
+            miss_rate: 1.0 - hit_rate,
+        }
+    }
+
+    /// 残りHPが `hp` の攻撃対象に与えるダメージと確率の組を列挙する。ダメージは `hp` で打ち切り、外れた場合は 0 とする。
+    /// `scratch` には、残りHPが `hp` の場合のカスダメの分布を渡す。
+    pub fn damages<'a>(
+        &'a self,
+        hp: u16,
+        scratch: &'a [(u16, f64)],
+    ) -> impl Iterator<Item = (u16, f64)> + 'a {
+        let normal = self.damages.iter().map(move |(d, p)| ((*d).min(hp), *p));
+        let scratch = scratch
+            .iter()
+            .map(move |(d, p)| ((*d).min(hp), p * self.scratch_rate));
+        normal
+            .chain(scratch)
+            .chain(std::iter::once((0, self.miss_rate)))
+    }
+}
+
+/// 残りHPごとのカスダメの分布。`table[残りHP]` がダメージと確率の組の一覧を表す。
+pub struct ScratchDamage {
+    pub table: Vec<Vec<(u16, f64)>>,
+}
+
+impl ScratchDamage {
+    /// 残りHPが 0 から `max_hp` までの場合の分布を計算する。
+    pub fn new(max_hp: u16, constants: &FormulaConstants) -> Self {
+        let table = (0..=max_hp)
+            .map(|hp| scratch_damages(hp, constants))
+            .collect();
+        Self { table }
+    }
+}
+
+/// 残りHPが `hp` の場合のカスダメと確率の組を列挙する。
+fn scratch_damages(hp: u16, constants: &FormulaConstants) -> Vec<(u16, f64)> {
+    let scratch = &constants.scratch_damage;
+    // floor(HP × 乱数) は 0 から HP - 1 までの整数を等確率でとる
+    let rolls = hp.max(1);
+    let mut damages = Vec::<(u16, f64)>::new();
+    for m in 0..rolls {
+        let damage = (hp as f64 * scratch.base + m as f64 * scratch.random) as u16;
+        match damages.last_mut() {
+            Some((d, p)) if *d == damage => *p += 1.0 / rolls as f64,
+            _ => damages.push((damage, 1.0 / rolls as f64)),
+        }
+    }
+    damages
+}
@@ -0,0 +1,103 @@
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::{day_attack_hit_rate, DamageDistribution};
+use crate::battle::{self, Phase, ShipSnapshot};
+use crate::fleet::{EnemyFleet, Fleet, FleetLike};
+use crate::formula::FormulaConstants;
+
+/// 味方艦と敵艦の組ごとに、攻撃の期待値を解析的に求めてフェーズごとに並べた表。
+/// すべての艦が戦闘開始時の状態のまま攻撃するとみなし、それまでの被害による変化は考慮しない。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DamageTable {
+    /// 入力された敵編成のうち何番目か
+    enemy_index: usize,
+    /// 発生するフェーズごとの表
+    phases: Vec<PhaseDamageTable>,
+}
+
+/// 1フェーズ分の表。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PhaseDamageTable {
+    phase: Phase,
+    /// `cells[味方艦][敵艦]` (艦隊内の並び順)
+    cells: Vec<Vec<DamageCell>>,
+}
+
+/// 味方艦1隻が敵艦1隻を攻撃する場合の期待値。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DamageCell {
+    /// このフェーズで、味方艦がこの敵艦を攻撃対象に選ぶ確率
+    target_rate: f64,
+    /// 攻撃対象に選んだ場合に与えるダメージの期待値
+    expected_damage: f64,
+    /// 攻撃対象に選んだ場合の命中率。陣形の補正は考慮しない
+    hit_rate: f64,
+    /// 攻撃対象に選んだ場合に、1回の攻撃で撃沈する確率
+    one_shot_rate: f64,
+}
+
+impl DamageTable {
+    pub fn calculate(
+        friend: &Fleet,
+        enemy: &EnemyFleet,
+        enemy_index: usize,
+        constants: &FormulaConstants,
+    ) -> Self {
+        let cells = friend
+            .ships()
+            .iter()
+            .map(|actor| {
+                let actor_snapshot = ShipSnapshot::from(actor);
+                if battle::skip_reason(&Phase::FirstArtillery, actor, &actor_snapshot).is_some() {
+                    return vec![DamageCell::default(); enemy.ships().len()];
+                }
+                let can_target_installation =
+                    battle::can_target_installation(actor, &actor_snapshot);
+                let can_target = |target: &crate::fleet::Ship| {
+                    target.hp() > 0 && (can_target_installation || !target.is_installation())
+                };
+                let candidates = enemy.ships().iter().filter(|t| can_target(t)).count();
+                enemy
+                    .ships()
+                    .iter()
+                    .map(|target| {
+                        if !can_target(target) {
+                            return DamageCell::default();
+                        }
+                        let distribution = DamageDistribution::day_attack(actor, target, constants);
+                        DamageCell {
+                            target_rate: 1.0 / candidates as f64,
+                            expected_damage: distribution.mean(),
+                            hit_rate: day_attack_hit_rate(actor, target),
+                            one_shot_rate: distribution.at_least(target.hp()),
+                        }
+                    })
+                    .collect()
+            })
+            .collect::<Vec<_>>();
+
+        // 砲撃戦2巡目は、いずれかの艦隊に戦艦系がいる場合のみ発生する
+        let includes_battleship_class = friend
+            .ships()
+            .iter()
+            .chain(enemy.ships())
+            .any(|s| s.is_battleship_class());
+        let mut phases = vec![PhaseDamageTable {
+            phase: Phase::FirstArtillery,
+            cells: cells.clone(),
+        }];
+        if includes_battleship_class {
+            phases.push(PhaseDamageTable {
+                phase: Phase::SecondArtillery,
+                cells,
+            });
+        }
+        Self {
+            enemy_index,
+            phases,
+        }
+    }
+}
@@ -0,0 +1,13 @@
+//! モンテカルロ法を使わずに、攻撃1回のダメージ分布から解析的に求める指標。
+//! 多数の編成を比較する際に、シミュレーションを補う高速な目安として使う。
+mod armor_roll;
+pub use armor_roll::{ArmorRoll, DefenseProbability};
+
+mod damage_distribution;
+use damage_distribution::{day_attack_hit_rate, DamageDistribution};
+
+mod damage_table;
+pub use damage_table::{DamageCell, DamageTable, PhaseDamageTable};
+
+mod time_to_kill;
+pub use time_to_kill::TimeToKill;
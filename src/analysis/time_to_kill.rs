@@ -0,0 +1,169 @@
+use serde::{Deserialize, Serialize};
+
+use crate::analysis::damage_distribution::{AttackOutcomes, ScratchDamage};
+use crate::battle::{self, BattleDirection, Phase, ShipSnapshot};
+use crate::fleet::{EnemyFleet, Fleet, FleetLike, Ship};
+use crate::formula::FormulaConstants;
+
+/// 計算を打ち切る巡目の数。昼戦の砲撃戦は最大2巡だが、夜戦が必要かどうかの目安として長めにとる。
+const MAX_ROUNDS: u32 = 20;
+
+/// これより小さい確率の状態は無視する
+const EPSILON: f64 = 1e-12;
+
+/// 味方艦隊が指定された敵艦を撃沈するまでに要する攻撃回数の推定値。
+/// 味方艦は艦隊内の並び順に1回ずつ攻撃し、全艦が攻撃すると1巡とみなす。
+/// 各艦は戦闘開始時の状態のまま攻撃し、他の敵艦は撃沈されずに攻撃対象の候補に残り続けるとみなす。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct TimeToKill {
+    /// 撃沈するまでの味方艦の攻撃回数 (指定された敵艦以外への攻撃を含む) の期待値
+    expected_attacks: f64,
+    /// 攻撃回数の分散
+    attacks_variance: f64,
+    /// 撃沈するまでの巡目の数の期待値
+    expected_rounds: f64,
+    /// 巡目の数の分散
+    rounds_variance: f64,
+    /// 昼戦の砲撃戦 (1巡目と、戦艦系がいる場合は2巡目) のうちに撃沈できる確率
+    day_kill_rate: f64,
+    /// 打ち切りまでに撃沈できない確率。期待値と分散は、撃沈できた場合に限って計算する
+    unresolved_rate: f64,
+}
+
+/// 攻撃1回分の行動者の情報。
+struct Attacker {
+    /// 指定された敵艦を攻撃対象に選ぶ確率
+    target_rate: f64,
+    outcomes: AttackOutcomes,
+}
+
+impl TimeToKill {
+    /// 味方艦隊が `enemy` の `ship_index` 番目の艦を撃沈するまでの攻撃回数を推定する。
+    /// 艦が存在しない場合は `None` を返す。
+    pub fn calculate(
+        friend: &Fleet,
+        enemy: &EnemyFleet,
+        ship_index: usize,
+        constants: &FormulaConstants,
+    ) -> Option<Self> {
+        let target = enemy.ships().get(ship_index)?;
+        let actors = friend.ships().len() as u32;
+        let day_rounds = if friend
+            .ships()
+            .iter()
+            .chain(enemy.ships())
+            .any(|s| s.is_battleship_class())
+        {
+            2
+        } else {
+            1
+        };
+        let scratch = ScratchDamage::new(target.hp(), constants);
+
+        // 交戦形態は戦闘を通して変わらないため、交戦形態ごとに計算してから混ぜる
+        // kills[k] は k 回目 (0 始まり) の攻撃で撃沈する確率
+        let mut kills = vec![0.0; (MAX_ROUNDS * actors) as usize];
+        for (direction, direction_rate) in BattleDirection::distribution() {
+            let attackers = friend
+                .ships()
+                .iter()
+                .map(|actor| Self::attacker(actor, enemy, target, &direction, constants))
+                .collect::<Vec<_>>();
+            let direction_kills = Self::kill_probabilities(target.hp(), &attackers, &scratch);
+            for (k, p) in direction_kills.iter().enumerate() {
+                kills[k] += direction_rate * p;
+            }
+        }
+
+        let killed: f64 = kills.iter().sum();
+        let mut result = Self {
+            unresolved_rate: (1.0 - killed).max(0.0),
+            ..Self::default()
+        };
+        if killed <= 0.0 {
+            return Some(result);
+        }
+        let moments = |value: &dyn Fn(usize) -> f64| {
+            let mean = kills
+                .iter()
+                .enumerate()
+                .map(|(k, p)| value(k) * p)
+                .sum::<f64>()
+                / killed;
+            let variance = kills
+                .iter()
+                .enumerate()
+                .map(|(k, p)| (value(k) - mean).powi(2) * p)
+                .sum::<f64>()
+                / killed;
+            (mean, variance)
+        };
+        (result.expected_attacks, result.attacks_variance) = moments(&|k| (k + 1) as f64);
+        (result.expected_rounds, result.rounds_variance) =
+            moments(&|k| (k as u32 / actors + 1) as f64);
+        result.day_kill_rate = kills
+            .iter()
+            .take((day_rounds * actors) as usize)
+            .sum::<f64>()
+            .min(1.0);
+        Some(result)
+    }
+
+    /// 味方艦 `actor` が指定された敵艦を攻撃する場合の情報を作る。攻撃できない場合は `None` を返す。
+    fn attacker(
+        actor: &Ship,
+        enemy: &EnemyFleet,
+        target: &Ship,
+        direction: &BattleDirection,
+        constants: &FormulaConstants,
+    ) -> Option<Attacker> {
+        let actor_snapshot = ShipSnapshot::from(actor);
+        if battle::skip_reason(&Phase::FirstArtillery, actor, &actor_snapshot).is_some() {
+            return None;
+        }
+        let can_target_installation = battle::can_target_installation(actor, &actor_snapshot);
+        let can_target =
+            |ship: &Ship| ship.hp() > 0 && (can_target_installation || !ship.is_installation());
+        if !can_target(target) {
+            return None;
+        }
+        let candidates = enemy.ships().iter().filter(|s| can_target(s)).count();
+        Some(Attacker {
+            target_rate: 1.0 / candidates as f64,
+            outcomes: AttackOutcomes::day_attack(actor, target, direction, constants),
+        })
+    }
+
+    /// 残りHPの分布を攻撃ごとに更新し、各攻撃で撃沈する確率を求める。
+    fn kill_probabilities(
+        hp: u16,
+        attackers: &[Option<Attacker>],
+        scratch: &ScratchDamage,
+    ) -> Vec<f64> {
+        let mut kills = vec![0.0; MAX_ROUNDS as usize * attackers.len()];
+        // hps[h] は残りHPが h である確率
+        let mut hps = vec![0.0; hp as usize + 1];
+        hps[hp as usize] = 1.0;
+        for (k, kill) in kills.iter_mut().enumerate() {
+            let Some(attacker) = &attackers[k % attackers.len()] else {
+                continue;
+            };
+            let mut next = hps.clone();
+            for h in 1..hps.len() {
+                let p = hps[h] * attacker.target_rate;
+                if p < EPSILON {
+                    continue;
+                }
+                next[h] -= p;
+                for (damage, q) in attacker.outcomes.damages(h as u16, &scratch.table[h]) {
+                    next[h - damage as usize] += p * q;
+                }
+            }
+            *kill = next[0];
+            next[0] = 0.0;
+            hps = next;
+        }
+        kills
+    }
+}
@@ -0,0 +1,86 @@
+use serde::{Deserialize, Serialize};
+
+use crate::api_log::replay::{defense_range, replay, ObservedAttack};
+use crate::api_log::ApiBattle;
+use crate::battle::{FleetSide, Phase};
+use crate::formula::FormulaConstants;
+
+/// 観測された戦闘と計算式の予測を比較した結果。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplayReport {
+    /// 記録されていた攻撃の数
+    pub attacks: u32,
+    /// 予測と比較した攻撃の数。ミス、連撃やカットイン、かばう、轟沈ストッパーが関わる攻撃は比較しない。
+    pub compared: u32,
+    /// 観測されたダメージが予測の範囲から外れた攻撃
+    pub divergences: Vec<Divergence>,
+}
+
+/// 観測されたダメージが予測の範囲から外れた攻撃。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Divergence {
+    pub phase: Phase,
+    /// フェーズ内での攻撃の順番 (0 始まり)
+    pub attack_index: usize,
+    pub attacker_side: FleetSide,
+    pub attacker_index: usize,
+    pub target_index: usize,
+    pub critical: bool,
+    pub observed_damage: u16,
+    /// 予測されるダメージの最小値
+    pub predicted_min: u16,
+    /// 予測されるダメージの最大値
+    pub predicted_max: u16,
+}
+
+/// 観測された戦闘の砲撃戦を、`constants` の計算式で再計算して比較する。
+pub fn compare(battle: &ApiBattle, constants: &FormulaConstants) -> ReplayReport {
+    let mut report = ReplayReport::default();
+    report.attacks = replay(battle, constants, |attack| {
+        let (min, max) = predicted_range(attack, constants);
+        // 味方艦が沈む予測の場合は轟沈ストッパーが働くため比較しない
+        if attack.enemy_attacks && max >= attack.target_hp {
+            return;
+        }
+        report.compared += 1;
+        // 撃沈した場合、ダメージは残りHPまでしか記録されない
+        let sunk = attack.damage >= attack.target_hp;
+        if (attack.damage < min && !sunk) || attack.damage > max {
+            report.divergences.push(Divergence {
+                phase: attack.phase.clone(),
+                attack_index: attack.attack_index,
+                attacker_side: if attack.enemy_attacks {
+                    FleetSide::Enemy
+                } else {
+                    FleetSide::Friend
+                },
+                attacker_index: attack.attacker_index,
+                target_index: attack.target_index,
+                critical: attack.critical,
+                observed_damage: attack.damage.max(0) as u16,
+                predicted_min: min.max(0) as u16,
+                predicted_max: max.max(0) as u16,
+            });
+        }
+    });
+    report
+}
+
+/// 攻撃1回で与えるダメージの予測範囲を計算する。
+fn predicted_range(attack: &ObservedAttack, constants: &FormulaConstants) -> (i32, i32) {
+    let (min_defense, max_defense) = defense_range(attack.target_armor);
+    let min = (attack.power - max_defense).floor() as i32;
+    let max = (attack.power - min_defense).floor() as i32;
+    if max > 0 && min > 0 {
+        return (min, max);
+    }
+
+    // カスダメの範囲を含める
+    let hp = attack.target_hp as f64;
+    let scratch = &constants.scratch_damage;
+    let scratch_min = scratch.damage(hp, 0.0) as i32;
+    let scratch_max = scratch.damage(hp, 1.0 - f64::EPSILON) as i32;
+    (scratch_min.min(min.max(0)), scratch_max.max(max))
+}
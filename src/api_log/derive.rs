@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+
+use crate::api_log::replay::replay;
+use crate::api_log::ApiBattle;
+use crate::fleet::{EnemyFleet, Formation, Ship};
+use crate::formula::FormulaConstants;
+
+/// 同じマスで記録された複数の戦闘から、敵編成の候補と出現確率を推定する。
+/// 敵編成は艦船IDの並びと陣形が一致するものをまとめ、出現回数の多い順に並べる。
+///
+/// 各艦のステータスは API に記録された値を使い、装甲のみ味方の攻撃で観測されたダメージから推定する。
+/// 艦種と装備は API に含まれないため設定されない。
+pub fn derive_enemy_fleets(
+    battles: &[ApiBattle],
+    area: u16,
+    map: u16,
+    node: &str,
+    constants: &FormulaConstants,
+) -> Vec<EnemyFleet> {
+    let armor = armor_bounds(battles, constants);
+
+    let mut compositions: Vec<(&ApiBattle, u32)> = Vec::new();
+    for battle in battles {
+        match compositions
+            .iter_mut()
+            .find(|(b, _)| same_composition(b, battle))
+        {
+            Some((_, count)) => *count += 1,
+            None => compositions.push((battle, 1)),
+        }
+    }
+    compositions.sort_by_key(|(_, count)| std::cmp::Reverse(*count));
+
+    let master = crate::master::master_data();
+    compositions
+        .into_iter()
+        .map(|(battle, count)| {
+            let ships = battle
+                .api_ship_ke
+                .iter()
+                .zip(&battle.api_e_maxhps)
+                .zip(&battle.api_e_param)
+                .map(|((&id, &max_hp), param)| {
+                    let name = master
+                        .as_deref()
+                        .and_then(|m| m.ship_name(id, &crate::interface::Locale::Ja))
+                        .unwrap_or_default();
+                    let armor = armor
+                        .get(&id)
+                        .and_then(|bounds| bounds.estimate(param[3]))
+                        .unwrap_or(param[3]);
+                    Ship::abyssal(id, name, max_hp.max(0) as u16, *param, armor)
+                })
+                .collect();
+            EnemyFleet::new(
+                area,
+                map,
+                node.to_string(),
+                count as f64 / battles.len() as f64,
+                ships,
+                formation(battle.api_formation.get(1)),
+            )
+        })
+        .collect()
+}
+
+fn same_composition(a: &ApiBattle, b: &ApiBattle) -> bool {
+    a.api_ship_ke == b.api_ship_ke && a.api_formation.get(1) == b.api_formation.get(1)
+}
+
+/// API の陣形番号を陣形に変換する。連合艦隊の陣形は `None` とする。
+pub fn formation(formation: Option<&u8>) -> Option<Formation> {
+    match formation {
+        Some(1) => Some(Formation::LineAhead),
+        Some(2) => Some(Formation::DoubleLine),
+        Some(3) => Some(Formation::Diamond),
+        Some(4) => Some(Formation::Echelon),
+        Some(5) => Some(Formation::LineAbreast),
+        Some(6) => Some(Formation::Vanguard),
+        _ => None,
+    }
+}
+
+/// 観測されたダメージと矛盾しない装甲の範囲。
+#[derive(Debug, Clone)]
+struct ArmorBounds {
+    min: f64,
+    max: f64,
+}
+
+impl ArmorBounds {
+    /// 範囲内で API に記録された装甲に最も近い値を返す。観測が矛盾している場合は `None` を返す。
+    /// API の装甲は装備を含まないため、実際の装甲はそれ以上になる。
+    fn estimate(&self, recorded: u16) -> Option<u16> {
+        let min = self.min.ceil();
+        let max = self.max.floor();
+        if min > max {
+            return None;
+        }
+        Some((recorded as f64).clamp(min, max) as u16)
+    }
+}
+
+/// 味方の攻撃で観測されたダメージから、敵艦の艦船IDごとに装甲の範囲を推定する。
+fn armor_bounds(battles: &[ApiBattle], constants: &FormulaConstants) -> HashMap<u16, ArmorBounds> {
+    let mut bounds: HashMap<u16, ArmorBounds> = HashMap::new();
+    for battle in battles {
+        replay(battle, constants, |attack| {
+            let Some(&id) = battle.api_ship_ke.get(attack.target_index) else {
+                return;
+            };
+            // 撃沈した場合とカスダメの可能性がある場合は、ダメージから装甲を逆算できない
+            let hp = attack.target_hp as f64;
+            let scratch_max = constants.scratch_damage.damage(hp, 1.0 - f64::EPSILON);
+            if attack.enemy_attacks
+                || attack.damage >= attack.target_hp
+                || attack.damage as f64 <= scratch_max
+            {
+                return;
+            }
+            // ダメージ d = floor(攻撃力 - 防御力) より、装甲 a は
+            // 0.7a <= 攻撃力 - d かつ 1.3a - 0.6 > 攻撃力 - d - 1 を満たす
+            let margin = attack.power - attack.damage as f64;
+            let observed = ArmorBounds {
+                min: (margin - 0.4) / 1.3,
+                max: margin / 0.7,
+            };
+            bounds
+                .entry(id)
+                .and_modify(|b| {
+                    b.min = b.min.max(observed.min);
+                    b.max = b.max.min(observed.max);
+                })
+                .or_insert(observed);
+        });
+    }
+    bounds
+}
@@ -0,0 +1,76 @@
+//! 実際のゲームの戦闘 API (`api_req_sortie/battle`) のレスポンスの読み込み。
+//! 観測された戦闘をシミュレーターの計算式で再計算し、予測と食い違う攻撃を洗い出すために使う。
+use serde::{Deserialize, Deserializer};
+
+mod compare;
+pub use compare::{compare, Divergence, ReplayReport};
+
+mod derive;
+pub use derive::derive_enemy_fleets;
+
+mod replay;
+
+/// 戦闘 API のレスポンスのうち、再計算に使う項目。
+/// 配列のインデックスは、2017 年以降の形式 (先頭の -1 を含まない 0 始まり) を前提とする。
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiBattle {
+    /// `[味方陣形, 敵陣形, 交戦形態]`。陣形は文字列で返されることがある。
+    #[serde(deserialize_with = "numbers")]
+    pub api_formation: Vec<u8>,
+    pub api_f_nowhps: Vec<i32>,
+    pub api_f_maxhps: Vec<i32>,
+    pub api_e_nowhps: Vec<i32>,
+    pub api_e_maxhps: Vec<i32>,
+    /// 敵艦の艦船ID
+    pub api_ship_ke: Vec<u16>,
+    /// 味方艦の `[火力, 雷装, 対空, 装甲]` (装備込み)
+    #[serde(rename = "api_fParam")]
+    pub api_f_param: Vec<[u16; 4]>,
+    /// 敵艦の `[火力, 雷装, 対空, 装甲]` (装備込み)
+    #[serde(rename = "api_eParam")]
+    pub api_e_param: Vec<[u16; 4]>,
+    #[serde(default)]
+    pub api_hougeki1: Option<ApiHougeki>,
+    #[serde(default)]
+    pub api_hougeki2: Option<ApiHougeki>,
+}
+
+/// 砲撃戦1回分の攻撃の一覧。各配列の同じインデックスが1回の攻撃を表す。
+#[derive(Deserialize, Debug, Clone)]
+pub struct ApiHougeki {
+    /// 攻撃側。0 が味方、1 が敵。
+    pub api_at_eflag: Vec<u8>,
+    pub api_at_list: Vec<usize>,
+    /// 攻撃種別。0 が通常攻撃で、それ以外は連撃やカットインなど。
+    pub api_at_type: Vec<u8>,
+    pub api_df_list: Vec<Vec<usize>>,
+    /// 命中判定。0 がミス、1 が命中、2 がクリティカル。
+    pub api_cl_list: Vec<Vec<u8>>,
+    /// ダメージ。小数部が 0.1 の場合は、旗艦がかばわれたことを表す。
+    pub api_damage: Vec<Vec<f64>>,
+}
+
+/// 数値または数値の文字列からなる配列をデシリアライズする。
+fn numbers<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+    let values = Vec::<serde_json::Value>::deserialize(deserializer)?;
+    values
+        .iter()
+        .map(|v| match v {
+            serde_json::Value::Number(n) => n.as_u64().map(|n| n as u8),
+            serde_json::Value::String(s) => s.parse().ok(),
+            _ => None,
+        })
+        .map(|v| v.ok_or_else(|| serde::de::Error::custom("expected a number")))
+        .collect()
+}
+
+/// 戦闘 API のレスポンスの文字列を読み込む。
+/// 先頭の `svdata=` や、`api_data` で包まれた形式にも対応する。
+pub fn parse(text: &str) -> Result<ApiBattle, String> {
+    let text = text.trim();
+    let text = text.strip_prefix("svdata=").unwrap_or(text);
+    let value: serde_json::Value =
+        serde_json::from_str(text).map_err(|err| format!("Invalid JSON: {}", err))?;
+    let data = value.get("api_data").cloned().unwrap_or(value);
+    serde_json::from_value(data).map_err(|err| format!("Unexpected battle API format: {}", err))
+}
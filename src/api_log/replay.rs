@@ -0,0 +1,197 @@
+use crate::analysis::ArmorRoll;
+use crate::api_log::derive::formation;
+use crate::api_log::{ApiBattle, ApiHougeki};
+use crate::battle::{BattleDirection, DamagedLevel, Phase};
+use crate::fleet::Formation;
+use crate::formula::FormulaConstants;
+
+/// 再計算の対象となった攻撃1回分の情報。
+/// ミス、連撃やカットイン、かばう攻撃は対象外とする。
+pub struct ObservedAttack {
+    pub phase: Phase,
+    /// フェーズ内での攻撃の順番 (0 始まり)
+    pub attack_index: usize,
+    pub enemy_attacks: bool,
+    pub attacker_index: usize,
+    pub target_index: usize,
+    pub critical: bool,
+    /// キャップ適用後の攻撃力 (クリティカル補正込み)
+    pub power: f64,
+    /// 攻撃を受ける前の攻撃対象の残りHP
+    pub target_hp: i32,
+    /// API に記録された攻撃対象の装甲
+    pub target_armor: u16,
+    pub damage: i32,
+}
+
+/// 艦のHPとステータス。攻撃のたびにHPを更新する。
+struct ReplayShip {
+    hp: i32,
+    max_hp: i32,
+    firepower: u16,
+    armor: u16,
+}
+
+/// 観測された戦闘の砲撃戦を先頭から再生し、再計算の対象となる攻撃ごとに `on_attack` を呼び出す。
+/// 攻撃力は `火力 + 5` に陣形・交戦形態・損傷状態の補正とキャップを適用したもので、
+/// 空母の攻撃力式や装備改修などは考慮しない。
+/// 戻り値は記録されていたすべての攻撃の数。
+pub fn replay(
+    battle: &ApiBattle,
+    constants: &FormulaConstants,
+    mut on_attack: impl FnMut(&ObservedAttack),
+) -> u32 {
+    let direction = match battle.api_formation.get(2) {
+        Some(2) => BattleDirection::Against,
+        Some(3) => BattleDirection::TAdvantage,
+        Some(4) => BattleDirection::TDisadvantage,
+        _ => BattleDirection::Same,
+    };
+    let fleet = |now: &[i32], max: &[i32], params: &[[u16; 4]]| {
+        now.iter()
+            .zip(max)
+            .zip(params)
+            .map(|((hp, max_hp), param)| ReplayShip {
+                hp: *hp,
+                max_hp: *max_hp,
+                firepower: param[0],
+                armor: param[3],
+            })
+            .collect::<Vec<_>>()
+    };
+    let mut friend = fleet(
+        &battle.api_f_nowhps,
+        &battle.api_f_maxhps,
+        &battle.api_f_param,
+    );
+    let mut enemy = fleet(
+        &battle.api_e_nowhps,
+        &battle.api_e_maxhps,
+        &battle.api_e_param,
+    );
+    let formations = [
+        formation(battle.api_formation.first()),
+        formation(battle.api_formation.get(1)),
+    ];
+
+    let mut attacks = 0;
+    for (phase, hougeki) in [
+        (Phase::FirstArtillery, &battle.api_hougeki1),
+        (Phase::SecondArtillery, &battle.api_hougeki2),
+    ] {
+        if let Some(hougeki) = hougeki {
+            attacks += hougeki.api_at_list.len() as u32;
+            let context = Context {
+                phase,
+                direction: &direction,
+                constants,
+                formations: &formations,
+            };
+            replay_hougeki(&context, hougeki, &mut friend, &mut enemy, &mut on_attack);
+        }
+    }
+    attacks
+}
+
+/// 砲撃戦1回分の再生中に変化しない値。
+struct Context<'a> {
+    phase: Phase,
+    direction: &'a BattleDirection,
+    constants: &'a FormulaConstants,
+    /// 味方、敵の順の陣形
+    formations: &'a [Option<Formation>; 2],
+}
+
+fn replay_hougeki(
+    context: &Context,
+    hougeki: &ApiHougeki,
+    friend: &mut [ReplayShip],
+    enemy: &mut [ReplayShip],
+    on_attack: &mut impl FnMut(&ObservedAttack),
+) {
+    for (i, &attacker_idx) in hougeki.api_at_list.iter().enumerate() {
+        let enemy_attacks = hougeki.api_at_eflag.get(i) == Some(&1);
+        let (attackers, targets) = if enemy_attacks {
+            (&*enemy, &mut *friend)
+        } else {
+            (&*friend, &mut *enemy)
+        };
+        let (Some(attacker), Some(&target_idx), Some(&damage), Some(&hit)) = (
+            attackers.get(attacker_idx),
+            hougeki.api_df_list.get(i).and_then(|l| l.first()),
+            hougeki.api_damage.get(i).and_then(|l| l.first()),
+            hougeki.api_cl_list.get(i).and_then(|l| l.first()),
+        ) else {
+            continue;
+        };
+        let Some(target) = targets.get_mut(target_idx) else {
+            continue;
+        };
+
+        // かばう場合は小数部に 0.1 が付く
+        let protected = damage.fract() > 0.05;
+        let observed = damage.floor() as i32;
+        let single_attack =
+            hougeki.api_at_type.get(i).is_none_or(|t| *t == 0) && hougeki.api_damage[i].len() == 1;
+
+        if single_attack && !protected && hit != 0 {
+            let critical = hit == 2;
+            let formation_factor = formation_factor(
+                context.formations[enemy_attacks as usize].as_ref(),
+                attacker_idx,
+                attackers.len(),
+                context.constants,
+            );
+            on_attack(&ObservedAttack {
+                phase: context.phase.clone(),
+                attack_index: i,
+                enemy_attacks,
+                attacker_index: attacker_idx,
+                target_index: target_idx,
+                critical,
+                power: power(attacker, context, formation_factor, critical),
+                target_hp: target.hp,
+                target_armor: target.armor,
+                damage: observed,
+            });
+        }
+
+        // 以降の攻撃の損傷状態のために、観測されたダメージを反映する
+        target.hp = (target.hp - observed).max(0);
+    }
+}
+
+/// 砲撃戦の攻撃力を計算する。
+fn power(attacker: &ReplayShip, context: &Context, formation_factor: f64, critical: bool) -> f64 {
+    let constants = context.constants;
+    let damaged_level = DamagedLevel::from_hp(attacker.hp.max(0) as u16, attacker.max_hp as u16);
+    let precap = (attacker.firepower as f64 + 5.0)
+        * formation_factor
+        * constants.direction_factor(context.direction)
+        * constants.damaged_level_factor(&damaged_level);
+    let cap = constants.day_artillery_cap;
+    let power = precap.min(cap) + (precap - cap).max(0.0).sqrt().floor();
+    if critical {
+        (power * crate::battle::CRITICAL_MULTIPLIER).floor()
+    } else {
+        power
+    }
+}
+
+/// 攻撃艦の位置に応じた砲撃戦の陣形補正。連合艦隊の陣形は考慮しない。
+fn formation_factor(
+    formation: Option<&Formation>,
+    ship_idx: usize,
+    fleet_len: usize,
+    constants: &FormulaConstants,
+) -> f64 {
+    formation.map_or(1.0, |f| {
+        constants.formation_factor(f, ship_idx, fleet_len).shelling
+    })
+}
+
+/// 防御力 `装甲 × 0.7 + floor(装甲 × 乱数) × 0.6` の最小値と最大値。乱数は [0, 1)。
+pub fn defense_range(armor: u16) -> (f64, f64) {
+    let roll = ArmorRoll::new(armor);
+    (roll.min, roll.max)
+}
@@ -0,0 +1,126 @@
+use crate::{aggregate, battle, diagnostics, interface, rng};
+
+/// 出力の形式ごとの、途中までの結果。
+enum Partial {
+    Reports(Vec<interface::BattleReport>, Box<interface::RunConfig>),
+    Columns(Box<interface::ColumnarReports>),
+    Summary(Box<aggregate::Aggregator>),
+}
+
+/// 戦闘を一定回数ずつ進められるシミュレーション。
+/// 長時間の実行の途中で制御を呼び出し側に返せるため、ブラウザのメインスレッドで
+/// 画面の更新を挟みながら実行したり、進捗を表示したりできる。
+/// 結果は、同じ入力で `run_simulation` を呼び出した場合と同じ形式になる。
+pub struct BatchSimulation {
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    options: interface::SimulationOptions,
+    input_digest: String,
+    count: u32,
+    completed: u32,
+    partial: Partial,
+    rng: rng::SimRng,
+}
+
+impl BatchSimulation {
+    /// 入力を検証・補完し、`count` 回の戦闘を行う準備をする。
+    pub fn new(
+        mut friend: interface::Fleet,
+        mut enemy: Vec<interface::EnemyFleet>,
+        count: u32,
+        options: &interface::SimulationOptions,
+    ) -> Self {
+        let input_digest = diagnostics::input_digest(&(&friend, &enemy, options));
+        diagnostics::begin(input_digest.clone());
+        let mut prepared = options.clone();
+        let applied_defaults = crate::prepare_input(&mut friend, &mut enemy, &mut prepared);
+        diagnostics::finish();
+        let count = crate::battle_count(&enemy, count);
+
+        let seed = rng::resolve_seed(options.seed);
+        let partial = if options.aggregate {
+            Partial::Summary(Box::new(aggregate::Aggregator::new(
+                options,
+                applied_defaults,
+            )))
+        } else if options.columnar {
+            Partial::Columns(Box::default())
+        } else {
+            let config = interface::RunConfig {
+                input_digest: input_digest.clone(),
+                applied_defaults,
+                options: options.clone(),
+                seed,
+                formula_version: battle::FORMULA_VERSION,
+            };
+            Partial::Reports(Vec::new(), Box::new(config))
+        };
+        Self {
+            friend,
+            enemy,
+            options: prepared,
+            input_digest,
+            count,
+            completed: 0,
+            partial,
+            rng: rng::from_seed(Some(seed)),
+        }
+    }
+
+    /// 最大 `size` 回の戦闘を行う。すべての戦闘を終えた場合は `true` を返す。
+    pub fn run_batch(&mut self, size: u32) -> bool {
+        diagnostics::begin(self.input_digest.clone());
+        let end = self.completed.saturating_add(size).min(self.count);
+        for i in self.completed..end {
+            diagnostics::set_iteration(i);
+            let (enemy_index, selected_enemy) =
+                crate::select_random_enemy(&self.enemy, &mut self.rng);
+            let seed = rng::next_seed(&mut self.rng);
+            let battle = crate::battle_once(&self.friend, selected_enemy, &self.options, seed);
+            match &mut self.partial {
+                Partial::Reports(reports, _) => {
+                    reports.push(battle.into_battle_report(enemy_index, self.options.report_detail))
+                }
+                Partial::Columns(columns) => columns.record(&battle, enemy_index),
+                Partial::Summary(aggregator) => aggregator.record(&battle, enemy_index),
+            }
+        }
+        diagnostics::finish();
+        self.completed = end;
+        self.is_finished()
+    }
+
+    /// 終えた戦闘の回数。
+    pub fn completed(&self) -> u32 {
+        self.completed
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.completed >= self.count
+    }
+
+    /// これまでの結果を `options.schema_version` の形式で返す。
+    /// すべての戦闘を終える前に呼び出した場合は、それまでに終えた戦闘だけの結果になる。
+    /// 戦闘ごとの結果の設定の控えは、`{ schemaVersion, reports }` で包むスキーマバージョン 2 以降でのみ含める。
+    pub fn finish(self) -> interface::SimulationOutput {
+        let schema_version = self
+            .options
+            .schema_version
+            .unwrap_or(interface::OLDEST_SCHEMA_VERSION);
+        let (output, config) = match self.partial {
+            Partial::Reports(reports, config) => {
+                (interface::SimulationOutput::Reports(reports), Some(config))
+            }
+            Partial::Columns(columns) => (interface::SimulationOutput::Columns(columns), None),
+            Partial::Summary(aggregator) => (
+                interface::SimulationOutput::Summary(Box::new(aggregator.summary())),
+                None,
+            ),
+        };
+        let mut output = output.into_schema(schema_version);
+        if let interface::SimulationOutput::Versioned(versioned) = &mut output {
+            versioned.config = config;
+        }
+        output
+    }
+}
@@ -0,0 +1,49 @@
+use crate::battle::{DamagedLevel, Phase, ShipSnapshot};
+use crate::fleet::Ship;
+
+/// 損傷状態による行動制限を判定し、`phase` で `actor` が攻撃できない場合はその理由を返す。
+/// 中破・大破による攻撃力の低下は行動の可否とは別に、`FormulaConstants::damaged_level_factor` で扱う。
+pub fn skip_reason(
+    phase: &Phase,
+    actor: &Ship,
+    actor_snapshot: &ShipSnapshot,
+) -> Option<&'static str> {
+    let damaged_level = actor.damaged_level(actor_snapshot);
+    if damaged_level == DamagedLevel::Sunk {
+        return Some("Sunk");
+    }
+    let carrier_attack = actor.has_attack_aircraft(actor_snapshot);
+    match phase {
+        // 空母系は中破以上で艦載機を発艦できない
+        Phase::AirCombat if carrier_attack && damaged_level >= DamagedLevel::Moderate => {
+            Some("Flight Deck is too Damaged")
+        }
+        Phase::FirstArtillery | Phase::SecondArtillery => {
+            // 潜水艦は砲撃戦に参加しない
+            if actor.is_submarine() {
+                Some("Submarine")
+            } else if damaged_level >= DamagedLevel::Heavy {
+                Some("Heavily Damaged")
+            } else if carrier_attack && damaged_level >= DamagedLevel::Moderate {
+                Some("Flight Deck is too Damaged")
+            } else {
+                None
+            }
+        }
+        Phase::OpeningTorpedo | Phase::Night if damaged_level >= DamagedLevel::Heavy => {
+            Some("Heavily Damaged")
+        }
+        // 空母系は夜間機を搭載している場合のみ夜戦で攻撃でき、中破以上では発艦できない
+        Phase::Night if actor.is_carrier_class() && !actor.has_night_aircraft(actor_snapshot) => {
+            Some("No Night Attack Capability")
+        }
+        Phase::Night if actor.is_carrier_class() && damaged_level >= DamagedLevel::Moderate => {
+            Some("Flight Deck is too Damaged")
+        }
+        // 閉幕雷撃は中破以上で行えない
+        Phase::ClosingTorpedo if damaged_level >= DamagedLevel::Moderate => {
+            Some("Too Damaged for Torpedo")
+        }
+        _ => None,
+    }
+}
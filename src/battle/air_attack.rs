@@ -0,0 +1,59 @@
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::{luck, ShipSnapshot};
+use crate::fleet::{EquipCategory, Equipment, Ship};
+use crate::formula::FormulaConstants;
+
+/// 航空攻撃を行う装備の位置を列挙する。航空攻撃に参加する航空機のうち、残りの搭載数が 1 以上のスロットが対象。
+pub fn strike_slots(actor: &Ship, actor_snapshot: &ShipSnapshot) -> Vec<usize> {
+    actor
+        .equips()
+        .iter()
+        .zip(actor_snapshot.slots())
+        .enumerate()
+        .filter(|(_, (e, count))| e.category().participates_in_airstrike() && **count > 0)
+        .map(|(i, _)| i)
+        .collect()
+}
+
+/// 航空攻撃の種別倍率を決めるための乱数が必要な装備かどうかを判定する。
+pub fn uses_multiplier_roll(equipment: &Equipment) -> bool {
+    equipment.category() == EquipCategory::CarrierBasedTorpedoBomber
+}
+
+/// 航空攻撃で `target` を攻撃対象に選べるかどうかを判定する。潜水艦は航空攻撃を受けない。
+pub fn can_target(target: &Ship) -> bool {
+    !target.is_submarine()
+}
+
+/// 対地攻撃できない航空機による航空攻撃で `target` を攻撃対象に選べるかどうかを判定する。
+pub fn can_target_except_installation(target: &Ship) -> bool {
+    can_target(target) && !target.is_installation()
+}
+
+/// 航空攻撃での `actor` の命中項を計算する。搭載する航空機の命中も装備の命中として含める。
+pub fn accuracy(actor: &Ship) -> f64 {
+    let aiming = actor.equipment_aiming() as f64
+        + actor.improvement_bonus(Equipment::accuracy_improvement_bonus);
+    luck::accuracy(luck::AIR_STRIKE_ACCURACY_BASE, actor.luck(), aiming, 1.0)
+}
+
+/// 航空攻撃での1スロット分のキャップ適用後の攻撃力を計算する。
+/// 基本攻撃力は `種別倍率 × (雷装または爆装 × √搭載数 + 25)` で、艦上攻撃機は `[0, 1)` の乱数 `r` が 0.5 未満なら 0.8 倍、それ以外は 1.5 倍となる。
+/// 交戦形態と損傷状態の補正はかからない。クリティカル補正は含まない。
+pub fn power<N: Scalar>(
+    equipment: &Equipment,
+    count: u16,
+    r: f64,
+    actor_snapshot: &ShipSnapshot,
+    constants: &FormulaConstants,
+) -> N {
+    let (stat, multiplier) = if uses_multiplier_roll(equipment) {
+        (equipment.torpedo(), if r < 0.5 { 0.8 } else { 1.5 })
+    } else {
+        (equipment.bombing(), 1.0)
+    };
+    let basic = N::from_int(stat as i64) * N::from_int(count as i64).sqrt() + N::from_int(25);
+    let precap = basic * N::from_f64(multiplier);
+    apply_cap(precap, N::from_f64(constants.air_strike_cap))
+        * N::from_f64(actor_snapshot.ammo_factor())
+}
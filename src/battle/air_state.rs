@@ -0,0 +1,110 @@
+use serde::{Deserialize, Serialize};
+
+/// 航空戦の制空状態。味方艦隊から見た状態を表す。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AirState {
+    /// 制空権確保 (AS+)
+    AirSupremacy,
+    /// 航空優勢 (AS)
+    AirSuperiority,
+    /// 航空均衡
+    AirParity,
+    /// 航空劣勢
+    AirDenial,
+    /// 制空権喪失
+    AirIncapability,
+}
+
+impl AirState {
+    /// 味方と敵の制空値から制空状態を決定する。両艦隊とも制空値が 0 の場合は航空均衡とする。
+    pub fn from_fighter_power(friend: u32, enemy: u32) -> Self {
+        let (friend, enemy) = (friend as u64, enemy as u64);
+        if friend == 0 && enemy == 0 {
+            AirState::AirParity
+        } else if friend >= enemy * 3 {
+            AirState::AirSupremacy
+        } else if friend * 2 >= enemy * 3 {
+            AirState::AirSuperiority
+        } else if friend * 3 > enemy * 2 {
+            AirState::AirParity
+        } else if friend * 3 > enemy {
+            AirState::AirDenial
+        } else {
+            AirState::AirIncapability
+        }
+    }
+
+    /// 相手の艦隊から見た制空状態。
+    pub fn reversed(&self) -> Self {
+        match self {
+            AirState::AirSupremacy => AirState::AirIncapability,
+            AirState::AirSuperiority => AirState::AirDenial,
+            AirState::AirParity => AirState::AirParity,
+            AirState::AirDenial => AirState::AirSuperiority,
+            AirState::AirIncapability => AirState::AirSupremacy,
+        }
+    }
+
+    /// 航空優勢以上かどうか。
+    pub fn is_superiority_or_better(&self) -> bool {
+        matches!(self, AirState::AirSupremacy | AirState::AirSuperiority)
+    }
+
+    /// 航空戦 stage 1 の撃墜数の計算に使う整数乱数の上限。
+    /// 撃墜数は `floor(搭載数 × (0.65 × x + 0.35 × y) / 10)` (x, y は 0 以上この値以下の整数乱数) で求める。
+    pub fn stage1_loss_step(&self) -> u16 {
+        match self {
+            AirState::AirSupremacy => 1,
+            AirState::AirSuperiority => 3,
+            AirState::AirParity => 5,
+            AirState::AirDenial => 7,
+            AirState::AirIncapability => 10,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn thresholds() {
+        assert_eq!(AirState::from_fighter_power(0, 0), AirState::AirParity);
+        assert_eq!(AirState::from_fighter_power(1, 0), AirState::AirSupremacy);
+        assert_eq!(
+            AirState::from_fighter_power(300, 100),
+            AirState::AirSupremacy
+        );
+        assert_eq!(
+            AirState::from_fighter_power(299, 100),
+            AirState::AirSuperiority
+        );
+        assert_eq!(
+            AirState::from_fighter_power(150, 100),
+            AirState::AirSuperiority
+        );
+        assert_eq!(AirState::from_fighter_power(149, 100), AirState::AirParity);
+        assert_eq!(AirState::from_fighter_power(67, 100), AirState::AirParity);
+        assert_eq!(AirState::from_fighter_power(66, 100), AirState::AirDenial);
+        assert_eq!(AirState::from_fighter_power(34, 100), AirState::AirDenial);
+        assert_eq!(
+            AirState::from_fighter_power(33, 100),
+            AirState::AirIncapability
+        );
+        assert_eq!(
+            AirState::from_fighter_power(0, 1),
+            AirState::AirIncapability
+        );
+    }
+
+    #[test]
+    fn reversed_matches_swapped_fighter_power() {
+        for (friend, enemy) in [(300, 100), (150, 100), (100, 100), (66, 100), (10, 100)] {
+            assert_eq!(
+                AirState::from_fighter_power(friend, enemy).reversed(),
+                AirState::from_fighter_power(enemy, friend)
+            );
+        }
+    }
+}
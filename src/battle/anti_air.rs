@@ -0,0 +1,126 @@
+use crate::battle::AntiAirCutIn;
+use crate::fleet::{EquipCategory, Equipment, Ship};
+
+/// 割合撃墜の係数。
+const PROPORTIONAL_FACTOR: f64 = 0.02 * 0.25;
+/// 固定撃墜の係数。
+const FIXED_FACTOR: f64 = 0.0625;
+
+/// 加重対空値を計算する際の装備の係数。
+fn ship_weight(equipment: &Equipment) -> f64 {
+    match equipment.category() {
+        EquipCategory::AntiAircraftGun => 6.0,
+        EquipCategory::AntiAircraftFireDirector => 4.0,
+        _ if equipment.is_high_angle_gun() => 4.0,
+        category if category.is_radar() => 3.0,
+        _ => 0.0,
+    }
+}
+
+/// 艦隊防空値を計算する際の装備の係数。
+fn fleet_weight(equipment: &Equipment) -> f64 {
+    match equipment.category() {
+        EquipCategory::AntiAircraftFireDirector => 0.35,
+        EquipCategory::AntiAircraftShell => 0.6,
+        _ if equipment.is_high_angle_gun() => 0.35,
+        category if category.is_radar() => 0.4,
+        _ => 0.2,
+    }
+}
+
+/// 艦の加重対空値を計算する。`素の対空 + Σ(装備の対空 × 係数)` で、深海棲艦は素の対空の平方根を使う。
+pub fn weighted_anti_aircraft(ship: &Ship) -> f64 {
+    let naked = ship.naked_anti_aircraft() as f64;
+    let naked = if ship.is_abyssal() {
+        naked.sqrt()
+    } else {
+        naked
+    };
+    let equipment: f64 = ship
+        .equips()
+        .iter()
+        .map(|e| e.anti_aircraft() as f64 * ship_weight(e))
+        .sum();
+    naked + equipment
+}
+
+/// 艦隊防空値を計算する。`floor(陣形補正 × Σ floor(Σ(装備の対空 × 係数)))` で、`ships` は生存艦。
+pub fn fleet_anti_aircraft<'a>(ships: impl Iterator<Item = &'a Ship>, formation: f64) -> f64 {
+    let total: f64 = ships
+        .map(|ship| {
+            ship.equips()
+                .iter()
+                .map(|e| e.anti_aircraft() as f64 * fleet_weight(e))
+                .sum::<f64>()
+                .floor()
+        })
+        .sum();
+    (formation * total).floor()
+}
+
+/// 対空砲火 (stage 2) による撃墜数を計算する。
+/// 割合撃墜は `floor(搭載数 × 加重対空 × 0.02 × 0.25)`、固定撃墜は `floor((加重対空 + 艦隊防空) × 0.0625 × 対空カットイン倍率)` で、
+/// それぞれ 1/2 の確率で発生する (`proportional`, `fixed`)。対空カットインの追加撃墜数は常に加算する。
+pub fn stage2_loss(
+    count: u16,
+    weighted: f64,
+    fleet: f64,
+    cut_in: Option<AntiAirCutIn>,
+    proportional: bool,
+    fixed: bool,
+) -> u16 {
+    let mut loss = 0;
+    if proportional {
+        loss += (count as f64 * weighted * PROPORTIONAL_FACTOR).floor() as u16;
+    }
+    if fixed {
+        let multiplier = cut_in.map_or(1.0, |c| c.multiplier());
+        loss += ((weighted + fleet) * FIXED_FACTOR * multiplier).floor() as u16;
+    }
+    loss += cut_in.map_or(0, |c| c.bonus());
+    loss.min(count)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn equip(type_id: u16, icon: u16, anti_aircraft: u16) -> serde_json::Value {
+        serde_json::json!({
+            "id": 1, "equipTypeId": [0, 0, type_id, icon, 0],
+            "status": { "antiAircraft": anti_aircraft }
+        })
+    }
+
+    #[test]
+    fn fleet_anti_aircraft_floors_per_ship() {
+        // 高角砲 8 × 0.35 + 電探 5 × 0.4 = 4.8 → 4、機銃 9 × 0.2 = 1.8 → 1
+        let ships = [
+            Ship::test_builder()
+                .equips([equip(1, 16, 8), equip(12, 11, 5)])
+                .build(),
+            Ship::test_builder().equips([equip(21, 15, 9)]).build(),
+        ];
+        assert_eq!(fleet_anti_aircraft(ships.iter(), 1.0), 5.0);
+        assert_eq!(fleet_anti_aircraft(ships.iter(), 1.6), 8.0);
+        assert_eq!(fleet_anti_aircraft(ships.iter(), 0.5), 2.0);
+        assert_eq!(fleet_anti_aircraft(std::iter::empty(), 1.0), 0.0);
+    }
+
+    #[test]
+    fn stage2_loss_components() {
+        // 割合撃墜 floor(20 × 100 × 0.005) = 10、固定撃墜 floor(120 × 0.0625) = 7
+        assert_eq!(stage2_loss(20, 100.0, 20.0, None, true, false), 10);
+        assert_eq!(stage2_loss(20, 100.0, 20.0, None, false, true), 7);
+        assert_eq!(stage2_loss(20, 100.0, 20.0, None, true, true), 17);
+        assert_eq!(stage2_loss(20, 100.0, 20.0, None, false, false), 0);
+
+        // 対空カットイン (5種): 固定撃墜 floor(120 × 0.0625 × 1.55) = 11、追加撃墜は常に 4
+        let cut_in = Some(AntiAirCutIn::DoubleDirectorHighAngleRadar);
+        assert_eq!(stage2_loss(20, 100.0, 20.0, cut_in, false, true), 15);
+        assert_eq!(stage2_loss(20, 100.0, 20.0, cut_in, false, false), 4);
+
+        // 撃墜数は搭載数を超えない
+        assert_eq!(stage2_loss(5, 100.0, 20.0, cut_in, true, true), 5);
+    }
+}
@@ -0,0 +1,195 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fleet::{EquipCategory, Equipment, Ship};
+
+/// 対空カットインの種別。括弧内はゲーム内の種別番号。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AntiAirCutIn {
+    /// 秋月型: 高角砲 × 2 + 対空電探 (1種)
+    AkizukiDoubleHighAngleRadar,
+    /// 秋月型: 高角砲 + 対空電探 (2種)
+    AkizukiHighAngleRadar,
+    /// 秋月型: 高角砲 × 2 (3種)
+    AkizukiDoubleHighAngle,
+    /// 大口径主砲 + 対空強化弾 + 高射装置 + 対空電探 (4種)
+    LargeGunShellDirectorRadar,
+    /// 高射装置付き高角砲 × 2 + 対空電探 (5種)
+    DoubleDirectorHighAngleRadar,
+    /// 大口径主砲 + 対空強化弾 + 高射装置 (6種)
+    LargeGunShellDirector,
+    /// 高角砲 + 高射装置 + 対空電探 (7種)
+    HighAngleDirectorRadar,
+    /// 高射装置付き高角砲 + 対空電探 (8種)
+    DirectorHighAngleRadar,
+    /// 高角砲 + 高射装置 (9種)
+    HighAngleDirector,
+    /// 集中配備の機銃 + 機銃 + 対空電探 (12種)
+    ConcentratedGunRadar,
+}
+
+/// 優先度の高い順に並べた対空カットインの種別。
+const PRIORITY: [AntiAirCutIn; 10] = [
+    AntiAirCutIn::AkizukiDoubleHighAngleRadar,
+    AntiAirCutIn::AkizukiHighAngleRadar,
+    AntiAirCutIn::AkizukiDoubleHighAngle,
+    AntiAirCutIn::LargeGunShellDirectorRadar,
+    AntiAirCutIn::DoubleDirectorHighAngleRadar,
+    AntiAirCutIn::LargeGunShellDirector,
+    AntiAirCutIn::HighAngleDirectorRadar,
+    AntiAirCutIn::DirectorHighAngleRadar,
+    AntiAirCutIn::HighAngleDirector,
+    AntiAirCutIn::ConcentratedGunRadar,
+];
+
+impl AntiAirCutIn {
+    /// `ship` が発動条件を満たす対空カットインを、判定する順 (優先度の高い順) に列挙する。
+    pub fn candidates(ship: &Ship) -> Vec<AntiAirCutIn> {
+        let count =
+            |filter: fn(&Equipment) -> bool| ship.equips().iter().filter(|e| filter(e)).count();
+        let high_angle = count(Equipment::is_high_angle_gun);
+        let director_high_angle = count(Equipment::is_high_angle_gun_with_fire_director);
+        let director = count(|e| e.category() == EquipCategory::AntiAircraftFireDirector);
+        let radar = count(Equipment::is_anti_aircraft_radar);
+        let large_gun = count(|e| e.category() == EquipCategory::LargeCaliberMainGun);
+        let shell = count(|e| e.category() == EquipCategory::AntiAircraftShell);
+        let concentrated = count(Equipment::is_concentrated_anti_aircraft_gun);
+        let machine_gun = count(|e| e.category() == EquipCategory::AntiAircraftGun);
+        let akizuki = ship.is_akizuki_class();
+
+        PRIORITY
+            .iter()
+            .copied()
+            .filter(|cut_in| match cut_in {
+                AntiAirCutIn::AkizukiDoubleHighAngleRadar => {
+                    akizuki && high_angle >= 2 && radar >= 1
+                }
+                AntiAirCutIn::AkizukiHighAngleRadar => akizuki && high_angle >= 1 && radar >= 1,
+                AntiAirCutIn::AkizukiDoubleHighAngle => akizuki && high_angle >= 2,
+                AntiAirCutIn::LargeGunShellDirectorRadar => {
+                    large_gun >= 1 && shell >= 1 && director >= 1 && radar >= 1
+                }
+                AntiAirCutIn::DoubleDirectorHighAngleRadar => {
+                    director_high_angle >= 2 && radar >= 1
+                }
+                AntiAirCutIn::LargeGunShellDirector => {
+                    large_gun >= 1 && shell >= 1 && director >= 1
+                }
+                AntiAirCutIn::HighAngleDirectorRadar => {
+                    high_angle >= 1 && director >= 1 && radar >= 1
+                }
+                AntiAirCutIn::DirectorHighAngleRadar => director_high_angle >= 1 && radar >= 1,
+                AntiAirCutIn::HighAngleDirector => high_angle >= 1 && director >= 1,
+                AntiAirCutIn::ConcentratedGunRadar => {
+                    concentrated >= 1 && machine_gun >= 2 && radar >= 1
+                }
+            })
+            .collect()
+    }
+
+    /// 発動率 (0.0〜1.0)。
+    pub fn rate(&self) -> f64 {
+        match self {
+            AntiAirCutIn::AkizukiDoubleHighAngleRadar => 0.65,
+            AntiAirCutIn::AkizukiHighAngleRadar => 0.58,
+            AntiAirCutIn::AkizukiDoubleHighAngle => 0.5,
+            AntiAirCutIn::LargeGunShellDirectorRadar => 0.52,
+            AntiAirCutIn::DoubleDirectorHighAngleRadar => 0.55,
+            AntiAirCutIn::LargeGunShellDirector => 0.4,
+            AntiAirCutIn::HighAngleDirectorRadar => 0.45,
+            AntiAirCutIn::DirectorHighAngleRadar => 0.5,
+            AntiAirCutIn::HighAngleDirector => 0.4,
+            AntiAirCutIn::ConcentratedGunRadar => 0.45,
+        }
+    }
+
+    /// 固定撃墜数にかかる倍率。
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            AntiAirCutIn::AkizukiDoubleHighAngleRadar => 1.7,
+            AntiAirCutIn::AkizukiHighAngleRadar => 1.7,
+            AntiAirCutIn::AkizukiDoubleHighAngle => 1.6,
+            AntiAirCutIn::LargeGunShellDirectorRadar => 1.5,
+            AntiAirCutIn::DoubleDirectorHighAngleRadar => 1.55,
+            AntiAirCutIn::LargeGunShellDirector => 1.45,
+            AntiAirCutIn::HighAngleDirectorRadar => 1.35,
+            AntiAirCutIn::DirectorHighAngleRadar => 1.4,
+            AntiAirCutIn::HighAngleDirector => 1.3,
+            AntiAirCutIn::ConcentratedGunRadar => 1.25,
+        }
+    }
+
+    /// 撃墜判定の結果にかかわらず追加される撃墜数。
+    pub fn bonus(&self) -> u16 {
+        match self {
+            AntiAirCutIn::AkizukiDoubleHighAngleRadar => 7,
+            AntiAirCutIn::AkizukiHighAngleRadar => 6,
+            AntiAirCutIn::AkizukiDoubleHighAngle => 4,
+            AntiAirCutIn::LargeGunShellDirectorRadar => 6,
+            AntiAirCutIn::DoubleDirectorHighAngleRadar => 4,
+            AntiAirCutIn::LargeGunShellDirector => 4,
+            AntiAirCutIn::HighAngleDirectorRadar => 3,
+            AntiAirCutIn::DirectorHighAngleRadar => 4,
+            AntiAirCutIn::HighAngleDirector => 2,
+            AntiAirCutIn::ConcentratedGunRadar => 3,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `[種別ID, アイコン種別]` と対空値から装備を作る。
+    fn equip(type_id: u16, icon: u16, anti_aircraft: u16) -> serde_json::Value {
+        serde_json::json!({
+            "id": 1, "equipTypeId": [0, 0, type_id, icon, 0],
+            "status": { "antiAircraft": anti_aircraft }
+        })
+    }
+
+    fn ship(id: u16, equips: Vec<serde_json::Value>) -> Ship {
+        Ship::test_builder().id(id).equips(equips).build()
+    }
+
+    #[test]
+    fn candidates_in_priority_order() {
+        let equips = || vec![equip(1, 16, 8), equip(1, 16, 8), equip(12, 11, 5)];
+        assert_eq!(
+            AntiAirCutIn::candidates(&ship(421, equips())),
+            vec![
+                AntiAirCutIn::AkizukiDoubleHighAngleRadar,
+                AntiAirCutIn::AkizukiHighAngleRadar,
+                AntiAirCutIn::AkizukiDoubleHighAngle,
+                AntiAirCutIn::DoubleDirectorHighAngleRadar,
+                AntiAirCutIn::DirectorHighAngleRadar,
+            ]
+        );
+        assert_eq!(
+            AntiAirCutIn::candidates(&ship(1, equips())),
+            vec![
+                AntiAirCutIn::DoubleDirectorHighAngleRadar,
+                AntiAirCutIn::DirectorHighAngleRadar,
+            ]
+        );
+        // 対空値が 8 未満の高角砲は高射装置付きとみなさない
+        let plain = vec![equip(1, 16, 5), equip(36, 30, 6), equip(12, 11, 1)];
+        assert_eq!(
+            AntiAirCutIn::candidates(&ship(1, plain)),
+            vec![AntiAirCutIn::HighAngleDirector]
+        );
+        let machine_guns = vec![equip(21, 15, 9), equip(21, 15, 6), equip(13, 11, 4)];
+        assert_eq!(
+            AntiAirCutIn::candidates(&ship(1, machine_guns)),
+            vec![AntiAirCutIn::ConcentratedGunRadar]
+        );
+        assert!(AntiAirCutIn::candidates(&ship(1, Vec::new())).is_empty());
+    }
+
+    #[test]
+    fn double_director_high_angle_radar() {
+        let cut_in = AntiAirCutIn::DoubleDirectorHighAngleRadar;
+        assert_eq!(cut_in.multiplier(), 1.55);
+        assert_eq!(cut_in.bonus(), 4);
+    }
+}
@@ -0,0 +1,97 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::{BattleDirection, ShipSnapshot};
+use crate::fleet::{EquipCategory, Equipment, Ship};
+use crate::formula::{FormationFactor, FormulaConstants};
+
+/// 対潜攻撃の種別を表す列挙型。
+/// 種別によって基本攻撃力の種別定数と、攻撃を行える艦種が異なる。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum AswAttackKind {
+    /// 駆逐艦・軽巡洋艦などの爆雷による攻撃
+    DepthCharge,
+    /// 軽空母・航空巡洋艦などの艦載機・水上機による攻撃
+    Aircraft,
+    /// 航空戦艦・水上機母艦などのオートジャイロ (カ号観測機など) による攻撃
+    Rotorcraft,
+    /// 揚陸艦・軽空母などの対潜哨戒機 (三式指揮連絡機(対潜)など) による攻撃
+    LiaisonPlane,
+}
+
+impl AswAttackKind {
+    /// 艦種と装備から、その艦が行える対潜攻撃の種別を判定する。
+    /// 複数の手段を持つ場合は、艦載機・水上機、オートジャイロ、対潜哨戒機の順に優先する。
+    /// 対潜攻撃を行えない場合は `None` を返す。
+    pub fn of(ship: &Ship) -> Option<Self> {
+        let ship_type_id = ship.ship_type_id();
+        if Self::DepthCharge.available_on(ship_type_id) {
+            return (ship.anti_submarine_warfare() > 0).then_some(AswAttackKind::DepthCharge);
+        }
+
+        if AswAttackKind::Aircraft.available_on(ship_type_id)
+            && ship.has_asw_aircraft(EquipCategory::is_asw_capable_aircraft)
+        {
+            Some(AswAttackKind::Aircraft)
+        } else if AswAttackKind::Rotorcraft.available_on(ship_type_id)
+            && ship.has_asw_aircraft(|c| *c == EquipCategory::Autogyro)
+        {
+            Some(AswAttackKind::Rotorcraft)
+        } else if AswAttackKind::LiaisonPlane.available_on(ship_type_id)
+            && ship.has_asw_aircraft(|c| *c == EquipCategory::AntiSubmarinePatrolAircraft)
+        {
+            Some(AswAttackKind::LiaisonPlane)
+        } else {
+            None
+        }
+    }
+
+    /// 指定された艦種がこの種別の対潜攻撃を行えるかどうかを判定する。
+    pub fn available_on(&self, ship_type_id: u16) -> bool {
+        match self {
+            // 海防艦、駆逐艦、軽巡洋艦、重雷装巡洋艦、練習巡洋艦、補給艦
+            AswAttackKind::DepthCharge => matches!(ship_type_id, 1 | 2 | 3 | 4 | 21 | 22),
+            // 航空巡洋艦、軽空母、航空戦艦、水上機母艦、揚陸艦
+            AswAttackKind::Aircraft => matches!(ship_type_id, 6 | 7 | 10 | 16 | 17),
+            // 航空巡洋艦、軽空母、航空戦艦、水上機母艦、揚陸艦
+            AswAttackKind::Rotorcraft => matches!(ship_type_id, 6 | 7 | 10 | 16 | 17),
+            // 軽空母、揚陸艦
+            AswAttackKind::LiaisonPlane => matches!(ship_type_id, 7 | 17),
+        }
+    }
+
+    /// 基本攻撃力に加算される種別定数。
+    fn type_constant(&self) -> f64 {
+        match self {
+            AswAttackKind::DepthCharge => 13.0,
+            AswAttackKind::Aircraft | AswAttackKind::Rotorcraft | AswAttackKind::LiaisonPlane => {
+                8.0
+            }
+        }
+    }
+}
+
+/// 対潜攻撃のキャップ後攻撃力を計算する。
+/// 基本攻撃力は `√素対潜 × 2 + 装備対潜 × 1.5 + 改修ボーナス + 種別定数` で、
+/// 陣形 `formation` の対潜攻撃の補正と、攻撃対象 `target` に対する装備のシナジーの倍率がキャップ前にかかる。
+pub fn asw_power<N: Scalar>(
+    actor: &Ship,
+    actor_snapshot: &ShipSnapshot,
+    target: &Ship,
+    direction: &BattleDirection,
+    formation: &FormationFactor,
+    kind: &AswAttackKind,
+    constants: &FormulaConstants,
+) -> N {
+    let basic = N::from_int(actor.naked_anti_submarine_warfare() as i64).sqrt() * N::from_int(2)
+        + N::from_int(actor.equipment_anti_submarine_warfare() as i64) * N::from_f64(1.5)
+        + N::from_f64(actor.improvement_bonus(Equipment::anti_submarine_improvement_bonus))
+        + N::from_f64(kind.type_constant());
+    let precap = basic
+        * N::from_f64(formation.anti_submarine)
+        * N::from_f64(actor.synergy(target).anti_submarine)
+        * N::from_f64(constants.direction_factor(direction))
+        * N::from_f64(constants.damaged_level_factor(&actor.damaged_level(actor_snapshot)));
+    apply_cap(precap, N::from_f64(constants.asw_cap))
+}
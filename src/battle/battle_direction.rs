@@ -1,5 +1,8 @@
+use serde::{Deserialize, Serialize};
+
 // 戦闘の陣形タイプを表す列挙型
-#[derive(Debug)]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase")]
 pub enum BattleDirection {
     Same,
     Against,
@@ -7,8 +10,8 @@ pub enum BattleDirection {
     TDisadvantage,
 }
 impl BattleDirection {
-    pub fn random() -> Self {
-        let r = rand::random::<f64>();
+    /// `[0, 1)` の乱数 `r` から交戦形態を決定する。
+    pub fn from_random(r: f64) -> Self {
         if r < 0.45 {
             BattleDirection::Same // 45%
         } else if r < 0.75 {
@@ -20,13 +23,14 @@ impl BattleDirection {
         }
     }
 
-    pub fn fp_factor(&self) -> f64 {
-        match self {
-            BattleDirection::Same => 1.0,
-            BattleDirection::Against => 0.8,
-            BattleDirection::TAdvantage => 1.2,
-            BattleDirection::TDisadvantage => 0.6,
-        }
+    /// 各交戦形態と、`from_random` で選ばれる確率の組。
+    pub fn distribution() -> [(Self, f64); 4] {
+        [
+            (BattleDirection::Same, 0.45),
+            (BattleDirection::Against, 0.3),
+            (BattleDirection::TAdvantage, 0.15),
+            (BattleDirection::TDisadvantage, 0.1),
+        ]
     }
 }
 impl std::fmt::Display for BattleDirection {
@@ -1,74 +1,353 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{AirState, AntiAirCutIn, AswAttackKind, DamagedLevel, SpecialAttack};
 use crate::fleet::{EnemyFleet, Fleet, FleetLike, Ship};
+use crate::rng::{self, SimRng};
 
+/// 戦闘の進行状況の記録。各艦の現在の状態と、発生した出来事を順に保持する。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct BattleLog {
-    action_logs: Vec<ActionLog>,
+    action_logs: Vec<LogEntry>,
     pub friend_snapshots: Vec<ShipSnapshot>,
     pub enemy_snapshots: Vec<ShipSnapshot>,
+    #[serde(skip)]
+    trace_rng: bool,
+    /// 直前の `PhaseStart` が示すフェーズ。以降に記録する出来事に付与する。
+    #[serde(skip)]
+    current_phase: Option<Phase>,
+    /// 航空戦の開始時に決まった、味方艦隊から見た制空状態。航空戦を行うまでは `None`。
+    #[serde(skip)]
+    air_state: Option<AirState>,
+    /// 戦闘中のすべての乱数を引く、戦闘ごとの乱数生成器。
+    #[serde(skip, default = "BattleLog::fallback_rng")]
+    rng: SimRng,
 }
 
 impl BattleLog {
-    pub fn new(friend: &Fleet, enemy: &EnemyFleet) -> Self {
+    pub fn new(friend: &Fleet, enemy: &EnemyFleet, trace_rng: bool, rng: SimRng) -> Self {
         let friend_snapshots = friend.ships().iter().map(|ship| ship.into()).collect();
         let enemy_snapshots = enemy.ships().iter().map(|ship| ship.into()).collect();
         Self {
             action_logs: Vec::new(),
             friend_snapshots,
             enemy_snapshots,
+            trace_rng,
+            current_phase: None,
+            air_state: None,
+            rng,
         }
     }
 
+    /// デシリアライズしたログの乱数生成器。戦闘を再開することはないため、シードは問わない。
+    fn fallback_rng() -> SimRng {
+        rng::from_seed(Some(0))
+    }
+
+    /// 航空戦の開始時に決まった制空状態。航空戦を行っていない場合は `None`。
+    pub fn air_state(&self) -> Option<AirState> {
+        self.air_state
+    }
+
+    /// 航空戦の開始時に決まった制空状態を記録する。
+    pub fn set_air_state(&mut self, air_state: AirState) {
+        self.air_state = Some(air_state);
+        self.push(ActionLog::AirState(air_state));
+    }
+
+    /// 出来事を記録する。通し番号と、その時点で進行中のフェーズが付与される。
     pub fn push(&mut self, log: ActionLog) {
-        self.action_logs.push(log);
+        if let ActionLog::PhaseStart(phase) = &log {
+            self.current_phase = Some(phase.clone());
+        }
+        self.action_logs.push(LogEntry {
+            sequence: self.action_logs.len() as u32,
+            phase: self.current_phase.clone(),
+            action: log,
+        });
+    }
+
+    /// 記録された行動ログを発生順に取得する。
+    pub fn actions(&self) -> impl Iterator<Item = &ActionLog> {
+        self.action_logs.iter().map(|entry| &entry.action)
+    }
+
+    /// 通し番号とフェーズを伴う行動ログを発生順に取得する。
+    pub fn entries(&self) -> &[LogEntry] {
+        &self.action_logs
+    }
+
+    /// 戦闘の途中で別の戦闘を計算するための乱数のシードを引く。乱数トレースには記録しない。
+    pub fn next_seed(&mut self) -> u64 {
+        rng::next_seed(&mut self.rng)
+    }
+
+    /// `[0, 1)` の一様乱数を1つ引く。
+    /// 乱数トレースが有効な場合は、引いた値を用途ラベルと共にログに記録する。
+    pub fn random(&mut self, label: RngLabel) -> f64 {
+        let value = rng::unit(&mut self.rng);
+        if self.trace_rng {
+            self.push(ActionLog::RandomDraw { label, value });
+        }
+        value
     }
 }
 
+/// 行動ログの1件。戦闘の時系列を再構成できるよう、出来事に通し番号とフェーズを添える。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LogEntry {
+    /// 戦闘内での発生順の通し番号 (0始まり)
+    pub sequence: u32,
+    /// 出来事が発生したフェーズ。最初のフェーズの開始前は `None`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<Phase>,
+    pub action: ActionLog,
+}
+
+/// 戦闘ログに記録される出来事。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub enum ActionLog {
     PhaseStart(Phase),
+    /// 航空戦の制空状態。航空戦の開始直後に記録される。
+    AirState(AirState),
+    /// 基地航空隊の1波の開始。攻撃する基地航空隊の位置と、その波の stage 1 の制空状態を伴う。
+    #[serde(rename_all = "camelCase")]
+    LandBaseWave {
+        base_idx: usize,
+        air_state: AirState,
+    },
+    /// 対空カットインの発動判定。味方艦隊の航空戦の対空砲火の前に、発動条件を満たす種別ごとに判定した順に記録される。
+    #[serde(rename_all = "camelCase")]
+    AntiAirCutInRoll {
+        ship_idx: usize,
+        cut_in: AntiAirCutIn,
+        /// 発動率 (0.0〜1.0)
+        rate: f64,
+        triggered: bool,
+    },
+    /// 航空戦の対空砲火 (stage 2)。`is_friend` と `ship_idx`, `slot_idx` は撃墜された航空機のスロット、
+    /// `shooter_idx` は対空砲火を行った相手艦隊の艦の位置を表す。
+    #[serde(rename_all = "camelCase")]
+    AntiAircraftFire {
+        is_friend: bool,
+        ship_idx: usize,
+        slot_idx: usize,
+        shooter_idx: usize,
+        shot_down: u16,
+    },
+    /// 基地航空隊の1中隊が受けた対空砲火 (stage 2)。`shooter_idx` は対空砲火を行った敵艦の位置を表す。
+    #[serde(rename_all = "camelCase")]
+    LandBaseAntiAircraftFire {
+        base_idx: usize,
+        squadron_idx: usize,
+        shooter_idx: usize,
+        shot_down: u16,
+    },
     Attack(AttackLog),
+    /// 友軍艦隊の出現。夜戦の開始直後に記録される。`fleet_idx` は出現候補 (`FriendlyFleetTable.fleets`) 内の位置。
+    #[serde(rename_all = "camelCase")]
+    FriendlyFleetArrival {
+        fleet_idx: usize,
+    },
+    /// 友軍艦隊から敵艦への攻撃。`actor_idx` は友軍艦隊内の位置を表す。
+    /// 味方艦隊の攻撃ではないため、与ダメージなどの集計には含まれない。
+    FriendlyFleetAttack(AttackLog),
+    #[serde(rename_all = "camelCase")]
     TurnSkip {
         is_friend: bool,
         ship_idx: usize,
         reason: String,
     },
+    /// 連撃・カットインなどの特殊攻撃の発動判定。発動条件を満たす種別ごとに、判定した順に記録される。
+    #[serde(rename_all = "camelCase")]
+    SpecialAttackRoll {
+        is_friend: bool,
+        ship_idx: usize,
+        special_attack: SpecialAttack,
+        /// 発動率 (0.0〜1.0)
+        rate: f64,
+        triggered: bool,
+    },
+    /// 攻撃によりHPが0になった艦。該当する `Attack` の直後に記録される。
+    #[serde(rename_all = "camelCase")]
     Sunk {
         is_friend: bool,
         ship_idx: usize,
     },
+    RandomDraw {
+        label: RngLabel,
+        value: f64,
+    },
 }
 
+/// 戦闘の各フェーズを表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
 pub enum Phase {
+    /// 基地航空隊の攻撃
+    LandBase,
     AirCombat,
-    Artillery,
-    Torpedo,
+    OpeningTorpedo,
+    /// 砲撃戦1巡目
+    FirstArtillery,
+    /// 砲撃戦2巡目
+    SecondArtillery,
+    ClosingTorpedo,
+    Night,
 }
 
+/// 攻撃1回分の記録。昼戦・夜戦の攻撃ごとに1つ記録される。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct AttackLog {
+    /// 味方艦から敵艦への攻撃かどうか
     pub to_enemy: bool,
+    /// 攻撃した艦の艦隊内の位置。基地航空隊の攻撃では、基地航空隊内の中隊の位置
     pub actor_idx: usize,
+    /// 攻撃を受けた艦の艦隊内の位置
     pub target_idx: usize,
     pub attack_type: AttackType,
+    /// 連撃・カットインなどの特殊攻撃の場合はその種別。特殊攻撃の各回の攻撃がそれぞれ記録される。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub special_attack: Option<SpecialAttack>,
+    /// 特殊攻撃による攻撃力の倍率。特殊攻撃でない場合は 1.0
+    #[serde(default = "AttackLog::default_multiplier")]
+    pub multiplier: f64,
+    /// キャップ・クリティカル補正適用後の攻撃力 (小数点以下切り捨て)
     pub firepower: u16,
+    /// 装甲乱数を含む防御力 (小数点以下切り捨て)
     pub armor: u16,
+    /// 轟沈ストッパーによる置き換え前のダメージ
     pub calculated_damage: u16,
+    /// 実際に減少したHP
     pub applied_damage: u16,
     pub is_critical: bool,
+    /// 命中判定で外れたかどうか。外れた場合はダメージを与えず、防御力とダメージは 0 として記録される
     pub is_miss: bool,
+    /// 轟沈ストッパーによりダメージが置き換えられたかどうか
+    #[serde(default)]
+    pub stopped: bool,
+}
+
+impl AttackLog {
+    fn default_multiplier() -> f64 {
+        1.0
+    }
+
+    /// 基地航空隊による攻撃かどうかを判定する。この場合 `actor_idx` は味方艦を指さない。
+    pub fn is_land_base_attack(&self) -> bool {
+        matches!(self.attack_type, AttackType::LandBaseAirStrike(_))
+    }
+
+    /// 味方艦への攻撃で轟沈ストッパーが発動したかどうかを判定する。
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "snake_case")]
 pub enum AttackType {
     Artillery,
+    /// 対潜攻撃。攻撃手段の種別を伴う。
+    AntiSubmarine(AswAttackKind),
     Torpedo,
     AirStrike,
+    /// 基地航空隊による航空攻撃。攻撃した基地航空隊の位置を伴う。
+    LandBaseAirStrike(usize),
+}
+
+/// 乱数の用途を表すラベル。乱数トレースで各値がどの判定に使われたかを示す。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RngLabel {
+    /// 交戦形態の決定
+    Engagement,
+    /// 攻撃対象の選択
+    TargetPick,
+    /// 命中とクリティカルの判定
+    Hit,
+    /// 防御力の乱数部分
+    ArmorRoll,
+    /// カスダメの乱数部分
+    ScratchDamage,
+    /// 轟沈ストッパー発動時の割合ダメージ
+    Stopper,
+    /// 同じ射程の艦どうし、および両艦隊の間の行動順
+    TurnOrder,
+    /// 航空戦 stage 1 の撃墜数
+    PlaneLoss,
+    /// 艦上攻撃機による航空攻撃の種別倍率
+    AirStrikeMultiplier,
+    /// 対空砲火 (stage 2) の撃墜の発生と、対空カットインの発動判定
+    AntiAircraft,
+    /// 出現する友軍艦隊の選択
+    FriendlyFleet,
+    /// 連撃・カットインなどの特殊攻撃の発動判定
+    SpecialAttack,
 }
 
+/// 戦闘中に変化する艦の状態。
+/// 損傷状態はHPから導出できるが、フロントエンドで再計算しなくて済むように併せて保持する。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 pub struct ShipSnapshot {
     hp: u16,
+    max_hp: u16,
+    damaged_level: DamagedLevel,
+    /// 各スロットの残りの搭載機数。
+    slots: Vec<u16>,
+    /// 戦意 (コンディション値)。
+    morale: u16,
+    /// 残り燃料の割合 (0.0〜1.0)。
+    fuel: f64,
+    /// 残り弾薬の割合 (0.0〜1.0)。
+    ammo: f64,
 }
 
 impl ShipSnapshot {
     pub fn hp(&self) -> u16 {
         self.hp
     }
+    pub fn max_hp(&self) -> u16 {
+        self.max_hp
+    }
+    pub fn damaged_level(&self) -> &DamagedLevel {
+        &self.damaged_level
+    }
+    pub fn slots(&self) -> &[u16] {
+        &self.slots
+    }
+    pub fn morale(&self) -> u16 {
+        self.morale
+    }
+    pub fn fuel(&self) -> f64 {
+        self.fuel
+    }
+    pub fn ammo(&self) -> f64 {
+        self.ammo
+    }
+    /// 戦意を増減させる。戦意は 0〜100 の範囲に収まる。
+    pub fn change_morale(&mut self, delta: i16) {
+        self.morale = (self.morale as i16 + delta).clamp(0, 100) as u16;
+    }
+    /// `slot_idx` 番目のスロットの搭載機を `count` 機失う。搭載数は 0 を下回らない。
+    pub fn lose_planes(&mut self, slot_idx: usize, count: u16) {
+        if let Some(slot) = self.slots.get_mut(slot_idx) {
+            *slot = slot.saturating_sub(count);
+        }
+    }
+    /// 燃料・弾薬を最大値に対する割合で消費する。
+    pub fn consume(&mut self, fuel: f64, ammo: f64) {
+        self.fuel = (self.fuel - fuel).max(0.0);
+        self.ammo = (self.ammo - ammo).max(0.0);
+    }
+    /// 弾薬不足による攻撃力の補正倍率。弾薬が半分を下回ると、残量に比例して低下する。
+    pub fn ammo_factor(&self) -> f64 {
+        (self.ammo * 2.0).min(1.0)
+    }
     pub fn is_alive(&self) -> bool {
         self.hp > 0
     }
@@ -78,11 +357,20 @@ impl ShipSnapshot {
         } else {
             self.hp -= damage;
         }
+        self.damaged_level = DamagedLevel::from_hp(self.hp, self.max_hp);
     }
 }
 
 impl From<&Ship> for ShipSnapshot {
     fn from(ship: &Ship) -> Self {
-        Self { hp: ship.hp() }
+        Self {
+            hp: ship.hp(),
+            max_hp: ship.max_hp(),
+            damaged_level: DamagedLevel::from_hp(ship.hp(), ship.max_hp()),
+            slots: ship.airplane_slots().to_vec(),
+            morale: ship.condition(),
+            fuel: ship.fuel(),
+            ammo: ship.ammo(),
+        }
     }
 }
@@ -1,6 +1,5 @@
-use crate::battle::Battle;
-use crate::battle::BattleLog;
-use crate::fleet::FleetLike;
+use crate::battle::{Battle, ShipSnapshot};
+use crate::fleet::{FleetLike, Ship};
 
 use serde::{Deserialize, Serialize};
 
@@ -19,68 +18,54 @@ pub enum BattleResult {
 impl BattleResult {
     /// Create BattleResult from BattleLog and Battle.
     pub fn calculate(battle: &Battle) -> Self {
-        let log = &battle.log;
-        let setup = &battle.setup;
+        let hps = |ships: &[Ship], snapshots: &[ShipSnapshot]| -> (Vec<u16>, Vec<u16>) {
+            (
+                ships.iter().map(|s| s.hp()).collect(),
+                snapshots.iter().map(|s| s.hp()).collect(),
+            )
+        };
+        let (friend_initial, friend_final) = hps(
+            battle.setup.friend_fleet.ships(),
+            &battle.log.friend_snapshots,
+        );
+        let (enemy_initial, enemy_final) = hps(
+            battle.setup.enemy_fleet.ships(),
+            &battle.log.enemy_snapshots,
+        );
+        Self::from_hp(&friend_initial, &friend_final, &enemy_initial, &enemy_final)
+    }
 
-        let sunk_friend = log
-            .friend_snapshots
-            .iter()
-            .filter(|fs| !fs.is_alive())
-            .count();
-        let sunk_enemy = log
-            .enemy_snapshots
-            .iter()
-            .filter(|fs| !fs.is_alive())
-            .count();
+    /// 戦闘開始時と終了時の各艦のHP (艦隊内の並び順) から戦闘評価を判定する。
+    /// 実際の戦闘を経ずに、仮定した結果の評価を求めるのに使える。HP が 0 の艦は撃沈されたとみなす。
+    pub fn from_hp(
+        friend_initial: &[u16],
+        friend_final: &[u16],
+        enemy_initial: &[u16],
+        enemy_final: &[u16],
+    ) -> Self {
+        let sunk_friend = friend_final.iter().filter(|&&hp| hp == 0).count();
+        let sunk_enemy = enemy_final.iter().filter(|&&hp| hp == 0).count();
 
-        let total_friend: usize = log.friend_snapshots.len();
+        let total_friend: usize = friend_final.len();
         let friend_sunk_ratio: f64 = sunk_friend as f64 / total_friend as f64;
 
-        let total_enemy: usize = log.enemy_snapshots.len();
-        let alive_enemy: usize = log
-            .enemy_snapshots
-            .iter()
-            .filter(|fs| fs.is_alive())
-            .count();
+        let total_enemy: usize = enemy_final.len();
+        let alive_enemy: usize = total_enemy - sunk_enemy;
         let enemy_sunk_ratio: f64 = sunk_enemy as f64 / total_enemy as f64;
-        let is_enemy_flagship_sunk: bool = log
-            .enemy_snapshots
-            .first()
-            .map(|fs| !fs.is_alive())
-            .unwrap_or(false);
-
-        let total_damage_to_friend: u32 = battle
-            .setup
-            .friend_fleet
-            .ships()
-            .iter()
-            .enumerate()
-            .map(|(i, fs)| (fs.hp() - log.friend_snapshots[i].hp()) as u32)
-            .sum();
+        let is_enemy_flagship_sunk: bool = enemy_final.first().map(|&hp| hp == 0).unwrap_or(false);
 
-        let total_damage_to_enemy: u32 = battle
-            .setup
-            .enemy_fleet
-            .ships()
-            .iter()
-            .enumerate()
-            .map(|(i, fs)| (fs.hp() - log.enemy_snapshots[i].hp()) as u32)
-            .sum();
+        let damage = |initial: &[u16], last: &[u16]| -> u32 {
+            initial
+                .iter()
+                .zip(last)
+                .map(|(&initial, &last)| initial.saturating_sub(last) as u32)
+                .sum()
+        };
+        let total_damage_to_friend: u32 = damage(friend_initial, friend_final);
+        let total_damage_to_enemy: u32 = damage(enemy_initial, enemy_final);
 
-        let total_friend_initial_hp: u32 = battle
-            .setup
-            .friend_fleet
-            .ships()
-            .iter()
-            .map(|fs| fs.hp() as u32)
-            .sum();
-        let total_enemy_initial_hp: u32 = battle
-            .setup
-            .enemy_fleet
-            .ships()
-            .iter()
-            .map(|fs| fs.hp() as u32)
-            .sum();
+        let total_friend_initial_hp: u32 = friend_initial.iter().map(|&hp| hp as u32).sum();
+        let total_enemy_initial_hp: u32 = enemy_initial.iter().map(|&hp| hp as u32).sum();
         let friend_gauge = (total_damage_to_enemy as f64) / (total_enemy_initial_hp as f64) * 100.0;
         let enemy_gauge =
             (total_damage_to_friend as f64) / (total_friend_initial_hp as f64) * 100.0;
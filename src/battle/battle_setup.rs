@@ -1,19 +1,82 @@
-use crate::battle::{battle_direction::BattleDirection, ActionLog, ShipSnapshot};
-use crate::fleet::{EnemyFleet, Fleet, FleetLike, Ship};
+use std::rc::Rc;
 
+use crate::battle::battle_direction::BattleDirection;
+use crate::battle::AirState;
+use crate::fleet::{EnemyFleet, Fleet, FleetLike, Formation, FriendlyFleetTable, LandBase};
+use crate::formula::FormulaConstants;
+use crate::interface::SimulationOptions;
+
+/// 戦闘の初期設定。交戦形態、陣形、制空状態、計算式の定数、戦闘開始時の両艦隊と支援を持ち、戦闘を通して不変。
 pub struct BattleSetup {
     direction: BattleDirection,
+    friend_formation: Formation,
+    enemy_formation: Formation,
+    air_state: AirState,
+    debug: bool,
+    fixed_point: bool,
+    constants: Rc<FormulaConstants>,
     pub friend_fleet: Fleet,
     pub enemy_fleet: EnemyFleet,
+    land_bases: Vec<LandBase>,
+    friendly_fleet: Option<FriendlyFleetTable>,
+    touch_attack_used: bool,
 }
 impl BattleSetup {
-    pub fn new(friend: &Fleet, enemy: &EnemyFleet) -> Self {
+    pub fn new(
+        friend: &Fleet,
+        enemy: &EnemyFleet,
+        direction: BattleDirection,
+        constants: Rc<FormulaConstants>,
+        options: &SimulationOptions,
+    ) -> Self {
+        let air_state = AirState::from_fighter_power(friend.fighter_power(), enemy.fighter_power());
+        let (friend_fleet, enemy_fleet) = Self::derive_synergies(friend, enemy, &constants);
+        Self {
+            direction,
+            friend_formation: friend.formation().unwrap_or_default(),
+            enemy_formation: enemy.formation().unwrap_or_default(),
+            air_state,
+            debug: cfg!(feature = "debug-log") && options.debug,
+            fixed_point: options.fixed_point,
+            constants,
+            friend_fleet,
+            enemy_fleet,
+            land_bases: options.land_bases.clone(),
+            friendly_fleet: options.friendly_fleet.clone(),
+            touch_attack_used: options.touch_attack_used,
+        }
+    }
+    /// 交戦形態と計算式の定数はそのままに、両艦隊だけを差し替えた初期設定を作る。
+    /// 友軍艦隊の戦闘のように、戦闘の途中で別の艦隊どうしを戦わせる場合に使う。
+    /// 基地航空隊と友軍艦隊は引き継がず、タッチ系特殊攻撃は発動しない。
+    pub fn with_fleets(&self, friend: &Fleet, enemy: &EnemyFleet) -> Self {
+        let (friend_fleet, enemy_fleet) = Self::derive_synergies(friend, enemy, &self.constants);
         Self {
-            direction: BattleDirection::random(),
-            friend_fleet: friend.clone(),
-            enemy_fleet: enemy.clone(),
+            direction: self.direction,
+            friend_formation: friend.formation().unwrap_or_default(),
+            enemy_formation: enemy.formation().unwrap_or_default(),
+            air_state: AirState::from_fighter_power(friend.fighter_power(), enemy.fighter_power()),
+            debug: self.debug,
+            fixed_point: self.fixed_point,
+            constants: self.constants.clone(),
+            friend_fleet,
+            enemy_fleet,
+            land_bases: Vec::new(),
+            friendly_fleet: None,
+            touch_attack_used: true,
         }
     }
+    /// 両艦隊の各艦に、計算式の定数のシナジー規則を適用した艦隊を作る。
+    fn derive_synergies(
+        friend: &Fleet,
+        enemy: &EnemyFleet,
+        constants: &FormulaConstants,
+    ) -> (Fleet, EnemyFleet) {
+        let (mut friend, mut enemy) = (friend.clone(), enemy.clone());
+        friend.apply_synergies(&constants.synergies);
+        enemy.apply_synergies(&constants.synergies);
+        (friend, enemy)
+    }
     pub fn includes_battleship_class(&self) -> bool {
         self.friend_fleet
             .ships()
@@ -28,4 +91,39 @@ impl BattleSetup {
     pub fn direction(&self) -> &BattleDirection {
         &self.direction
     }
+    /// 指定された艦隊の陣形。入力で省略された場合は単縦陣とする。
+    pub fn formation(&self, is_friend: bool) -> &Formation {
+        if is_friend {
+            &self.friend_formation
+        } else {
+            &self.enemy_formation
+        }
+    }
+    /// 戦闘開始時の両艦隊の制空値から決まる、味方艦隊から見た制空状態。
+    pub fn air_state(&self) -> AirState {
+        self.air_state
+    }
+    pub fn debug(&self) -> bool {
+        self.debug
+    }
+    /// ダメージ計算を固定小数点数で行うかどうか。
+    pub fn fixed_point(&self) -> bool {
+        self.fixed_point
+    }
+    /// この出撃ですでにタッチ系特殊攻撃を発動したかどうか。
+    pub fn touch_attack_used(&self) -> bool {
+        self.touch_attack_used
+    }
+    /// 入力された基地航空隊。出撃していないものも含む。
+    pub fn land_bases(&self) -> &[LandBase] {
+        &self.land_bases
+    }
+    /// 夜戦に駆けつける友軍艦隊の出現候補。
+    pub fn friendly_fleet(&self) -> Option<&FriendlyFleetTable> {
+        self.friendly_fleet.as_ref()
+    }
+    /// 計算式で使う定数を取得する。
+    pub fn constants(&self) -> &FormulaConstants {
+        &self.constants
+    }
 }
@@ -0,0 +1,61 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{ActionLog, BattleLog, Phase};
+
+/// 戦闘1回分の行動の概要。エンジンの挙動をひと目で確認できるよう、行動ログから数え上げる。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BattleStats {
+    /// 実行された砲撃戦の巡数 (1巡目・2巡目)
+    pub shelling_rounds: u32,
+    /// 味方艦の行動
+    pub friend: ActionCounts,
+    /// 敵艦の行動
+    pub enemy: ActionCounts,
+}
+
+/// 一方の艦隊の攻撃回数と、手番を飛ばした回数。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionCounts {
+    /// 攻撃した回数
+    pub attacks: u32,
+    /// 手番を飛ばした理由ごとの回数
+    pub skipped: BTreeMap<String, u32>,
+}
+
+impl BattleStats {
+    pub fn from_log(log: &BattleLog) -> Self {
+        let mut stats = Self::default();
+        for action in log.actions() {
+            match action {
+                ActionLog::PhaseStart(Phase::FirstArtillery | Phase::SecondArtillery) => {
+                    stats.shelling_rounds += 1;
+                }
+                ActionLog::Attack(attack) => {
+                    // `to_enemy` は攻撃側が味方であることを表す
+                    let side = if attack.to_enemy {
+                        &mut stats.friend
+                    } else {
+                        &mut stats.enemy
+                    };
+                    side.attacks += 1;
+                }
+                ActionLog::TurnSkip {
+                    is_friend, reason, ..
+                } => {
+                    let side = if *is_friend {
+                        &mut stats.friend
+                    } else {
+                        &mut stats.enemy
+                    };
+                    *side.skipped.entry(reason.clone()).or_default() += 1;
+                }
+                _ => {}
+            }
+        }
+        stats
+    }
+}
@@ -1,4 +1,8 @@
-#[derive(Debug, Clone, PartialEq, PartialOrd)]
+use serde::{Deserialize, Serialize};
+
+/// HPの割合から決まる損傷状態。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, PartialOrd)]
+#[serde(rename_all = "snake_case")]
 pub enum DamagedLevel {
     NoDamage,
     Minor,
@@ -8,13 +12,19 @@ pub enum DamagedLevel {
 }
 
 impl DamagedLevel {
-    pub fn fp_factor(&self) -> f64 {
-        match self {
-            DamagedLevel::NoDamage => 1.0,
-            DamagedLevel::Minor => 1.0,
-            DamagedLevel::Moderate => 0.7,
-            DamagedLevel::Heavy => 0.4,
-            DamagedLevel::Sunk => 0.0,
+    /// 現在HPと最大HPの比から損傷状態を判定する。
+    pub fn from_hp(now_hp: u16, max_hp: u16) -> Self {
+        let ratio = now_hp as f64 / max_hp as f64;
+        if now_hp == 0 {
+            DamagedLevel::Sunk
+        } else if ratio <= 0.25 {
+            DamagedLevel::Heavy
+        } else if ratio <= 0.5 {
+            DamagedLevel::Moderate
+        } else if ratio <= 0.75 {
+            DamagedLevel::Minor
+        } else {
+            DamagedLevel::NoDamage
         }
     }
 }
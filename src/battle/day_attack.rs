@@ -0,0 +1,156 @@
+use crate::battle::anti_submarine::{self, AswAttackKind};
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::{fit_gun, luck, AttackType, BattleDirection, ShipSnapshot, SpecialAttack};
+use crate::fleet::{EquipCategory, Equipment, Ship};
+use crate::formula::{FormationFactor, FormulaConstants};
+
+/// 弾着観測射撃の発動値で、制空権確保の場合に加算される値。
+const SPOTTING_AIR_SUPREMACY_BONUS: f64 = 10.0;
+/// 弾着観測射撃の発動値で、旗艦に加算される値。
+const SPOTTING_FLAGSHIP_BONUS: f64 = 15.0;
+
+/// 昼砲撃戦で `actor` が陸上型を攻撃対象に選べるかどうかを判定する。
+/// 空母系は、対地攻撃できる艦載機を搭載している場合のみ陸上型を狙える。
+pub fn can_target_installation(actor: &Ship, actor_snapshot: &ShipSnapshot) -> bool {
+    !actor.has_attack_aircraft(actor_snapshot) || actor.has_installation_attack_aircraft()
+}
+
+/// 昼砲撃戦で `actor` が `target` を攻撃する際の命中項を計算する。`accuracy_modifier` は陣形などによる命中補正。
+/// 装備の命中に加え、改修・フィット砲・シナジーによる補正を含める。
+pub fn accuracy(actor: &Ship, target: &Ship, accuracy_modifier: f64) -> f64 {
+    let aiming = actor.equipment_aiming() as f64
+        + actor.improvement_bonus(Equipment::accuracy_improvement_bonus)
+        + fit_gun::accuracy_bonus(actor)
+        + actor.synergy(target).accuracy;
+    luck::accuracy(
+        luck::SHELLING_ACCURACY_BASE,
+        actor.luck(),
+        aiming,
+        accuracy_modifier,
+    )
+}
+
+/// 弾着観測射撃で `actor` が試みる攻撃の種別を、試みる順に列挙する。
+/// 水上機の搭載や制空状態などの発動の前提条件は含まず、装備の組み合わせだけで判定する。
+pub fn spotting_candidates(actor: &Ship) -> Vec<SpecialAttack> {
+    let main = actor.count_equips(EquipCategory::is_main_gun);
+    let secondary = actor.count_equips(|c| *c == EquipCategory::SecondaryGun);
+    let radar =
+        actor.count_equips(|c| matches!(c, EquipCategory::SmallRadar | EquipCategory::LargeRadar));
+    let armor_piercing = actor.count_equips(|c| *c == EquipCategory::ArmorPiercingShell);
+
+    let mut candidates = Vec::new();
+    if main >= 2 && armor_piercing >= 1 {
+        candidates.push(SpecialAttack::SpottingMainMain);
+    }
+    if main >= 1 && secondary >= 1 && armor_piercing >= 1 {
+        candidates.push(SpecialAttack::SpottingMainArmorPiercing);
+    }
+    if main >= 1 && secondary >= 1 && radar >= 1 {
+        candidates.push(SpecialAttack::SpottingMainRadar);
+    }
+    if main >= 1 && secondary >= 1 {
+        candidates.push(SpecialAttack::SpottingMainSecondary);
+    }
+    if main >= 2 {
+        candidates.push(SpecialAttack::SpottingDoubleAttack);
+    }
+    candidates
+}
+
+/// 弾着観測射撃の発動値を計算する。種別ごとの係数で割った値が発動率 (%) になる。
+/// `fleet_scouting` は艦隊の生存艦の索敵値の合計。
+pub fn spotting_value(
+    actor: &Ship,
+    is_flagship: bool,
+    fleet_scouting: u16,
+    air_supremacy: bool,
+) -> f64 {
+    let fleet_scouting = fleet_scouting as f64;
+    let fleet_los = fleet_scouting.sqrt().floor() + (fleet_scouting * 0.1).floor();
+    let mut value = luck::day_special_attack_luck_term(actor.luck())
+        + (0.7 * (fleet_los + 1.6 * actor.scouting() as f64)).floor();
+    if air_supremacy {
+        value += SPOTTING_AIR_SUPREMACY_BONUS;
+    }
+    if is_flagship {
+        value += SPOTTING_FLAGSHIP_BONUS;
+    }
+    value
+}
+
+/// 弾着観測射撃の発動率 (0.0〜1.0) を計算する。
+pub fn spotting_rate(kind: &SpecialAttack, value: f64) -> f64 {
+    let factor = match kind {
+        SpecialAttack::SpottingDoubleAttack => 130.0,
+        SpecialAttack::SpottingMainSecondary => 120.0,
+        SpecialAttack::SpottingMainRadar => 130.0,
+        SpecialAttack::SpottingMainArmorPiercing => 140.0,
+        SpecialAttack::SpottingMainMain => 150.0,
+        _ => return 0.0,
+    };
+    (value / factor).clamp(0.0, 1.0)
+}
+
+/// 昼砲撃戦で `actor` が `target` を攻撃する場合の、キャップ適用後の攻撃力と攻撃の種別を計算する。
+/// 潜水艦に対しては、対潜攻撃が可能なら対潜攻撃の式を使う。
+/// `formation` は攻撃側の艦隊の陣形の補正で、キャップ前にかかる。
+/// 弾着観測射撃などの特殊攻撃の倍率 `multiplier` と、装備のシナジーの倍率はキャップ後にかかる。
+/// クリティカル補正とスクリプトによる補正は含まない。
+pub fn power<N: Scalar>(
+    actor: &Ship,
+    actor_snapshot: &ShipSnapshot,
+    target: &Ship,
+    direction: &BattleDirection,
+    formation: &FormationFactor,
+    multiplier: f64,
+    constants: &FormulaConstants,
+) -> (N, AttackType) {
+    if target.is_submarine() {
+        if let Some(kind) = AswAttackKind::of(actor) {
+            let power = anti_submarine::asw_power(
+                actor,
+                actor_snapshot,
+                target,
+                direction,
+                formation,
+                &kind,
+                constants,
+            );
+            return (power, AttackType::AntiSubmarine(kind));
+        }
+    }
+
+    // TODO: 航空機を搭載していない空母系の場合の分岐が変
+    let improvement = N::from_f64(actor.improvement_bonus(Equipment::firepower_improvement_bonus));
+    let basic_fp = if actor.has_attack_aircraft(actor_snapshot) {
+        // TODO: 航空要員ボーナス
+        let fp = N::from_int(actor.firepower() as i64);
+        let torpedo_fp = N::from_int(actor.torpedo() as i64);
+        // 陸上型に対しては、対地攻撃できない艦爆の爆装は加算されない
+        let bomb_fp = if target.is_installation() {
+            N::from_int(actor.installation_bombing() as i64)
+        } else {
+            N::from_int(actor.bombing() as i64)
+        };
+        ((fp + torpedo_fp + bomb_fp + improvement) * N::from_f64(1.5)).floor() + N::from_int(55)
+    } else {
+        N::from_int(actor.firepower() as i64 + 5)
+            + improvement
+            + N::from_f64(fit_gun::firepower_bonus(actor))
+    };
+
+    let precap_fp = basic_fp
+        * N::from_f64(formation.shelling)
+        * N::from_f64(constants.direction_factor(direction))
+        * N::from_f64(constants.damaged_level_factor(&actor.damaged_level(actor_snapshot)));
+    let capped_fp = apply_cap(precap_fp, N::from_f64(constants.day_artillery_cap));
+    // 今後の調整をここで行う
+    (
+        capped_fp
+            * N::from_f64(multiplier)
+            * N::from_f64(actor.synergy(target).shelling)
+            * N::from_f64(actor_snapshot.ammo_factor()),
+        AttackType::Artillery,
+    )
+}
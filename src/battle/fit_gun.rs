@@ -0,0 +1,96 @@
+//! 艦と主砲の組み合わせによるフィット砲補正。
+//! 練度・ケッコンカッコカリによる違いは考慮せず、艦種と大和型かどうかで戦艦の艦型を近似する。
+//! 戦艦の命中補正は命中項に加算されるため、過重な主砲は命中率とクリティカル率の両方を下げる。
+use crate::fleet::Ship;
+
+/// 戦艦が大口径主砲1門ごとに受ける命中補正を、主砲の口径 (mm) から求める。
+fn battleship_accuracy(actor: &Ship, caliber: u16) -> f64 {
+    if actor.is_yamato_class() {
+        return match caliber {
+            510 => -3.0,
+            _ => 0.0,
+        };
+    }
+    // 高速戦艦は 41cm 以上、それ以外の戦艦は 46cm 以上の主砲で過重となる
+    match (actor.ship_type_id(), caliber) {
+        (8, 410) => -5.0,
+        (8, 460) => -10.0,
+        (8, 510) => -12.0,
+        (_, 460) => -7.0,
+        (_, 510) => -10.0,
+        _ => 0.0,
+    }
+}
+
+/// 昼戦・夜戦の命中項に加算されるフィット砲補正。戦艦系以外は 0.0。
+pub fn accuracy_bonus(actor: &Ship) -> f64 {
+    if !actor.is_battleship_class() || actor.is_abyssal() {
+        return 0.0;
+    }
+    actor
+        .equips()
+        .iter()
+        .filter_map(|e| e.large_gun_caliber())
+        .map(|caliber| battleship_accuracy(actor, caliber))
+        .sum()
+}
+
+/// 軽巡系の基本攻撃力に加算されるフィット砲補正 `√単装砲数 + 2 × √連装砲数`。軽巡系以外は 0.0。
+pub fn firepower_bonus(actor: &Ship) -> f64 {
+    if !actor.is_light_cruiser_class() || actor.is_abyssal() {
+        return 0.0;
+    }
+    let single = actor
+        .equips()
+        .iter()
+        .filter(|e| e.is_light_cruiser_single_gun())
+        .count() as f64;
+    let twin = actor
+        .equips()
+        .iter()
+        .filter(|e| e.is_light_cruiser_twin_gun())
+        .count() as f64;
+    single.sqrt() + 2.0 * twin.sqrt()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::{day_attack, luck};
+
+    fn ship(ship_type_id: u16, equip_ids: &[u16]) -> Ship {
+        let equips = equip_ids
+            .iter()
+            .map(|id| serde_json::json!({ "id": id, "equipTypeId": [1, 2, 3, 1, 0] }));
+        Ship::test_builder()
+            .id(80)
+            .ship_type(ship_type_id)
+            .luck(20)
+            .equips(equips)
+            .build()
+    }
+
+    #[test]
+    fn overweight_guns_lower_hit_value() {
+        let fit = ship(9, &[8, 8]);
+        let overweight = ship(9, &[9, 9]);
+        assert_eq!(accuracy_bonus(&fit), 0.0);
+        assert_eq!(accuracy_bonus(&overweight), -14.0);
+        // 高速戦艦は 41cm 主砲でも過重となる
+        assert_eq!(accuracy_bonus(&ship(8, &[8])), -5.0);
+
+        let evasion = luck::evasion(50, 1.0);
+        let hit = |s: &Ship| luck::hit_value(day_attack::accuracy(s, s, 1.0), evasion);
+        assert_eq!(hit(&fit) - hit(&overweight), 14.0);
+        assert!(luck::hit_rate(hit(&overweight)) < luck::hit_rate(hit(&fit)));
+    }
+
+    #[test]
+    fn light_cruiser_firepower() {
+        assert_eq!(
+            firepower_bonus(&ship(3, &[4, 65, 65])),
+            1.0 + 2.0 * 2f64.sqrt()
+        );
+        assert_eq!(firepower_bonus(&ship(9, &[65])), 0.0);
+    }
+}
@@ -0,0 +1,146 @@
+use std::ops::{Add, Mul, Sub};
+
+/// 固定小数点数の1に相当する値。計算式の定数 (0.7 など) を正確に表せるよう10進で定める。
+const SCALE: i64 = 1_000_000;
+
+/// ダメージ計算で使う数値の型。浮動小数点数 (`f64`) と固定小数点数 (`Fixed`) の両方で
+/// 同じ計算式を書けるようにする。
+pub trait Scalar:
+    Copy + PartialOrd + Add<Output = Self> + Sub<Output = Self> + Mul<Output = Self>
+{
+    fn from_int(value: i64) -> Self;
+    /// 計算式の定数や入力の値を変換する。固定小数点数では最も近い値に丸める。
+    fn from_f64(value: f64) -> Self;
+    /// `[0, 1)` の乱数を変換する。固定小数点数では切り捨て、1未満に収める。
+    fn from_random(value: f64) -> Self;
+    fn to_f64(self) -> f64;
+    fn floor(self) -> Self;
+    /// 平方根。固定小数点数では最小単位未満を切り捨てる。
+    fn sqrt(self) -> Self;
+
+    fn min(self, other: Self) -> Self {
+        if other < self {
+            other
+        } else {
+            self
+        }
+    }
+
+    fn max(self, other: Self) -> Self {
+        if other > self {
+            other
+        } else {
+            self
+        }
+    }
+}
+
+impl Scalar for f64 {
+    fn from_int(value: i64) -> Self {
+        value as f64
+    }
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn from_random(value: f64) -> Self {
+        value
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn floor(self) -> Self {
+        f64::floor(self)
+    }
+    fn sqrt(self) -> Self {
+        f64::sqrt(self)
+    }
+}
+
+/// 最小単位 1/1,000,000 の固定小数点数。
+/// 四則演算と平方根をすべて整数演算で行うため、wasm・x86・ARM のどのビルドでも計算結果が一致する。
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Fixed(i64);
+
+impl Scalar for Fixed {
+    fn from_int(value: i64) -> Self {
+        Fixed(value * SCALE)
+    }
+    fn from_f64(value: f64) -> Self {
+        Fixed((value * SCALE as f64).round() as i64)
+    }
+    fn from_random(value: f64) -> Self {
+        Fixed(((value * SCALE as f64).floor() as i64).clamp(0, SCALE - 1))
+    }
+    fn to_f64(self) -> f64 {
+        self.0 as f64 / SCALE as f64
+    }
+    fn floor(self) -> Self {
+        Fixed(self.0.div_euclid(SCALE) * SCALE)
+    }
+    fn sqrt(self) -> Self {
+        // √(x / SCALE) × SCALE = √(x × SCALE)
+        Fixed((self.0.max(0) as u128 * SCALE as u128).isqrt() as i64)
+    }
+}
+
+impl Add for Fixed {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Fixed(self.0 + rhs.0)
+    }
+}
+
+impl Sub for Fixed {
+    type Output = Self;
+    fn sub(self, rhs: Self) -> Self {
+        Fixed(self.0 - rhs.0)
+    }
+}
+
+impl Mul for Fixed {
+    type Output = Self;
+    /// 積の最小単位未満は切り捨てる。
+    fn mul(self, rhs: Self) -> Self {
+        Fixed((self.0 as i128 * rhs.0 as i128).div_euclid(SCALE as i128) as i64)
+    }
+}
+
+/// キャップ前の攻撃力にキャップを適用する。キャップを超えた分は平方根を取って切り捨てる。
+pub fn apply_cap<N: Scalar>(precap: N, cap: N) -> N {
+    precap.min(cap) + (precap - cap).max(N::from_int(0)).sqrt().floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decimal_constants_are_exact() {
+        // 浮動小数点数では 10 × 0.7 などに誤差が出るが、固定小数点数では正確に表せる
+        assert_eq!(
+            Fixed::from_int(10) * Fixed::from_f64(0.7),
+            Fixed::from_int(7)
+        );
+        assert_eq!(
+            (Fixed::from_int(41) * Fixed::from_f64(1.5)).floor(),
+            Fixed::from_int(61)
+        );
+    }
+
+    #[test]
+    fn random_stays_below_one() {
+        assert!(Fixed::from_random(1.0 - f64::EPSILON) < Fixed::from_int(1));
+        assert_eq!(Fixed::from_random(0.0), Fixed::from_int(0));
+    }
+
+    #[test]
+    fn cap_matches_float() {
+        for precap in [100, 220, 221, 250, 400, 1000] {
+            let float = apply_cap(precap as f64, 220.0);
+            let fixed = apply_cap(Fixed::from_int(precap), Fixed::from_int(220));
+            assert_eq!(fixed.to_f64(), float);
+        }
+        let fixed = apply_cap(Fixed::from_f64(300.5), Fixed::from_int(220));
+        assert_eq!(fixed, Fixed::from_f64(228.0));
+    }
+}
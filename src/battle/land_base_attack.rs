@@ -0,0 +1,114 @@
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::luck;
+use crate::fleet::{EquipCategory, Equipment, Ship};
+use crate::formula::FormulaConstants;
+
+/// 陸上攻撃機の種別倍率。
+const LAND_BASED_ATTACK_AIRCRAFT_MULTIPLIER: f64 = 0.8;
+/// 陸上攻撃機が艦船を攻撃する場合の、キャップ適用後の補正。
+const LAND_BASED_ATTACK_AIRCRAFT_POSTCAP: f64 = 1.8;
+
+/// 基地航空隊の航空攻撃に参加する機体かどうかを判定する。
+pub fn can_strike(plane: &Equipment) -> bool {
+    let category = plane.category();
+    category.participates_in_airstrike() || category == EquipCategory::LandBasedAttackAircraft
+}
+
+/// 基地航空隊の航空攻撃で `target` を攻撃対象に選べるかどうかを判定する。潜水艦は攻撃対象にならない。
+pub fn can_target(target: &Ship) -> bool {
+    !target.is_submarine()
+}
+
+/// 対地攻撃できない機体による攻撃で `target` を攻撃対象に選べるかどうかを判定する。
+pub fn can_target_except_installation(target: &Ship) -> bool {
+    can_target(target) && !target.is_installation()
+}
+
+/// 中隊の命中項。運の項は含めず、機体の命中値だけで計算する。
+pub fn accuracy(plane: &Equipment) -> f64 {
+    luck::accuracy(
+        luck::AIR_STRIKE_ACCURACY_BASE,
+        0,
+        plane.aiming() as f64,
+        1.0,
+    )
+}
+
+/// 基地航空隊の航空攻撃での1中隊分のキャップ適用後の攻撃力を計算する。
+/// 基本攻撃力は `種別倍率 × (雷装または爆装 × √(1.8 × 搭載数) + 25)` で、陸上攻撃機の種別倍率は 0.8、それ以外は 1.0。
+/// 陸上攻撃機はキャップ適用後に 1.8 倍される。交戦形態の補正はかからない。クリティカル補正は含まない。
+pub fn power<N: Scalar>(plane: &Equipment, count: u16, constants: &FormulaConstants) -> N {
+    let category = plane.category();
+    let is_land_based = category == EquipCategory::LandBasedAttackAircraft;
+    let stat = if is_land_based || category == EquipCategory::CarrierBasedTorpedoBomber {
+        plane.torpedo()
+    } else {
+        plane.bombing()
+    };
+    let multiplier = if is_land_based {
+        LAND_BASED_ATTACK_AIRCRAFT_MULTIPLIER
+    } else {
+        1.0
+    };
+    let basic = N::from_int(stat as i64) * (N::from_f64(1.8) * N::from_int(count as i64)).sqrt()
+        + N::from_int(25);
+    let capped = apply_cap(
+        basic * N::from_f64(multiplier),
+        N::from_f64(constants.land_base_cap),
+    );
+    if is_land_based {
+        capped * N::from_f64(LAND_BASED_ATTACK_AIRCRAFT_POSTCAP)
+    } else {
+        capped
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn plane(type_id: u16, torpedo: u16, bombing: u16) -> Equipment {
+        serde_json::from_value(serde_json::json!({
+            "id": 1, "equipTypeId": [0, 0, type_id, 0, 0],
+            "status": { "torpedo": torpedo, "bombing": bombing }
+        }))
+        .unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!(
+            (actual - expected).abs() < 1e-9,
+            "{} != {}",
+            actual,
+            expected
+        );
+    }
+
+    #[test]
+    fn power_by_category() {
+        let constants = FormulaConstants::default();
+        let sqrt_count = (1.8f64 * 18.0).sqrt();
+        // 艦上攻撃機は雷装、艦上爆撃機は爆装を使う
+        assert_close(
+            power::<f64>(&plane(8, 13, 0), 18, &constants),
+            13.0 * sqrt_count + 25.0,
+        );
+        assert_close(
+            power::<f64>(&plane(7, 0, 12), 18, &constants),
+            12.0 * sqrt_count + 25.0,
+        );
+        // 陸上攻撃機は種別倍率 0.8 とキャップ後の 1.8 倍がかかる
+        assert_close(
+            power::<f64>(&plane(47, 14, 0), 18, &constants),
+            (14.0 * sqrt_count + 25.0) * 0.8 * 1.8,
+        );
+    }
+
+    #[test]
+    fn power_is_capped_before_land_based_multiplier() {
+        let constants = FormulaConstants::default();
+        // (50 × √32.4 + 25) × 0.8 ≒ 247.7 → 220 + floor(√27.7) = 225
+        assert_close(power::<f64>(&plane(47, 50, 0), 18, &constants), 225.0 * 1.8);
+        assert_close(power::<f64>(&plane(8, 13, 0), 0, &constants), 25.0);
+    }
+}
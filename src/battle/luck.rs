@@ -0,0 +1,140 @@
+//! 運ステータスが関わる命中・クリティカルなどの確率の計算。
+//! 練度は入力されないため、本来練度に依存する項は含めない。
+use crate::battle::DamagedLevel;
+
+/// 昼砲撃戦の命中項の基本値。
+pub const SHELLING_ACCURACY_BASE: f64 = 90.0;
+/// 雷撃戦の命中項の基本値。
+pub const TORPEDO_ACCURACY_BASE: f64 = 85.0;
+/// 夜戦の命中項の基本値。
+pub const NIGHT_ACCURACY_BASE: f64 = 69.0;
+/// 航空攻撃の命中項の基本値。
+pub const AIR_STRIKE_ACCURACY_BASE: f64 = 95.0;
+/// 命中項と回避項の差の下限。
+const HIT_VALUE_MIN: f64 = 10.0;
+/// 命中項と回避項の差の上限。
+const HIT_VALUE_MAX: f64 = 96.0;
+/// クリティカル時の攻撃力倍率。
+pub const CRITICAL_MULTIPLIER: f64 = 1.5;
+
+/// 夜戦カットインの発動値で、旗艦に加算される値。
+const NIGHT_CUT_IN_FLAGSHIP_BONUS: f64 = 15.0;
+/// 夜戦カットインの発動値で、中破艦に加算される値。
+const NIGHT_CUT_IN_MODERATE_BONUS: f64 = 18.0;
+
+/// 攻撃側の命中項を計算する。`base` は攻撃の種別ごとの基本値 (`SHELLING_ACCURACY_BASE` など)。
+/// `equipment_aiming` は装備の命中と改修などによる命中ボーナスの合計。`modifier` には陣形などによる命中補正を渡す。
+pub fn accuracy(base: f64, luck: u16, equipment_aiming: f64, modifier: f64) -> f64 {
+    ((base + 1.5 * (luck as f64).sqrt() + equipment_aiming) * modifier).floor()
+}
+
+/// 防御側の回避項を計算する。`modifier` は防御側の陣形による回避補正。
+/// 補正後の回避値が 40 以上の部分には、段階的に減衰するキャップがかかる。
+pub fn evasion(evasion: u16, modifier: f64) -> f64 {
+    let base = evasion as f64 * modifier;
+    if base >= 65.0 {
+        (55.0 + 2.0 * (base - 65.0).sqrt()).floor()
+    } else if base >= 40.0 {
+        (40.0 + 3.0 * (base - 40.0).sqrt()).floor()
+    } else {
+        base.floor()
+    }
+}
+
+/// 命中項 `accuracy` と回避項 `evasion` の差を、命中率とクリティカル率の計算に使う範囲に収める。
+pub fn hit_value(accuracy: f64, evasion: f64) -> f64 {
+    (accuracy - evasion).clamp(HIT_VALUE_MIN, HIT_VALUE_MAX)
+}
+
+/// `hit_value` の値から命中率 (0.0〜1.0) を計算する。
+pub fn hit_rate(hit_value: f64) -> f64 {
+    (hit_value + 1.0) / 100.0
+}
+
+/// `hit_value` の値からクリティカル率 (0.0〜1.0) を計算する。クリティカルは命中に含まれる。
+pub fn critical_rate(hit_value: f64) -> f64 {
+    ((hit_value.sqrt() * 1.3).floor() + 1.0) / 100.0
+}
+
+/// 命中判定の結果。
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HitKind {
+    Miss,
+    Hit,
+    Critical,
+}
+
+impl HitKind {
+    /// `[0, 1)` の乱数 `r` 1つで、クリティカル・命中・ミスを判定する。
+    pub fn roll(r: f64, hit_value: f64) -> Self {
+        if r < critical_rate(hit_value) {
+            HitKind::Critical
+        } else if r < hit_rate(hit_value) {
+            HitKind::Hit
+        } else {
+            HitKind::Miss
+        }
+    }
+}
+
+/// 夜戦カットインの発動値を計算する。
+/// カットインの種別ごとの係数で割った値が発動率 (%) になる。
+/// `modifier` には照明弾・探照灯による補正 (`NightEquipment::cut_in_modifier`) を渡す。
+pub fn night_cut_in_value(
+    luck: u16,
+    is_flagship: bool,
+    damaged_level: &DamagedLevel,
+    modifier: f64,
+) -> f64 {
+    let luck = luck as f64;
+    let mut value = if luck < 50.0 {
+        15.0 + luck
+    } else {
+        65.0 + (luck - 50.0).sqrt()
+    };
+    if is_flagship {
+        value += NIGHT_CUT_IN_FLAGSHIP_BONUS;
+    }
+    if *damaged_level == DamagedLevel::Moderate {
+        value += NIGHT_CUT_IN_MODERATE_BONUS;
+    }
+    (value + modifier).floor()
+}
+
+/// 弾着観測射撃などの昼戦特殊攻撃の発動値のうち、運に依存する項を計算する。
+/// 索敵値や制空状態による項は呼び出し側で加算する。
+pub fn day_special_attack_luck_term(luck: u16) -> f64 {
+    ((luck as f64).sqrt() + 10.0).floor()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn evasion_cap() {
+        assert_eq!(evasion(30, 1.0), 30.0);
+        assert_eq!(evasion(49, 1.0), 49.0);
+        assert_eq!(evasion(65, 1.0), 55.0);
+        assert_eq!(evasion(81, 1.0), 63.0);
+        // 陣形の補正はキャップの前にかかる
+        assert_eq!(evasion(50, 1.3), 55.0);
+    }
+
+    #[test]
+    fn hit_value_is_clamped() {
+        assert_eq!(hit_value(120.0, 10.0), 96.0);
+        assert_eq!(hit_value(30.0, 40.0), 10.0);
+        assert_eq!(hit_value(90.0, 40.0), 50.0);
+    }
+
+    #[test]
+    fn roll_order() {
+        // 命中率 51%、クリティカル率 10%
+        assert_eq!(hit_rate(50.0), 0.51);
+        assert_eq!(critical_rate(50.0), 0.1);
+        assert_eq!(HitKind::roll(0.05, 50.0), HitKind::Critical);
+        assert_eq!(HitKind::roll(0.3, 50.0), HitKind::Hit);
+        assert_eq!(HitKind::roll(0.51, 50.0), HitKind::Miss);
+    }
+}
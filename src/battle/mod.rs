@@ -1,22 +1,104 @@
-use crate::fleet::{EnemyFleet, Fleet, FleetLike, Ship};
+use crate::fleet::{EnemyFleet, Fleet, FleetLike, Range, Ship, Squadron};
+use crate::formula::FormationFactor;
+use crate::interface::{ReportDetail, SimulationOptions};
+use crate::rng;
 use itertools::Itertools;
-use rand::Rng;
 use serde::{Deserialize, Serialize};
 
 mod battle_log;
-pub use battle_log::{ActionLog, AttackLog, AttackType, BattleLog, Phase, ShipSnapshot};
+pub use battle_log::{
+    ActionLog, AttackLog, AttackType, BattleLog, LogEntry, Phase, RngLabel, ShipSnapshot,
+};
+
+mod air_attack;
+
+mod air_state;
+pub use air_state::AirState;
+
+mod action_restriction;
+pub use action_restriction::skip_reason;
+
+mod anti_air;
+
+mod anti_air_cut_in;
+pub use anti_air_cut_in::AntiAirCutIn;
+
+mod anti_submarine;
+pub use anti_submarine::AswAttackKind;
 
 mod battle_setup;
-use battle_setup::BattleSetup;
+pub use battle_setup::BattleSetup;
 
 mod battle_direction;
 pub use battle_direction::BattleDirection;
 mod battle_result;
 pub use battle_result::BattleResult;
 
+mod battle_stats;
+pub use battle_stats::{ActionCounts, BattleStats};
+
+mod fit_gun;
+
+mod fixed_point;
+use fixed_point::Fixed;
+pub use fixed_point::Scalar;
+
+mod day_attack;
+pub use day_attack::{
+    accuracy as day_attack_accuracy, can_target_installation, power as day_attack_power,
+};
+
 mod damaged_level;
 pub use damaged_level::DamagedLevel;
 
+mod land_base_attack;
+
+mod luck;
+use luck::HitKind;
+pub use luck::{critical_rate, evasion, hit_rate, hit_value, CRITICAL_MULTIPLIER};
+
+mod night_attack;
+
+mod night_equipment;
+use night_equipment::NightEquipment;
+
+mod observer;
+pub use observer::{notify, BattleObserver};
+
+mod phase;
+pub use phase::{
+    AirCombatPhase, ArtilleryPhase, BattlePhase, ClosingTorpedoPhase, LandBasePhase, NightPhase,
+    NodeType, OpeningTorpedoPhase, PhasePipeline, SinglePhase,
+};
+
+mod special_attack;
+pub use special_attack::SpecialAttack;
+
+mod stopper;
+
+mod torpedo_attack;
+
+mod touch_attack;
+
+mod ship_ref;
+pub use ship_ref::{FleetSide, ShipRef};
+
+/// 戦闘の計算式のバージョン。計算結果が変わる変更を加えたら更新する。
+pub const FORMULA_VERSION: u32 = 30;
+
+/// 攻撃1回分の攻撃手段。
+#[derive(Clone, Copy)]
+enum AttackMethod {
+    /// フェーズごとの通常の攻撃
+    Single,
+    /// 航空攻撃。攻撃する航空機の装備の位置を持つ。
+    AirStrike(usize),
+    /// 連撃・カットインなどの特殊攻撃の1回分
+    Special(SpecialAttack),
+    /// タッチ系特殊攻撃に参加する1隻の攻撃。攻撃力の倍率を持つ。
+    Touch(SpecialAttack, f64),
+}
+
 /// バトルを制御するための構造体。
 /// `setup`フィールドはバトルの初期設定を保持し、戦闘を通して不変です。
 /// `log`フィールドはバトルの進行状況を記録します。可変です。
@@ -28,44 +110,88 @@ pub struct Battle {
 impl Battle {
     /// 新しいBattleインスタンスを作成します。
     /// 与えられた艦隊の情報をCloneし、`BattleSetup`と`BattleLog`をそれぞれ初期化します。
-    pub fn new(friend: &Fleet, enemy: &EnemyFleet) -> Self {
-        let setup = BattleSetup::new(friend, enemy);
-        let log = BattleLog::new(friend, enemy);
+    /// `options.debug` が有効な場合、戦闘中に引いた乱数はすべて`BattleLog`に記録されます。
+    /// ただし`debug-log`フィーチャーが無効なビルドでは、`options.debug`は無視されます。
+    /// `options.seed` を指定した場合は、そのシードから戦闘中の乱数を生成します。
+    pub fn new(friend: &Fleet, enemy: &EnemyFleet, options: &SimulationOptions) -> Self {
+        let seed = options
+            .seed
+            .unwrap_or_else(|| rng::next_seed(&mut rng::from_seed(None)));
+        Self::with_seed(friend, enemy, options, seed)
+    }
+
+    /// 戦闘中の乱数を `seed` から生成して戦闘を準備する。`options.seed` は使わない。
+    /// 同じ入力とシードからは、どのプラットフォームのビルドでも同じ戦闘が再現される。
+    pub fn with_seed(
+        friend: &Fleet,
+        enemy: &EnemyFleet,
+        options: &SimulationOptions,
+        seed: u64,
+    ) -> Self {
+        let debug = cfg!(feature = "debug-log") && options.debug;
+        let rng = rng::from_seed(Some(seed));
+        let mut log = BattleLog::new(friend, enemy, debug, rng);
+        let direction = BattleDirection::from_random(log.random(RngLabel::Engagement));
+        // 未知の定数セット名は入力の検証で報告済みのため、既定の定数で代替する
+        let constants = crate::formula::constants_for(options.formula_set.as_deref())
+            .unwrap_or_else(crate::formula::constants);
+        let setup = BattleSetup::new(friend, enemy, direction, constants, options);
         Self { setup, log }
     }
 
     /// reference: [戦闘について - 艦隊これくしょん -艦これ- 攻略 Wiki*](https://wikiwiki.jp/kancolle/%E6%88%A6%E9%97%98%E3%81%AB%E3%81%A4%E3%81%84%E3%81%A6#b7dbae4f)
     /// 艦これの砲撃戦1巡目は次のルールで行動順が決定されます:
-    /// 1. 味方・敵双方の生存艦をそれぞれ抽出し、射程順にソートします。
+    /// 1. 味方・敵双方の生存艦をそれぞれ抽出し、射程順にソートします。射程が同じ艦どうしの順序はランダムです。
     /// 2. それぞれの艦隊の最も射程の長い艦を比較し、射程の長い方の艦隊の艦をキューの最初に追加します。
+    ///    射程が同じ場合、どちらの艦隊が先になるかはランダムです。
     /// 3. 以降、両艦隊の艦を交互に行動させるよう、艦をキューに追加します。
     ///    味方艦の射程がそれぞれ`[長, 短]`, 敵艦が`[中, 中]`の場合、行動順は`[味方長, 敵中, 味方短, 敵中]`となります。
     /// 4. どちらかの艦隊の生存艦が尽きた場合、残った艦隊の艦をそのままキューに追加します。
     /// 5. 1巡目中に艦が撃沈されても、行動順は再計算されず、撃沈された艦は単にスキップされます。
-    fn ordered_by_range(&self) -> Vec<(bool, usize)> {
+    fn ordered_by_range(&mut self) -> Vec<(bool, usize)> {
         // 味方と敵の生存艦をそれぞれ取得し、射程順にソート
-        let mut friend =
-            Self::filter_alive(self.setup.friend_fleet.ships(), &self.log.friend_snapshots);
-        friend.sort_by_key(|(_, s)| std::cmp::Reverse(s.range()));
-        let mut enemy =
-            Self::filter_alive(self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots);
-        enemy.sort_by_key(|(_, s)| std::cmp::Reverse(s.range()));
+        let ranges = |ships: &[Ship], snapshots: &[ShipSnapshot]| {
+            Self::filter_alive(ships, snapshots)
+                .into_iter()
+                .map(|(idx, s)| (idx, s.range()))
+                .collect::<Vec<_>>()
+        };
+        let friend = ranges(self.setup.friend_fleet.ships(), &self.log.friend_snapshots);
+        let enemy = ranges(self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots);
+        let friend = self.sort_by_range(friend);
+        let enemy = self.sort_by_range(enemy);
 
         // 先に動き始める艦隊を決定
-        let (first, second) =
-            if friend.first().map(|(_, s)| s.range()) >= enemy.first().map(|(_, s)| s.range()) {
-                (friend, enemy)
-            } else {
-                (enemy, friend)
-            };
+        let friend_first = match friend
+            .first()
+            .map(|(_, r)| r)
+            .cmp(&enemy.first().map(|(_, r)| r))
+        {
+            std::cmp::Ordering::Greater => true,
+            std::cmp::Ordering::Less => false,
+            std::cmp::Ordering::Equal => self.log.random(RngLabel::TurnOrder) < 0.5,
+        };
+        let friend = friend.iter().map(|(idx, _)| (true, *idx));
+        let enemy = enemy.iter().map(|(idx, _)| (false, *idx));
 
         // 交互にキューに追加; (艦隊識別子, 艦インデックス)
-        let order = first
-            .iter()
-            .map(|(idx, _)| (true, *idx))
-            .interleave(second.iter().map(|(idx, _)| (false, *idx)))
-            .collect::<Vec<_>>();
-        order
+        if friend_first {
+            friend.interleave(enemy).collect::<Vec<_>>()
+        } else {
+            enemy.interleave(friend).collect::<Vec<_>>()
+        }
+    }
+
+    /// (艦インデックス, 射程) の組を射程の長い順に並べます。射程が同じ艦どうしの順序はランダムです。
+    fn sort_by_range(&mut self, mut ships: Vec<(usize, Range)>) -> Vec<(usize, Range)> {
+        // 先にシャッフルしてから安定ソートすることで、同じ射程の艦の順序をランダムにする
+        for i in (1..ships.len()).rev() {
+            let r = self.log.random(RngLabel::TurnOrder);
+            let j = ((r * (i + 1) as f64) as usize).min(i);
+            ships.swap(i, j);
+        }
+        ships.sort_by_key(|(_, range)| std::cmp::Reverse(range.clone()));
+        ships
     }
 
     /// 2巡目の行動順決定はより単純で、艦隊内の艦をインデックス順に並べたものになります。
@@ -74,12 +200,11 @@ impl Battle {
             Self::filter_alive(self.setup.friend_fleet.ships(), &self.log.friend_snapshots);
         let enemy = Self::filter_alive(self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots);
 
-        let order = friend
+        friend
             .iter()
             .map(|(idx, _)| (true, *idx))
             .interleave(enemy.iter().map(|(idx, _)| (false, *idx)))
-            .collect::<Vec<_>>();
-        order
+            .collect::<Vec<_>>()
     }
 
     /// 生存している艦の所属フラグとインデックスを抽出します。
@@ -95,15 +220,17 @@ impl Battle {
     }
 
     /// 指定された艦隊とインデックスに対応する艦への参照を取得します。
+    /// 損傷状態などにより `phase` で行動できない場合は、その理由を返します。
     fn actor(
         &self,
+        phase: &Phase,
         actor_is_friend: bool,
         actor_idx: usize,
     ) -> Result<(&Ship, &ShipSnapshot), String> {
         let actor_snapshots = if actor_is_friend {
-            &self.log.enemy_snapshots
-        } else {
             &self.log.friend_snapshots
+        } else {
+            &self.log.enemy_snapshots
         };
 
         let actor = if actor_is_friend {
@@ -114,49 +241,439 @@ impl Battle {
 
         let actor_snapshot = &actor_snapshots[actor_idx];
 
-        if !actor_snapshot.is_alive() {
-            return Err("Sunk".to_string());
+        if let Some(reason) = action_restriction::skip_reason(phase, actor, actor_snapshot) {
+            return Err(reason.to_string());
         }
+        Ok((actor, actor_snapshot))
+    }
 
-        if actor.has_attack_aircraft(actor_snapshot)
-            && actor.damaged_level(actor_snapshot) >= DamagedLevel::Moderate
-        {
-            return Err("Flight Deck is too Damaged".to_string());
+    /// 指定された艦隊の生存艦からランダムに1隻選び、そのインデックスを取得します。
+    /// `can_target` が偽を返す艦は対象から除外します。
+    /// 対象となる艦がいない場合は`None`を返します。
+    fn random_target(
+        &mut self,
+        actor_is_friend: bool,
+        can_target: fn(&Ship) -> bool,
+    ) -> Option<usize> {
+        #[cfg(feature = "scripting")]
+        if crate::scripting::has_target_weight() {
+            return self.scripted_random_target(actor_is_friend, can_target);
         }
-        Ok((actor, actor_snapshot))
+
+        // 攻撃ごとに呼ばれるため、候補を Vec に集めずに数えてから n 番目を選ぶ
+        let count = self.target_candidates(actor_is_friend, can_target).count();
+        if count == 0 {
+            return None;
+        }
+        let r = self.log.random(RngLabel::TargetPick);
+        let n = ((r * count as f64) as usize).min(count - 1);
+        self.target_candidates(actor_is_friend, can_target).nth(n)
     }
 
-    /// 指定された艦隊のランダムな艦への参照とそのスナップショットの可変参照を取得します。
-    fn random_target(&mut self, actor_is_friend: bool) -> (usize, &Ship, &mut ShipSnapshot) {
+    /// 登録されたスクリプトの重みに比例した確率で、攻撃対象を選びます。
+    /// 重みの合計が 0 の場合は、候補から一様に選びます。
+    #[cfg(feature = "scripting")]
+    fn scripted_random_target(
+        &mut self,
+        actor_is_friend: bool,
+        can_target: fn(&Ship) -> bool,
+    ) -> Option<usize> {
+        use evalexpr::Value;
+
         let (ships, snapshots) = if actor_is_friend {
+            (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+        } else {
+            (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+        };
+        let weights = self
+            .target_candidates(actor_is_friend, can_target)
+            .map(|idx| {
+                let weight = crate::scripting::target_weight(&[
+                    ("actor_is_friend", Value::Boolean(actor_is_friend)),
+                    ("target_index", Value::Int(idx as i64)),
+                    ("target_id", Value::Int(ships[idx].id() as i64)),
+                    (
+                        "target_ship_type",
+                        Value::Int(ships[idx].ship_type_id() as i64),
+                    ),
+                    ("target_hp", Value::Int(snapshots[idx].hp() as i64)),
+                    ("target_max_hp", Value::Int(snapshots[idx].max_hp() as i64)),
+                ]);
+                (idx, weight)
+            })
+            .collect::<Vec<_>>();
+        if weights.is_empty() {
+            return None;
+        }
+
+        let r = self.log.random(RngLabel::TargetPick);
+        Some(pick_weighted(r, &weights))
+    }
+
+    /// `weight` が返す重みに比例した確率で、指定された艦隊の生存艦から攻撃対象を選びます。
+    /// `can_target` が偽を返す艦は対象から除外します。スクリプトの重みが登録されている場合はそちらを優先します。
+    fn weighted_random_target(
+        &mut self,
+        actor_is_friend: bool,
+        can_target: fn(&Ship) -> bool,
+        weight: impl Fn(usize) -> f64,
+    ) -> Option<usize> {
+        #[cfg(feature = "scripting")]
+        if crate::scripting::has_target_weight() {
+            return self.scripted_random_target(actor_is_friend, can_target);
+        }
+
+        let weights = self
+            .target_candidates(actor_is_friend, can_target)
+            .map(|idx| (idx, weight(idx)))
+            .collect::<Vec<_>>();
+        if weights.is_empty() {
+            return None;
+        }
+        let r = self.log.random(RngLabel::TargetPick);
+        Some(pick_weighted(r, &weights))
+    }
+
+    /// 攻撃対象になり得る艦のインデックスを順に返すイテレータを取得します。
+    fn target_candidates(
+        &self,
+        actor_is_friend: bool,
+        can_target: fn(&Ship) -> bool,
+    ) -> impl Iterator<Item = usize> + '_ {
+        let (ships, snapshots) = if actor_is_friend {
+            (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+        } else {
+            (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+        };
+        ships
+            .iter()
+            .zip(snapshots)
+            .enumerate()
+            .filter(move |(_, (ship, snap))| snap.is_alive() && can_target(ship))
+            .map(|(idx, _)| idx)
+    }
+
+    /// 指定された艦隊とインデックスに対応する艦の、陣形による補正を取得します。
+    fn formation_factor(&self, is_friend: bool, ship_idx: usize) -> FormationFactor {
+        let fleet_len = if is_friend {
+            self.setup.friend_fleet.ships().len()
+        } else {
+            self.setup.enemy_fleet.ships().len()
+        };
+        *self.setup.constants().formation_factor(
+            self.setup.formation(is_friend),
+            ship_idx,
+            fleet_len,
+        )
+    }
+
+    /// 指定された艦隊とインデックスに対応する攻撃対象への参照とそのスナップショットの可変参照を取得します。
+    fn target_mut(
+        &mut self,
+        actor_is_friend: bool,
+        target_idx: usize,
+    ) -> (&Ship, &mut ShipSnapshot) {
+        if actor_is_friend {
             (
-                &self.setup.enemy_fleet.ships(),
-                &mut self.log.enemy_snapshots,
+                &self.setup.enemy_fleet.ships()[target_idx],
+                &mut self.log.enemy_snapshots[target_idx],
             )
         } else {
             (
-                &self.setup.friend_fleet.ships(),
-                &mut self.log.friend_snapshots,
+                &self.setup.friend_fleet.ships()[target_idx],
+                &mut self.log.friend_snapshots[target_idx],
             )
+        }
+    }
+
+    /// `actor_idx` の艦から `target_idx` の艦への攻撃1回分のダメージを、数値の型 `N` で計算する。
+    /// `accuracy` は攻撃側の命中項で、特殊攻撃の場合は種別ごとの命中の倍率をかける。
+    /// 航空攻撃や特殊攻撃の場合は、その内容を `method` に指定する。
+    /// 乱数は引くが、轟沈ストッパーとダメージの適用、戦闘ログへの記録は行わない。
+    fn attack<N: Scalar>(
+        &mut self,
+        phase: &Phase,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        target_idx: usize,
+        accuracy: f64,
+        method: AttackMethod,
+    ) -> AttackLog {
+        let (special_attack, multiplier) = match method {
+            AttackMethod::Special(kind) => (Some(kind), kind.multiplier()),
+            AttackMethod::Touch(kind, multiplier) => (Some(kind), multiplier),
+            _ => (None, 1.0),
         };
-        let alive_indices = snapshots
-            .iter()
-            .enumerate()
-            .filter_map(|(idx, snap)| snap.is_alive().then_some(idx))
-            .collect::<Vec<usize>>();
-        if alive_indices.is_empty() {
-            panic!("No alive targets to choose from");
+        let accuracy = match special_attack {
+            Some(kind) => (accuracy * kind.accuracy_multiplier()).floor(),
+            None => accuracy,
+        };
+        let multiplier_roll = {
+            let actor = if actor_is_friend {
+                &self.setup.friend_fleet.ships()[actor_idx]
+            } else {
+                &self.setup.enemy_fleet.ships()[actor_idx]
+            };
+            match method {
+                AttackMethod::AirStrike(i)
+                    if air_attack::uses_multiplier_roll(&actor.equips()[i]) =>
+                {
+                    self.log.random(RngLabel::AirStrikeMultiplier)
+                }
+                _ => 0.0,
+            }
+        };
+        let formation = self.formation_factor(actor_is_friend, actor_idx);
+        let (firepower, attack_type) = {
+            let (actor, actor_snapshot, target) = if actor_is_friend {
+                (
+                    &self.setup.friend_fleet.ships()[actor_idx],
+                    &self.log.friend_snapshots[actor_idx],
+                    &self.setup.enemy_fleet.ships()[target_idx],
+                )
+            } else {
+                (
+                    &self.setup.enemy_fleet.ships()[actor_idx],
+                    &self.log.enemy_snapshots[actor_idx],
+                    &self.setup.friend_fleet.ships()[target_idx],
+                )
+            };
+            if let AttackMethod::AirStrike(i) = method {
+                let power = air_attack::power::<N>(
+                    &actor.equips()[i],
+                    actor_snapshot.slots()[i],
+                    multiplier_roll,
+                    actor_snapshot,
+                    self.setup.constants(),
+                );
+                (power, AttackType::AirStrike)
+            } else {
+                match phase {
+                    Phase::Night => night_attack::power::<N>(
+                        actor,
+                        actor_snapshot,
+                        target,
+                        multiplier,
+                        self.setup.constants(),
+                    ),
+                    Phase::OpeningTorpedo | Phase::ClosingTorpedo => (
+                        torpedo_attack::power::<N>(
+                            actor,
+                            actor_snapshot,
+                            self.setup.direction(),
+                            &formation,
+                            self.setup.constants(),
+                        ),
+                        AttackType::Torpedo,
+                    ),
+                    _ => day_attack::power::<N>(
+                        actor,
+                        actor_snapshot,
+                        target,
+                        self.setup.direction(),
+                        &formation,
+                        multiplier,
+                        self.setup.constants(),
+                    ),
+                }
+            }
+        };
+        #[cfg(feature = "scripting")]
+        let firepower = {
+            use evalexpr::Value;
+
+            let (actor, target) = if actor_is_friend {
+                (
+                    &self.setup.friend_fleet.ships()[actor_idx],
+                    &self.setup.enemy_fleet.ships()[target_idx],
+                )
+            } else {
+                (
+                    &self.setup.enemy_fleet.ships()[actor_idx],
+                    &self.setup.friend_fleet.ships()[target_idx],
+                )
+            };
+            N::from_f64(crate::scripting::post_cap_modifier(
+                firepower.to_f64(),
+                &[
+                    ("actor_id", Value::Int(actor.id() as i64)),
+                    ("actor_ship_type", Value::Int(actor.ship_type_id() as i64)),
+                    ("actor_is_friend", Value::Boolean(actor_is_friend)),
+                    ("target_id", Value::Int(target.id() as i64)),
+                    ("target_ship_type", Value::Int(target.ship_type_id() as i64)),
+                    (
+                        "target_is_installation",
+                        Value::Boolean(target.is_installation()),
+                    ),
+                    ("target_is_submarine", Value::Boolean(target.is_submarine())),
+                ],
+            ))
+        };
+
+        let (hit, firepower, armor, calculated_damage) =
+            self.damage(actor_is_friend, target_idx, firepower, accuracy);
+
+        AttackLog {
+            to_enemy: actor_is_friend,
+            actor_idx,
+            target_idx,
+            attack_type,
+            special_attack,
+            multiplier,
+            firepower: firepower.to_f64() as u16,
+            armor: armor.to_f64() as u16,
+            calculated_damage: calculated_damage.to_f64() as u16,
+            applied_damage: 0,
+            is_critical: hit == HitKind::Critical,
+            is_miss: hit == HitKind::Miss,
+            stopped: false,
         }
-        let mut rng = rand::rng();
-        let target_idx = alive_indices[rng.random_range(0..alive_indices.len())];
+    }
 
-        (target_idx, &ships[target_idx], &mut snapshots[target_idx])
+    /// キャップ適用後の攻撃力 `firepower` から、命中の判定と防御力を経てダメージを計算する。
+    /// 命中率は攻撃側の命中項 `accuracy` と、攻撃対象の回避と陣形の回避補正から求めた回避項の差で決まる。
+    /// 命中判定の結果、クリティカル補正後の攻撃力、防御力、轟沈ストッパーによる置き換え前のダメージを返す。
+    /// 外れた場合は防御力とダメージを 0 とし、以降の乱数は引かない。
+    fn damage<N: Scalar>(
+        &mut self,
+        actor_is_friend: bool,
+        target_idx: usize,
+        firepower: N,
+        accuracy: f64,
+    ) -> (HitKind, N, N, N) {
+        let evasion_modifier = self.formation_factor(!actor_is_friend, target_idx).evasion;
+        let (target_armor, target_evasion, hp_now) = {
+            let (target, target_snapshot) = self.target_mut(actor_is_friend, target_idx);
+            (
+                N::from_int(target.armor() as i64),
+                target.evasion(),
+                target_snapshot.hp(),
+            )
+        };
+        let hit_value = luck::hit_value(accuracy, luck::evasion(target_evasion, evasion_modifier));
+        let hit = HitKind::roll(self.log.random(RngLabel::Hit), hit_value);
+        if hit == HitKind::Miss {
+            return (hit, firepower, N::from_int(0), N::from_int(0));
+        }
+        let firepower = if hit == HitKind::Critical {
+            (firepower * N::from_f64(luck::CRITICAL_MULTIPLIER)).floor()
+        } else {
+            firepower
+        };
+
+        let armor = {
+            let r = N::from_random(self.log.random(RngLabel::ArmorRoll));
+            target_armor * N::from_f64(0.7) + (target_armor * r).floor() * N::from_f64(0.6)
+        };
+
+        // -- ダメージ計算 --
+
+        // 轟沈ストッパーによる置き換え前のダメージ
+        let calculated_damage = {
+            let diff = (firepower - armor).floor();
+            if diff > N::from_int(0) {
+                diff
+            } else {
+                // カスダメ化
+                let r = N::from_random(self.log.random(RngLabel::ScratchDamage));
+                self.setup
+                    .constants()
+                    .scratch_damage
+                    .damage(N::from_int(hp_now as i64), r)
+            }
+        };
+        (hit, firepower, armor, calculated_damage)
     }
 
-    pub fn artillery_phase_helper(&mut self, fire_order: Vec<(bool, usize)>) {
+    /// 攻撃1回分のダメージを計算する。固定小数点数モードでは、どのビルドでも結果が一致するよう整数演算で計算する。
+    fn calculate_attack(
+        &mut self,
+        phase: &Phase,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        target_idx: usize,
+        accuracy: f64,
+        method: AttackMethod,
+    ) -> AttackLog {
+        if self.setup.fixed_point() {
+            self.attack::<Fixed>(
+                phase,
+                actor_is_friend,
+                actor_idx,
+                target_idx,
+                accuracy,
+                method,
+            )
+        } else {
+            self.attack::<f64>(
+                phase,
+                actor_is_friend,
+                actor_idx,
+                target_idx,
+                accuracy,
+                method,
+            )
+        }
+    }
+
+    /// 計算済みの攻撃に轟沈ストッパーを適用して攻撃対象のHPを減らし、戦闘ログに記録する。
+    /// 轟沈ストッパーは適用する時点のHPに対して判定する。
+    fn resolve_attack(&mut self, mut attack: AttackLog) {
+        let (actor_is_friend, target_idx) = (attack.to_enemy, attack.target_idx);
+        let (side, target_at_start) = if actor_is_friend {
+            (
+                FleetSide::Enemy,
+                &self.setup.enemy_fleet.ships()[target_idx],
+            )
+        } else {
+            (
+                FleetSide::Friend,
+                &self.setup.friend_fleet.ships()[target_idx],
+            )
+        };
+        let protected = stopper::is_protected(side, target_idx, target_at_start);
+        let (_, target_snapshot) = self.target_mut(actor_is_friend, target_idx);
+        let hp_now = target_snapshot.hp();
+        let coefficients = self.setup.constants().stopper.clone();
+        let calculated_damage = attack.calculated_damage;
+        attack.applied_damage = if self.setup.fixed_point() {
+            stopper::apply(calculated_damage, hp_now, protected, &coefficients, || {
+                Fixed::from_random(self.log.random(RngLabel::Stopper))
+            })
+        } else {
+            stopper::apply(calculated_damage, hp_now, protected, &coefficients, || {
+                self.log.random(RngLabel::Stopper)
+            })
+        };
+        attack.stopped = protected && hp_now > 0 && calculated_damage >= hp_now;
+
+        let (_, target_snapshot) = self.target_mut(actor_is_friend, target_idx);
+        target_snapshot.apply_damage(attack.applied_damage);
+        // 同じフェーズで先に撃沈された艦への攻撃では、撃沈を重ねて記録しない
+        let sunk = hp_now > 0 && target_snapshot.hp() == 0;
+        self.log.push(ActionLog::Attack(attack));
+        if sunk {
+            self.log.push(ActionLog::Sunk {
+                is_friend: !actor_is_friend,
+                ship_idx: target_idx,
+            });
+        }
+    }
+
+    /// `fire_order` の順 (`(味方かどうか, 艦隊内の位置)`) に砲撃戦の攻撃を1巡行う。
+    /// `phase` は行動の可否の判定と攻撃力の計算式 (昼戦・夜戦) の選択に使い、フェーズの開始は記録しない。
+    /// 昼戦では弾着観測射撃、夜戦では連撃・カットインの発動を判定する。
+    pub fn artillery_phase_helper(&mut self, phase: Phase, fire_order: Vec<(bool, usize)>) {
+        // 照明弾・探照灯は夜戦の開始時の状態で決まる
+        let night_equipment = (phase == Phase::Night).then(|| {
+            (
+                NightEquipment::of(self.setup.friend_fleet.ships(), &self.log.friend_snapshots),
+                NightEquipment::of(self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots),
+            )
+        });
         for (actor_is_friend, actor_idx) in fire_order {
             // -- 行動者の火力を計算 --
-            let (actor, actor_snapshot) = match self.actor(actor_is_friend, actor_idx) {
+            let (actor, actor_snapshot) = match self.actor(&phase, actor_is_friend, actor_idx) {
                 Ok(a) => a,
                 Err(reason) => {
                     self.log.push(ActionLog::TurnSkip {
@@ -168,121 +685,1027 @@ impl Battle {
                 }
             };
 
-            let firepower = {
-                let cap = 220.0;
-
-                // TODO: 装備改修ボーナス
-                // TODO: 航空機を搭載していない空母系の場合の分岐が変
-                let basic_fp = if actor.has_attack_aircraft(actor_snapshot) {
-                    // TODO: 航空要員ボーナス
-                    let fp = actor.firepower() as f64;
-                    let torpedo_fp = actor.torpedo() as f64;
-                    let bomb_fp = actor.bombing() as f64;
-                    ((fp + torpedo_fp + bomb_fp) * 1.5).floor() + 55.0
-                } else {
-                    actor.firepower() as f64 + 5.0
-                };
-
-                let precap_fp = basic_fp
-                    * self.setup.direction().fp_factor()
-                    * actor.damaged_level(actor_snapshot).fp_factor();
-                let capped_fp = precap_fp.min(cap) + (precap_fp - cap).max(0.0).sqrt().floor();
-                let postcap_fp = capped_fp * 1.0; // 今後の調整をここで行う
+            // 夜戦で攻撃できる艦は艦載機によらず砲撃するため、陸上型も狙える
+            let can_target_installation =
+                phase == Phase::Night || day_attack::can_target_installation(actor, actor_snapshot);
+            // 空母系は夜戦で対潜攻撃を行わない
+            let can_attack_submarine = AswAttackKind::of(actor).is_some()
+                && !(phase == Phase::Night && actor.is_carrier_class());
+            // -- 攻撃対象の選定と防御力計算 --
 
-                postcap_fp
+            // 対潜攻撃できる艦は潜水艦を優先して狙い、対潜攻撃できない艦は潜水艦を狙えない
+            let can_target: fn(&Ship) -> bool = if can_target_installation {
+                |ship| !ship.is_submarine()
+            } else {
+                |ship| !ship.is_submarine() && !ship.is_installation()
+            };
+            let submarine = if can_attack_submarine {
+                self.random_target(actor_is_friend, Ship::is_submarine)
+            } else {
+                None
+            };
+            // 夜戦では探照灯を照射した艦が狙われやすい
+            let target_equipment = night_equipment
+                .as_ref()
+                .map(|(friend, enemy)| if actor_is_friend { enemy } else { friend })
+                .filter(|equipment| equipment.searchlight.is_some());
+            let Some(target_idx) = submarine.or_else(|| match target_equipment {
+                Some(equipment) => {
+                    self.weighted_random_target(actor_is_friend, can_target, |idx| {
+                        equipment.target_weight(idx)
+                    })
+                }
+                None => self.random_target(actor_is_friend, can_target),
+            }) else {
+                self.log.push(ActionLog::TurnSkip {
+                    is_friend: actor_is_friend,
+                    ship_idx: actor_idx,
+                    reason: "No Target".to_string(),
+                });
+                continue;
+            };
+            let accuracy = self.artillery_accuracy(&phase, actor_is_friend, actor_idx, target_idx);
+            let special_attack = match &night_equipment {
+                Some((friend, enemy)) => {
+                    let modifier = if actor_is_friend {
+                        NightEquipment::cut_in_modifier(friend, enemy)
+                    } else {
+                        NightEquipment::cut_in_modifier(enemy, friend)
+                    };
+                    self.night_special_attack(actor_is_friend, actor_idx, target_idx, modifier)
+                }
+                None => self.artillery_spotting(actor_is_friend, actor_idx, target_idx),
+            };
+            let (method, hits) = match special_attack {
+                Some(kind) => (AttackMethod::Special(kind), kind.hits()),
+                None => (AttackMethod::Single, 1),
             };
 
-            // -- 攻撃対象の選定と防御力計算 --
+            for _ in 0..hits {
+                let attack = self.calculate_attack(
+                    &phase,
+                    actor_is_friend,
+                    actor_idx,
+                    target_idx,
+                    accuracy,
+                    method,
+                );
+
+                // -- ダメージの適用 --
 
-            let (target_idx, target, target_snapshot) = self.random_target(actor_is_friend);
+                self.resolve_attack(attack);
+            }
+        }
+    }
 
-            let armor = {
-                let armor = target.armor() as f64;
-                let r: f64 = rand::random();
-                armor * 0.7 + (armor * r).floor() * 0.6
+    /// 砲撃戦で `actor_idx` の艦が `target_idx` の艦を攻撃する際の命中項を計算する。
+    /// 昼戦では攻撃側の陣形の命中補正がかかり、夜戦ではかからない。
+    fn artillery_accuracy(
+        &self,
+        phase: &Phase,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        target_idx: usize,
+    ) -> f64 {
+        let (actor, target) = if actor_is_friend {
+            (
+                &self.setup.friend_fleet.ships()[actor_idx],
+                &self.setup.enemy_fleet.ships()[target_idx],
+            )
+        } else {
+            (
+                &self.setup.enemy_fleet.ships()[actor_idx],
+                &self.setup.friend_fleet.ships()[target_idx],
+            )
+        };
+        if *phase == Phase::Night {
+            night_attack::accuracy(actor, target)
+        } else {
+            let accuracy_modifier = self.formation_factor(actor_is_friend, actor_idx).accuracy;
+            day_attack::accuracy(actor, target, accuracy_modifier)
+        }
+    }
+
+    /// 夜戦で `actor_idx` の艦が `target_idx` の艦を攻撃する際に発動する連撃・カットインを判定する。
+    /// 潜水艦への攻撃と空母系の夜間航空攻撃では特殊攻撃を行わない。
+    /// `modifier` は照明弾・探照灯によるカットイン率の補正値。
+    fn night_special_attack(
+        &mut self,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        target_idx: usize,
+        modifier: f64,
+    ) -> Option<SpecialAttack> {
+        let (candidates, cut_in_value) = {
+            let (actor, actor_snapshot, target) = if actor_is_friend {
+                (
+                    &self.setup.friend_fleet.ships()[actor_idx],
+                    &self.log.friend_snapshots[actor_idx],
+                    &self.setup.enemy_fleet.ships()[target_idx],
+                )
+            } else {
+                (
+                    &self.setup.enemy_fleet.ships()[actor_idx],
+                    &self.log.enemy_snapshots[actor_idx],
+                    &self.setup.friend_fleet.ships()[target_idx],
+                )
             };
+            if target.is_submarine()
+                || (actor.is_carrier_class() && actor.has_night_aircraft(actor_snapshot))
+            {
+                return None;
+            }
+            let cut_in_value = luck::night_cut_in_value(
+                actor.luck(),
+                actor_idx == 0,
+                actor_snapshot.damaged_level(),
+                modifier,
+            );
+            (night_attack::special_attack_candidates(actor), cut_in_value)
+        };
+        let candidates = candidates
+            .into_iter()
+            .map(|kind| (kind, night_attack::special_attack_rate(&kind, cut_in_value)))
+            .collect();
+        self.roll_special_attack(actor_is_friend, actor_idx, candidates)
+    }
 
-            // -- ダメージ計算と適用 --
+    /// 昼砲撃戦で `actor_idx` の艦が `target_idx` の艦を攻撃する際に発動する弾着観測射撃を判定する。
+    /// 自艦隊から見て航空優勢以上で、搭載機が残っている水上偵察機・水上爆撃機を装備している場合に発動しうる。
+    /// 潜水艦への攻撃と空母系の攻撃では発動しない。
+    fn artillery_spotting(
+        &mut self,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        target_idx: usize,
+    ) -> Option<SpecialAttack> {
+        let air_state = if actor_is_friend {
+            self.air_state()
+        } else {
+            self.air_state().reversed()
+        };
+        if !air_state.is_superiority_or_better() {
+            return None;
+        }
+        let candidates = {
+            let (ships, snapshots, target) = if actor_is_friend {
+                (
+                    self.setup.friend_fleet.ships(),
+                    &self.log.friend_snapshots,
+                    &self.setup.enemy_fleet.ships()[target_idx],
+                )
+            } else {
+                (
+                    self.setup.enemy_fleet.ships(),
+                    &self.log.enemy_snapshots,
+                    &self.setup.friend_fleet.ships()[target_idx],
+                )
+            };
+            let (actor, actor_snapshot) = (&ships[actor_idx], &snapshots[actor_idx]);
+            if target.is_submarine()
+                || actor.has_attack_aircraft(actor_snapshot)
+                || !actor.has_spotting_plane(actor_snapshot)
+            {
+                return None;
+            }
+            let fleet_scouting = Self::filter_alive(ships, snapshots)
+                .iter()
+                .map(|(_, ship)| ship.scouting())
+                .sum();
+            let value = day_attack::spotting_value(
+                actor,
+                actor_idx == 0,
+                fleet_scouting,
+                air_state == AirState::AirSupremacy,
+            );
+            day_attack::spotting_candidates(actor)
+                .into_iter()
+                .map(|kind| (kind, day_attack::spotting_rate(&kind, value)))
+                .collect()
+        };
+        self.roll_special_attack(actor_is_friend, actor_idx, candidates)
+    }
 
-            let damage = {
-                let diff = (firepower - armor).floor();
-                let hp_now = target_snapshot.hp() as f64;
-                let calculated_damage = if diff > 0.0 {
-                    diff
-                } else {
-                    // カスダメ化
-                    let r = rand::random::<f64>();
-                    hp_now * 0.06 + f64::floor(hp_now * r) * 0.08
-                };
+    /// 特殊攻撃の種別と発動率の組を順に判定し、最初に発動したものを返す。判定の結果は戦闘ログに記録する。
+    fn roll_special_attack(
+        &mut self,
+        actor_is_friend: bool,
+        actor_idx: usize,
+        candidates: Vec<(SpecialAttack, f64)>,
+    ) -> Option<SpecialAttack> {
+        for (special_attack, rate) in candidates {
+            let triggered = self.log.random(RngLabel::SpecialAttack) < rate;
+            self.log.push(ActionLog::SpecialAttackRoll {
+                is_friend: actor_is_friend,
+                ship_idx: actor_idx,
+                special_attack,
+                rate,
+                triggered,
+            });
+            if triggered {
+                return Some(special_attack);
+            }
+        }
+        None
+    }
 
-                let adjusted_damage = if !actor_is_friend && calculated_damage >= hp_now {
-                    if target_idx == 0 {
-                        let r: f64 = rand::random();
-                        f64::floor(hp_now * 0.5 + f64::floor(hp_now * r) * 0.3) as u16
-                    } else {
-                        hp_now as u16 - 1
-                    }
-                } else {
-                    calculated_damage as u16
-                };
+    /// 砲撃戦を行う。1巡目は射程順、戦艦級がいる場合の2巡目は艦隊内の並び順に攻撃する。
+    pub fn artillery_phase(&mut self) {
+        self.first_artillery_round();
+        if self.setup.includes_battleship_class() {
+            self.second_artillery_round();
+        }
+    }
+
+    /// 砲撃戦1巡目を行う。行動順は射程順。
+    /// 開始時にタッチ系特殊攻撃が発動した場合、味方旗艦はこの巡の通常の攻撃を行わない。
+    pub fn first_artillery_round(&mut self) {
+        self.log.push(ActionLog::PhaseStart(Phase::FirstArtillery));
+        let touched = self.touch_attack();
+        let fire_order = self
+            .ordered_by_range()
+            .into_iter()
+            .filter(|actor| !(touched && *actor == (true, 0)))
+            .collect();
+        self.artillery_phase_helper(Phase::FirstArtillery, fire_order);
+    }
 
-                adjusted_damage
+    /// 味方旗艦がタッチ系特殊攻撃の発動条件を満たしていれば発動を判定し、発動した場合は参加する艦が順に攻撃する。
+    /// 各艦は潜水艦以外の敵艦からランダムに攻撃対象を選ぶ。この出撃ですでに発動している場合は判定しない。
+    /// 発動したかどうかを返す。
+    fn touch_attack(&mut self) -> bool {
+        if self.setup.touch_attack_used() {
+            return false;
+        }
+        let formation = self.setup.formation(true);
+        let Some(kind) = touch_attack::candidate(
+            self.setup.friend_fleet.ships(),
+            &self.log.friend_snapshots,
+            formation,
+        ) else {
+            return false;
+        };
+        let rate = touch_attack::rate(&kind, self.log.friend_snapshots[0].morale(), formation);
+        if self
+            .roll_special_attack(true, 0, vec![(kind, rate)])
+            .is_none()
+        {
+            return false;
+        }
+
+        let participants = touch_attack::participants(&kind, self.setup.friend_fleet.ships());
+        for (actor_idx, multiplier) in participants {
+            if let Err(reason) = self.actor(&Phase::FirstArtillery, true, actor_idx) {
+                self.log.push(ActionLog::TurnSkip {
+                    is_friend: true,
+                    ship_idx: actor_idx,
+                    reason,
+                });
+                continue;
+            }
+            let Some(target_idx) = self.random_target(true, |ship| !ship.is_submarine()) else {
+                break;
             };
+            let accuracy =
+                self.artillery_accuracy(&Phase::FirstArtillery, true, actor_idx, target_idx);
+            let attack = self.calculate_attack(
+                &Phase::FirstArtillery,
+                true,
+                actor_idx,
+                target_idx,
+                accuracy,
+                AttackMethod::Touch(kind, multiplier),
+            );
+            self.resolve_attack(attack);
+        }
+        true
+    }
+
+    /// 砲撃戦2巡目を行う。行動順は艦隊内の並び順で、戦艦級の有無は確認しない。
+    pub fn second_artillery_round(&mut self) {
+        self.log.push(ActionLog::PhaseStart(Phase::SecondArtillery));
+        let fire_order = self.ordered_by_index();
+        self.artillery_phase_helper(Phase::SecondArtillery, fire_order);
+    }
+
+    /// 開幕雷撃を行う。
+    pub fn opening_torpedo_phase(&mut self) {
+        self.torpedo_phase(Phase::OpeningTorpedo);
+    }
+
+    /// 閉幕雷撃を行う。
+    pub fn closing_torpedo_phase(&mut self) {
+        self.torpedo_phase(Phase::ClosingTorpedo);
+    }
 
-            target_snapshot.apply_damage(damage);
-            self.log.push(ActionLog::Attack(AttackLog {
-                to_enemy: !actor_is_friend,
+    /// 雷撃戦を行う。参加できる艦が艦隊内の並び順に攻撃対象とダメージを決め、
+    /// フェーズの終わりにすべての攻撃のダメージをまとめて適用する。
+    /// そのため、このフェーズ中に撃沈される艦も攻撃を行い、既に撃沈が決まった艦も攻撃対象になりうる。
+    fn torpedo_phase(&mut self, phase: Phase) {
+        self.log.push(ActionLog::PhaseStart(phase.clone()));
+
+        let mut attacks = Vec::new();
+        for (actor_is_friend, actor_idx) in self.ordered_by_index() {
+            let actor = if actor_is_friend {
+                &self.setup.friend_fleet.ships()[actor_idx]
+            } else {
+                &self.setup.enemy_fleet.ships()[actor_idx]
+            };
+            if !torpedo_attack::can_attack(&phase, actor) {
+                continue;
+            }
+            let accuracy = match self.actor(&phase, actor_is_friend, actor_idx) {
+                Ok((actor, _)) => torpedo_attack::accuracy(actor),
+                Err(reason) => {
+                    self.log.push(ActionLog::TurnSkip {
+                        is_friend: actor_is_friend,
+                        ship_idx: actor_idx,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            let Some(target_idx) = self.random_target(actor_is_friend, torpedo_attack::can_target)
+            else {
+                self.log.push(ActionLog::TurnSkip {
+                    is_friend: actor_is_friend,
+                    ship_idx: actor_idx,
+                    reason: "No Target".to_string(),
+                });
+                continue;
+            };
+            attacks.push(self.calculate_attack(
+                &phase,
+                actor_is_friend,
                 actor_idx,
                 target_idx,
-                attack_type: AttackType::Artillery,
-                firepower: firepower as u16,
-                armor: armor as u16,
-                calculated_damage: damage,
-                applied_damage: damage,
-                is_critical: false,
-                is_miss: false,
-            }));
+                accuracy,
+                AttackMethod::Single,
+            ));
+        }
+
+        for attack in attacks {
+            self.resolve_attack(attack);
         }
     }
 
-    pub fn artillery_phase(&mut self) {
-        self.log.push(ActionLog::PhaseStart(Phase::Artillery));
+    /// 基地航空隊の攻撃を行う。出撃した基地航空隊のうち、このマスを攻撃目標とするものが基地航空隊の順に攻撃する。
+    /// 攻撃する基地航空隊がない場合は、フェーズの開始も記録しない。
+    /// 撃墜による中隊の損耗は同じ基地航空隊の次の波に引き継ぐが、味方艦隊のスナップショットには含めない。
+    pub fn land_base_phase(&mut self) {
+        let node = self.setup.enemy_fleet.node();
+        let waves = self
+            .setup
+            .land_bases()
+            .iter()
+            .enumerate()
+            .flat_map(|(i, base)| std::iter::repeat_n(i, base.waves_at(node)))
+            .collect::<Vec<_>>();
+        if waves.is_empty() {
+            return;
+        }
+        self.log.push(ActionLog::PhaseStart(Phase::LandBase));
 
-        let fire_order = self.ordered_by_range();
-        self.artillery_phase_helper(fire_order);
+        let mut counts = self
+            .setup
+            .land_bases()
+            .iter()
+            .map(|base| base.sortie_squadrons().map(|(_, c)| c).collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        for base_idx in waves {
+            self.land_base_wave(base_idx, &mut counts[base_idx]);
+        }
+    }
 
-        if self.setup.includes_battleship_class() {
-            self.log.push(ActionLog::PhaseStart(Phase::Artillery));
-            let fire_order = self.ordered_by_index();
-            self.artillery_phase_helper(fire_order);
+    /// 味方艦隊から見た現在の制空状態。航空戦を行った場合はその開始時の状態、行っていない場合は戦闘開始時の状態とする。
+    fn air_state(&self) -> AirState {
+        self.log
+            .air_state()
+            .unwrap_or_else(|| self.setup.air_state())
+    }
+
+    /// 指定された艦隊の生存艦の、現在の搭載数での制空値の合計。
+    fn fighter_power(&self, is_friend: bool) -> u32 {
+        let (ships, snapshots) = if is_friend {
+            (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+        } else {
+            (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+        };
+        ships
+            .iter()
+            .zip(snapshots)
+            .filter(|(_, snapshot)| snapshot.is_alive())
+            .map(|(ship, snapshot)| ship.fighter_power_with_slots(snapshot.slots()))
+            .sum()
+    }
+
+    /// 基地航空隊の1波分の攻撃を行う。`counts` は各中隊の残りの搭載数で、撃墜された分を減らす。
+    /// stage 1 で敵艦隊の制空値と争った後、攻撃機はランダムに選ばれた敵艦の対空砲火 (stage 2) を受けてから攻撃する。
+    /// 航空戦と同様に、ダメージは波の終わりにまとめて適用する。
+    fn land_base_wave(&mut self, base_idx: usize, counts: &mut [u16]) {
+        let planes = self.setup.land_bases()[base_idx]
+            .sortie_squadrons()
+            .map(|(s, _)| s.clone())
+            .collect::<Vec<_>>();
+
+        // -- stage 1: 制空状態に応じた撃墜 --
+
+        let friend_power = planes
+            .iter()
+            .zip(counts.iter())
+            .map(|(s, count)| s.fighter_power(*count))
+            .sum();
+        let air_state = AirState::from_fighter_power(friend_power, self.fighter_power(false));
+        self.log.push(ActionLog::LandBaseWave {
+            base_idx,
+            air_state,
+        });
+        for count in counts.iter_mut().filter(|c| **c > 0) {
+            *count -= self.stage1_loss(*count, air_state.stage1_loss_step());
+        }
+        self.shoot_down(false, air_state.reversed().stage1_loss_step());
+
+        // -- stage 2 と航空攻撃 --
+
+        let (fleet_anti_aircraft, cut_in) = self.anti_aircraft_defense(false);
+        let mut attacks = Vec::new();
+        for (squadron_idx, squadron) in planes.iter().enumerate() {
+            if !land_base_attack::can_strike(squadron.plane()) || counts[squadron_idx] == 0 {
+                continue;
+            }
+            let count = &mut counts[squadron_idx];
+            let Some((shooter_idx, shot_down)) =
+                self.anti_aircraft_fire(false, fleet_anti_aircraft, cut_in, *count)
+            else {
+                break;
+            };
+            self.log.push(ActionLog::LandBaseAntiAircraftFire {
+                base_idx,
+                squadron_idx,
+                shooter_idx,
+                shot_down,
+            });
+            *count -= shot_down;
+            if *count == 0 {
+                continue;
+            }
+            let count = *count;
+
+            let can_target: fn(&Ship) -> bool = if squadron.plane().can_attack_installation() {
+                land_base_attack::can_target
+            } else {
+                land_base_attack::can_target_except_installation
+            };
+            let Some(target_idx) = self.random_target(true, can_target) else {
+                continue;
+            };
+            let attack = if self.setup.fixed_point() {
+                self.land_base_attack::<Fixed>(base_idx, squadron_idx, squadron, count, target_idx)
+            } else {
+                self.land_base_attack::<f64>(base_idx, squadron_idx, squadron, count, target_idx)
+            };
+            attacks.push(attack);
+        }
+
+        for attack in attacks {
+            self.resolve_attack(attack);
         }
     }
 
-    pub fn into_battle_report(self) -> BattleReport {
-        // Use this battle's setup and snapshot to build the report.
-        // call calculate using the final state twice to keep the original signature expectations; adjust if calculate expects other types
-        let result = battle_result::BattleResult::calculate(&self);
-        let friend_fleet = self
+    /// 基地航空隊の1中隊から `target_idx` の敵艦への攻撃1回分のダメージを、数値の型 `N` で計算する。
+    /// `scripting` フィーチャーの補正式は、攻撃する艦がいないため適用しない。
+    fn land_base_attack<N: Scalar>(
+        &mut self,
+        base_idx: usize,
+        squadron_idx: usize,
+        squadron: &Squadron,
+        count: u16,
+        target_idx: usize,
+    ) -> AttackLog {
+        let firepower =
+            land_base_attack::power::<N>(squadron.plane(), count, self.setup.constants());
+        let accuracy = land_base_attack::accuracy(squadron.plane());
+        let (hit, firepower, armor, calculated_damage) =
+            self.damage(true, target_idx, firepower, accuracy);
+        AttackLog {
+            to_enemy: true,
+            actor_idx: squadron_idx,
+            target_idx,
+            attack_type: AttackType::LandBaseAirStrike(base_idx),
+            special_attack: None,
+            multiplier: 1.0,
+            firepower: firepower.to_f64() as u16,
+            armor: armor.to_f64() as u16,
+            calculated_damage: calculated_damage.to_f64() as u16,
+            applied_damage: 0,
+            is_critical: hit == HitKind::Critical,
+            is_miss: hit == HitKind::Miss,
+            stopped: false,
+        }
+    }
+
+    /// 航空戦を行う。制空状態を記録し、stage 1 で両艦隊の艦載機を撃墜した後、
+    /// stage 2 で攻撃機が相手艦隊の対空砲火を受けてから航空攻撃を行う。
+    /// 航空攻撃は攻撃機を搭載したスロットごとに1回行い、雷撃戦と同様にフェーズの終わりにダメージをまとめて適用する。
+    pub fn air_combat_phase(&mut self) {
+        self.log.push(ActionLog::PhaseStart(Phase::AirCombat));
+        // 基地航空隊の攻撃で撃墜された分を反映するため、現在の搭載数から制空状態を決める
+        let air_state =
+            AirState::from_fighter_power(self.fighter_power(true), self.fighter_power(false));
+        self.log.set_air_state(air_state);
+
+        // -- stage 1: 制空状態に応じた撃墜 --
+
+        self.shoot_down(true, air_state.stage1_loss_step());
+        self.shoot_down(false, air_state.reversed().stage1_loss_step());
+
+        // -- stage 2: 対空砲火 --
+
+        for attacker_is_friend in [true, false] {
+            self.anti_aircraft_phase(attacker_is_friend);
+        }
+
+        // -- 航空攻撃 --
+
+        let mut attacks = Vec::new();
+        for (actor_is_friend, actor_idx) in self.ordered_by_index() {
+            let (ships, snapshots) = if actor_is_friend {
+                (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+            } else {
+                (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+            };
+            let slots = air_attack::strike_slots(&ships[actor_idx], &snapshots[actor_idx]);
+            if slots.is_empty() {
+                continue;
+            }
+            let accuracy = match self.actor(&Phase::AirCombat, actor_is_friend, actor_idx) {
+                Ok((actor, _)) => air_attack::accuracy(actor),
+                Err(reason) => {
+                    self.log.push(ActionLog::TurnSkip {
+                        is_friend: actor_is_friend,
+                        ship_idx: actor_idx,
+                        reason,
+                    });
+                    continue;
+                }
+            };
+            for slot_idx in slots {
+                let actor = if actor_is_friend {
+                    &self.setup.friend_fleet.ships()[actor_idx]
+                } else {
+                    &self.setup.enemy_fleet.ships()[actor_idx]
+                };
+                let can_target: fn(&Ship) -> bool =
+                    if actor.equips()[slot_idx].can_attack_installation() {
+                        air_attack::can_target
+                    } else {
+                        air_attack::can_target_except_installation
+                    };
+                let Some(target_idx) = self.random_target(actor_is_friend, can_target) else {
+                    continue;
+                };
+                attacks.push(self.calculate_attack(
+                    &Phase::AirCombat,
+                    actor_is_friend,
+                    actor_idx,
+                    target_idx,
+                    accuracy,
+                    AttackMethod::AirStrike(slot_idx),
+                ));
+            }
+        }
+
+        for attack in attacks {
+            self.resolve_attack(attack);
+        }
+    }
+
+    /// 航空戦 stage 1 で、指定された艦隊の制空値の計算に含まれる艦載機を撃墜する。
+    /// 撃墜数の計算は `AirState::stage1_loss_step` を参照。
+    fn shoot_down(&mut self, is_friend: bool, loss_step: u16) {
+        let ships = if is_friend {
+            self.setup.friend_fleet.ships()
+        } else {
+            self.setup.enemy_fleet.ships()
+        };
+        let planes = ships
+            .iter()
+            .enumerate()
+            .flat_map(|(ship_idx, ship)| {
+                ship.equips()
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, e)| e.category().counts_for_air_power())
+                    .map(move |(slot_idx, _)| (ship_idx, slot_idx))
+            })
+            .collect::<Vec<_>>();
+        for (ship_idx, slot_idx) in planes {
+            let snapshots = if is_friend {
+                &self.log.friend_snapshots
+            } else {
+                &self.log.enemy_snapshots
+            };
+            let snapshot = &snapshots[ship_idx];
+            let count = snapshot.slots().get(slot_idx).copied().unwrap_or(0);
+            if count == 0 || !snapshot.is_alive() {
+                continue;
+            }
+            let loss = self.stage1_loss(count, loss_step);
+            let snapshots = if is_friend {
+                &mut self.log.friend_snapshots
+            } else {
+                &mut self.log.enemy_snapshots
+            };
+            snapshots[ship_idx].lose_planes(slot_idx, loss);
+        }
+    }
+
+    /// 航空戦 stage 2 で、`attacker_is_friend` 側の艦隊の攻撃機が相手艦隊の対空砲火を受ける。
+    /// 攻撃機のスロットがない場合は、対空カットインの判定も行わない。
+    fn anti_aircraft_phase(&mut self, attacker_is_friend: bool) {
+        let (ships, snapshots) = if attacker_is_friend {
+            (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+        } else {
+            (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+        };
+        let slots = ships
+            .iter()
+            .zip(snapshots)
+            .enumerate()
+            .filter(|(_, (_, snapshot))| snapshot.is_alive())
+            .flat_map(|(ship_idx, (ship, snapshot))| {
+                air_attack::strike_slots(ship, snapshot)
+                    .into_iter()
+                    .map(move |slot_idx| (ship_idx, slot_idx))
+            })
+            .collect::<Vec<_>>();
+        if slots.is_empty() {
+            return;
+        }
+
+        let (fleet_anti_aircraft, cut_in) = self.anti_aircraft_defense(!attacker_is_friend);
+        for (ship_idx, slot_idx) in slots {
+            let snapshots = if attacker_is_friend {
+                &self.log.friend_snapshots
+            } else {
+                &self.log.enemy_snapshots
+            };
+            let count = snapshots[ship_idx].slots()[slot_idx];
+            let Some((shooter_idx, shot_down)) =
+                self.anti_aircraft_fire(!attacker_is_friend, fleet_anti_aircraft, cut_in, count)
+            else {
+                return;
+            };
+            let snapshots = if attacker_is_friend {
+                &mut self.log.friend_snapshots
+            } else {
+                &mut self.log.enemy_snapshots
+            };
+            snapshots[ship_idx].lose_planes(slot_idx, shot_down);
+            self.log.push(ActionLog::AntiAircraftFire {
+                is_friend: attacker_is_friend,
+                ship_idx,
+                slot_idx,
+                shooter_idx,
+                shot_down,
+            });
+        }
+    }
+
+    /// 対空砲火を行う `is_friend` 側の艦隊の艦隊防空値を計算し、味方艦隊であれば対空カットインを判定する。
+    /// 敵艦隊は対空カットインを行わない。艦隊防空値の陣形補正には、警戒陣でも前半の艦の値を使う。
+    fn anti_aircraft_defense(&mut self, is_friend: bool) -> (f64, Option<AntiAirCutIn>) {
+        let (ships, snapshots) = if is_friend {
+            (self.setup.friend_fleet.ships(), &self.log.friend_snapshots)
+        } else {
+            (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots)
+        };
+        let alive = ships
+            .iter()
+            .zip(snapshots)
+            .filter(|(_, snapshot)| snapshot.is_alive())
+            .map(|(ship, _)| ship);
+        let formation = self.formation_factor(is_friend, 0).anti_aircraft;
+        let fleet_anti_aircraft = anti_air::fleet_anti_aircraft(alive, formation);
+        let cut_in = if is_friend {
+            self.roll_anti_air_cut_in()
+        } else {
+            None
+        };
+        (fleet_anti_aircraft, cut_in)
+    }
+
+    /// 味方艦隊の生存艦を並び順に、発動条件を満たす対空カットインを優先度の高い順に判定し、最初に発動したものを返す。
+    /// 判定の結果は戦闘ログに記録する。
+    fn roll_anti_air_cut_in(&mut self) -> Option<AntiAirCutIn> {
+        let candidates = self
             .setup
             .friend_fleet
-            .apply_snapshot(&self.log.friend_snapshots);
-        let enemy_fleet = self
+            .ships()
+            .iter()
+            .zip(&self.log.friend_snapshots)
+            .enumerate()
+            .filter(|(_, (_, snapshot))| snapshot.is_alive())
+            .flat_map(|(ship_idx, (ship, _))| {
+                AntiAirCutIn::candidates(ship)
+                    .into_iter()
+                    .map(move |cut_in| (ship_idx, cut_in))
+            })
+            .collect::<Vec<_>>();
+        for (ship_idx, cut_in) in candidates {
+            let rate = cut_in.rate();
+            let triggered = self.log.random(RngLabel::AntiAircraft) < rate;
+            self.log.push(ActionLog::AntiAirCutInRoll {
+                ship_idx,
+                cut_in,
+                rate,
+                triggered,
+            });
+            if triggered {
+                return Some(cut_in);
+            }
+        }
+        None
+    }
+
+    /// `is_friend` 側の艦隊からランダムに選ばれた生存艦が、搭載数 `count` のスロットに対空砲火を行う。
+    /// 対空砲火を行った艦の位置と撃墜数を返す。生存艦がいない場合は `None` を返す。
+    fn anti_aircraft_fire(
+        &mut self,
+        is_friend: bool,
+        fleet_anti_aircraft: f64,
+        cut_in: Option<AntiAirCutIn>,
+        count: u16,
+    ) -> Option<(usize, u16)> {
+        let shooter_idx = self.random_target(!is_friend, |_| true)?;
+        let shooter = if is_friend {
+            &self.setup.friend_fleet.ships()[shooter_idx]
+        } else {
+            &self.setup.enemy_fleet.ships()[shooter_idx]
+        };
+        let weighted = anti_air::weighted_anti_aircraft(shooter);
+        let proportional = self.log.random(RngLabel::AntiAircraft) < 0.5;
+        let fixed = self.log.random(RngLabel::AntiAircraft) < 0.5;
+        let shot_down = anti_air::stage2_loss(
+            count,
+            weighted,
+            fleet_anti_aircraft,
+            cut_in,
+            proportional,
+            fixed,
+        );
+        Some((shooter_idx, shot_down))
+    }
+
+    /// 搭載数 `count` のスロットが stage 1 で撃墜される数を、乱数を引いて決める。
+    fn stage1_loss(&mut self, count: u16, loss_step: u16) -> u16 {
+        let mut roll = || {
+            let r = self.log.random(RngLabel::PlaneLoss);
+            ((r * (loss_step + 1) as f64) as u16).min(loss_step) as f64
+        };
+        let (x, y) = (roll(), roll());
+        (count as f64 * (0.65 * x + 0.35 * y) / 10.0).floor() as u16
+    }
+
+    /// 夜戦を行う。友軍艦隊が出現した場合はその攻撃の後に、味方と敵の生存艦が艦隊内の並び順に交互に攻撃する。
+    pub fn night_phase(&mut self) {
+        self.log.push(ActionLog::PhaseStart(Phase::Night));
+        self.friendly_fleet_support();
+
+        let fire_order = self.ordered_by_index();
+        self.artillery_phase_helper(Phase::Night, fire_order);
+    }
+
+    /// 友軍艦隊の出現を判定し、出現した場合は味方艦隊より先に敵艦隊と夜戦を行わせる。
+    /// 友軍艦隊の戦闘は友軍艦隊自身のスナップショットを持つ別の戦闘として計算し、敵艦隊の損害だけを引き継ぐ。
+    /// 戦闘ログには友軍艦隊の出現、友軍艦隊から敵艦への攻撃、敵艦の撃沈だけを記録し、友軍艦隊の損害は記録しない。
+    fn friendly_fleet_support(&mut self) {
+        let Some(table) = self.setup.friendly_fleet() else {
+            return;
+        };
+        let r = self.log.random(RngLabel::FriendlyFleet);
+        let Some((fleet_idx, candidate)) = table.choose(r) else {
+            return;
+        };
+        let friendly = candidate.to_fleet();
+        let enemy = self
             .setup
             .enemy_fleet
             .apply_snapshot(&self.log.enemy_snapshots);
+        let rng = rng::from_seed(Some(self.log.next_seed()));
+        let mut support = Battle {
+            setup: self.setup.with_fleets(&friendly, &enemy),
+            log: BattleLog::new(&friendly, &enemy, false, rng),
+        };
+        let fire_order = support.ordered_by_index();
+        support.artillery_phase_helper(Phase::Night, fire_order);
+
+        self.log.push(ActionLog::FriendlyFleetArrival { fleet_idx });
+        for action in support.log.actions() {
+            match action {
+                ActionLog::Attack(attack) if attack.to_enemy => {
+                    self.log
+                        .push(ActionLog::FriendlyFleetAttack(attack.clone()));
+                }
+                ActionLog::Sunk {
+                    is_friend: false, ..
+                } => self.log.push(action.clone()),
+                _ => {}
+            }
+        }
+        self.log.enemy_snapshots = support.log.enemy_snapshots;
+    }
+
+    /// 戦闘の初期設定への参照を取得します。
+    pub fn setup(&self) -> &BattleSetup {
+        &self.setup
+    }
+
+    /// 戦闘ログへの参照を取得します。
+    pub fn log(&self) -> &BattleLog {
+        &self.log
+    }
+
+    fn ship_refs(&self, side: FleetSide) -> impl Iterator<Item = ShipRef> + '_ {
+        let (ships, snapshots) = match side {
+            FleetSide::Friend => (self.setup.friend_fleet.ships(), &self.log.friend_snapshots),
+            FleetSide::Enemy => (self.setup.enemy_fleet.ships(), &self.log.enemy_snapshots),
+        };
+        ships
+            .iter()
+            .zip(snapshots)
+            .enumerate()
+            .map(move |(i, (ship, snapshot))| ShipRef::new(side, i, ship, snapshot))
+    }
+
+    /// 戦闘結果をレポートに変換します。
+    /// `enemy_index` は、入力された敵編成のうち何番目と戦ったかを表します。
+    /// `detail` に応じて、戦闘後の艦隊の代わりにスナップショットや艦の参照を含めます。
+    pub fn into_battle_report(self, enemy_index: usize, detail: ReportDetail) -> BattleReport {
+        let result = battle_result::BattleResult::calculate(&self);
+        let stats = BattleStats::from_log(&self.log);
+
+        let (friend_fleet, friend_snapshots) = match detail {
+            ReportDetail::Full | ReportDetail::OmitEnemyFleet => (
+                Some(
+                    self.setup
+                        .friend_fleet
+                        .apply_snapshot(&self.log.friend_snapshots),
+                ),
+                None,
+            ),
+            ReportDetail::SnapshotsOnly => (None, Some(self.log.friend_snapshots.clone())),
+            ReportDetail::ShipRefs => (None, None),
+        };
+        let (enemy_fleet, enemy_snapshots) = match detail {
+            ReportDetail::Full => (
+                Some(
+                    self.setup
+                        .enemy_fleet
+                        .apply_snapshot(&self.log.enemy_snapshots),
+                ),
+                None,
+            ),
+            ReportDetail::OmitEnemyFleet | ReportDetail::SnapshotsOnly => {
+                (None, Some(self.log.enemy_snapshots.clone()))
+            }
+            ReportDetail::ShipRefs => (None, None),
+        };
+        let ships = (detail == ReportDetail::ShipRefs).then(|| {
+            let friend = self.ship_refs(FleetSide::Friend);
+            let enemy = self.ship_refs(FleetSide::Enemy);
+            friend.chain(enemy).collect()
+        });
 
         BattleReport {
             result,
+            enemy_index,
             friend_fleet,
             enemy_fleet,
+            friend_snapshots,
+            enemy_snapshots,
+            ships,
+            stats,
+            #[cfg(feature = "debug-log")]
+            log: self.setup.debug().then_some(self.log),
+        }
+    }
+}
+
+/// `(インデックス, 重み)` の組から、乱数 `r` (0.0〜1.0) で重みに比例した確率で1つ選ぶ。
+/// 重みの合計が 0 の場合は一様に選ぶ。`weights` は空でないこと。
+fn pick_weighted(r: f64, weights: &[(usize, f64)]) -> usize {
+    let total: f64 = weights.iter().map(|(_, w)| w).sum();
+    if total <= 0.0 {
+        let n = ((r * weights.len() as f64) as usize).min(weights.len() - 1);
+        return weights[n].0;
+    }
+    let mut threshold = r * total;
+    for (idx, weight) in weights {
+        if threshold < *weight {
+            return *idx;
         }
+        threshold -= weight;
     }
+    weights[weights.len() - 1].0
 }
 
+/// 戦闘1回分の結果をフロントエンドに返すための構造体。
 #[derive(Serialize, Deserialize, Debug, Clone)]
 #[serde(rename_all = "camelCase")]
 pub struct BattleReport {
     result: battle_result::BattleResult,
-    friend_fleet: Fleet,
-    enemy_fleet: EnemyFleet,
+    /// 入力された敵編成のうち、この戦闘で選ばれたもののインデックス。
+    enemy_index: usize,
+    /// 戦闘後の味方艦隊。`ReportDetail::SnapshotsOnly` と `ReportDetail::ShipRefs` では省略される。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    friend_fleet: Option<Fleet>,
+    /// 戦闘後の敵艦隊。`ReportDetail::Full` 以外では省略される。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enemy_fleet: Option<EnemyFleet>,
+    /// 味方艦隊を省略した場合の、各艦の戦闘後のスナップショット。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    friend_snapshots: Option<Vec<ShipSnapshot>>,
+    /// 敵艦隊を省略した場合の、各艦の戦闘後のスナップショット。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    enemy_snapshots: Option<Vec<ShipSnapshot>>,
+    /// `ReportDetail::ShipRefs` の場合の、味方・敵すべての艦の参照。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    ships: Option<Vec<ShipRef>>,
+    /// 砲撃戦の巡数、攻撃回数、手番を飛ばした回数の概要。
+    #[serde(default)]
+    stats: BattleStats,
+    /// デバッグモード時のみ添付される戦闘ログ。
+    #[cfg(feature = "debug-log")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    log: Option<BattleLog>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn carrier(id: u16, fighter_slot: u16) -> Ship {
+        let fighter = serde_json::json!({
+            "id": 1, "equipTypeId": [0, 0, 6, 0, 0], "status": { "antiAircraft": 10 }
+        });
+        Ship::test_builder()
+            .id(id)
+            .ship_type(11)
+            .hp(80, 80)
+            .airplane_slots(&[fighter_slot])
+            .equips([fighter])
+            .build()
+    }
+
+    fn battle(land_bases: serde_json::Value, seed: u64) -> Battle {
+        let friend = Fleet::new(vec![carrier(1, 20)], None);
+        let enemy: EnemyFleet = serde_json::from_value(serde_json::json!({
+            "area": 1, "map": 1, "node": "A", "probability": 1.0,
+            "ships": [carrier(1510, 20)]
+        }))
+        .unwrap();
+        let options = SimulationOptions {
+            land_bases: serde_json::from_value(land_bases).unwrap(),
+            ..Default::default()
+        };
+        Battle::with_seed(&friend, &enemy, &options, seed)
+    }
+
+    #[test]
+    fn land_base_losses_change_air_state() {
+        let fighter = serde_json::json!({
+            "plane": { "id": 2, "equipTypeId": [0, 0, 6, 0, 0], "status": { "antiAircraft": 10 } },
+            "slot": 18,
+            "proficiency": 7
+        });
+        let land_bases = serde_json::json!([{
+            "squadrons": [fighter, fighter, fighter, fighter],
+            "action": "sortie",
+            "targets": ["A", "A"]
+        }]);
+
+        let mut superiority = 0;
+        for seed in 0..20 {
+            // 制空値は両艦隊とも floor(10 × √20) = 44 で航空均衡
+            let mut without = battle(serde_json::json!([]), seed);
+            assert_eq!(without.setup.air_state(), AirState::AirParity);
+            without.air_combat_phase();
+            assert_eq!(without.air_state(), AirState::AirParity);
+
+            let mut with = battle(land_bases.clone(), seed);
+            with.land_base_phase();
+            let enemy_power = with.fighter_power(false);
+            with.air_combat_phase();
+            assert_eq!(
+                with.air_state(),
+                AirState::from_fighter_power(44, enemy_power)
+            );
+            if with.air_state() == AirState::AirSuperiority {
+                superiority += 1;
+            }
+        }
+        // 敵の艦戦が 8 機以下まで撃墜されると航空優勢になる
+        assert!(superiority > 0);
+    }
 }
@@ -0,0 +1,137 @@
+use crate::battle::anti_submarine::{self, AswAttackKind};
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::{fit_gun, luck, AttackType, BattleDirection, ShipSnapshot, SpecialAttack};
+use crate::fleet::{EquipCategory, Equipment, Ship};
+use crate::formula::{FormationFactor, FormulaConstants};
+
+/// 夜戦連撃の発動率。
+const NIGHT_DOUBLE_ATTACK_RATE: f64 = 0.99;
+
+/// 夜戦で `actor` が `target` を攻撃する際の命中項を計算する。
+/// 夜戦の命中は陣形の補正を受けず、昼戦のシナジーに代えて夜戦のシナジー (魚雷と水上電探など) による命中補正を加える。
+pub fn accuracy(actor: &Ship, target: &Ship) -> f64 {
+    let aiming = actor.equipment_aiming() as f64
+        + actor.improvement_bonus(Equipment::accuracy_improvement_bonus)
+        + fit_gun::accuracy_bonus(actor)
+        + actor.synergy(target).night_accuracy;
+    luck::accuracy(luck::NIGHT_ACCURACY_BASE, actor.luck(), aiming, 1.0)
+}
+
+/// 夜戦で `actor` が試みる特殊攻撃を、試みる順に列挙する。
+/// カットインは装備の組み合わせから1種類だけが選ばれ、発動しなかった場合は連撃の条件を満たせば連撃を試みる。
+pub fn special_attack_candidates(actor: &Ship) -> Vec<SpecialAttack> {
+    let main = actor.count_equips(EquipCategory::is_main_gun);
+    let secondary = actor.count_equips(|c| *c == EquipCategory::SecondaryGun);
+    let torpedo = actor.count_equips(EquipCategory::is_torpedo);
+
+    let cut_in = if main >= 3 {
+        Some(SpecialAttack::MainGunCutIn)
+    } else if main >= 2 && secondary >= 1 {
+        Some(SpecialAttack::MainSecondaryCutIn)
+    } else if torpedo >= 2 {
+        Some(SpecialAttack::TorpedoCutIn)
+    } else if main >= 1 && torpedo >= 1 {
+        Some(SpecialAttack::MainTorpedoCutIn)
+    } else {
+        None
+    };
+    let double_attack = main >= 2 || (main >= 1 && secondary >= 1) || secondary >= 2;
+    cut_in
+        .into_iter()
+        .chain(double_attack.then_some(SpecialAttack::NightDoubleAttack))
+        .collect()
+}
+
+/// 夜戦の特殊攻撃の発動率 (0.0〜1.0) を計算する。
+/// カットインは発動値 (`luck::night_cut_in_value`) を種別ごとの係数で割った値を発動率 (%) とする。
+pub fn special_attack_rate(kind: &SpecialAttack, cut_in_value: f64) -> f64 {
+    let factor = match kind {
+        SpecialAttack::NightDoubleAttack => return NIGHT_DOUBLE_ATTACK_RATE,
+        SpecialAttack::MainTorpedoCutIn => 115.0,
+        SpecialAttack::TorpedoCutIn => 122.0,
+        SpecialAttack::MainSecondaryCutIn => 130.0,
+        SpecialAttack::MainGunCutIn => 140.0,
+        _ => return 0.0,
+    };
+    (cut_in_value / factor).clamp(0.0, 1.0)
+}
+
+/// 夜戦で `actor` が `target` を攻撃する場合の、キャップ適用後の攻撃力と攻撃の種別を計算する。
+/// 基本攻撃力は火力と雷装と改修ボーナス、軽巡系のフィット砲補正の和で、陸上型に対しては雷装を加えない。夜間機を搭載した空母系は夜間航空攻撃を行う。
+/// 交戦形態の補正はかからず、特殊攻撃の倍率 `multiplier` はキャップ前にかかる。
+/// クリティカル補正とスクリプトによる補正は含まない。
+pub fn power<N: Scalar>(
+    actor: &Ship,
+    actor_snapshot: &ShipSnapshot,
+    target: &Ship,
+    multiplier: f64,
+    constants: &FormulaConstants,
+) -> (N, AttackType) {
+    if target.is_submarine() {
+        if let Some(kind) = AswAttackKind::of(actor) {
+            // 夜戦では交戦形態と陣形の補正がないため、同航戦・補正なしとして計算する
+            let power = anti_submarine::asw_power(
+                actor,
+                actor_snapshot,
+                target,
+                &BattleDirection::Same,
+                &FormationFactor::default(),
+                &kind,
+                constants,
+            );
+            return (power, AttackType::AntiSubmarine(kind));
+        }
+    }
+
+    // TODO: 夜間触接
+    let (basic_fp, attack_type) = if actor.is_carrier_class()
+        && actor.has_night_aircraft(actor_snapshot)
+    {
+        (
+            night_air_attack_basic_power(actor, actor_snapshot),
+            AttackType::AirStrike,
+        )
+    } else {
+        let improvement = N::from_f64(actor.improvement_bonus(Equipment::night_improvement_bonus));
+        let basic = if target.is_installation() {
+            N::from_int(actor.firepower() as i64)
+        } else {
+            N::from_int(actor.firepower() as i64 + actor.torpedo() as i64)
+        };
+        let fit = N::from_f64(fit_gun::firepower_bonus(actor));
+        (basic + improvement + fit, AttackType::Artillery)
+    };
+
+    let precap_fp = basic_fp
+        * N::from_f64(constants.damaged_level_factor(&actor.damaged_level(actor_snapshot)))
+        * N::from_f64(multiplier);
+    let capped_fp = apply_cap(precap_fp, N::from_f64(constants.night_cap));
+    (
+        capped_fp * N::from_f64(actor_snapshot.ammo_factor()),
+        attack_type,
+    )
+}
+
+/// 空母系の夜間航空攻撃の基本攻撃力を計算する。
+/// 素の火力に、搭載機が残っている夜間機ごとの `火力 + 雷装 + 3 × 搭載数 + 0.45 × (火力 + 雷装 + 爆装 + 対潜) × √搭載数` を加える。
+fn night_air_attack_basic_power<N: Scalar>(actor: &Ship, actor_snapshot: &ShipSnapshot) -> N {
+    actor
+        .equips()
+        .iter()
+        .zip(actor_snapshot.slots())
+        .filter(|(e, count)| e.is_night_aircraft() && **count > 0)
+        .fold(
+            N::from_int(actor.naked_firepower() as i64),
+            |power, (e, count)| {
+                let count = N::from_int(*count as i64);
+                let stats = e.firepower() as i64
+                    + e.torpedo() as i64
+                    + e.bombing() as i64
+                    + e.anti_submarine_warfare() as i64;
+                power
+                    + N::from_int(e.firepower() as i64 + e.torpedo() as i64)
+                    + N::from_int(3) * count
+                    + N::from_f64(0.45) * N::from_int(stats) * count.sqrt()
+            },
+        )
+}
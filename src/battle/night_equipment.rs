@@ -0,0 +1,66 @@
+use crate::battle::{DamagedLevel, ShipSnapshot};
+use crate::fleet::Ship;
+
+/// 照明弾・探照灯による夜戦のカットイン率補正 (%)。
+const OWN_STAR_SHELL_BONUS: f64 = 4.0;
+const OPPONENT_STAR_SHELL_PENALTY: f64 = -10.0;
+const OWN_SEARCHLIGHT_BONUS: f64 = 7.0;
+const OPPONENT_SEARCHLIGHT_PENALTY: f64 = -5.0;
+
+/// 探照灯を照射した艦が攻撃対象に選ばれる際の重み。他の艦は 1.0。
+const SEARCHLIGHT_TARGET_WEIGHT: f64 = 2.0;
+
+/// 夜戦で一方の艦隊が使う照明弾・探照灯の状態。
+/// 艦娘・深海棲艦を区別せず、装備から同じ規則で判定する。
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NightEquipment {
+    /// 照明弾を投射するか。
+    pub star_shell: bool,
+    /// 探照灯を照射する艦のインデックス。
+    pub searchlight: Option<usize>,
+}
+
+impl NightEquipment {
+    /// 艦隊の装備と戦闘中の状態から、夜戦で使われる照明弾・探照灯を判定する。
+    /// 照明弾は大破していない生存艦、探照灯は生存艦のうち先頭の 1 隻のみが使う。
+    pub fn of(ships: &[Ship], snapshots: &[ShipSnapshot]) -> Self {
+        let star_shell = ships.iter().zip(snapshots).any(|(ship, snapshot)| {
+            ship.has_star_shell() && ship.damaged_level(snapshot) < DamagedLevel::Heavy
+        });
+        let searchlight = ships
+            .iter()
+            .zip(snapshots)
+            .position(|(ship, snapshot)| ship.has_searchlight() && snapshot.is_alive());
+        Self {
+            star_shell,
+            searchlight,
+        }
+    }
+
+    /// 自艦隊と相手艦隊の照明弾・探照灯によるカットイン率の補正値 (%) を計算する。
+    pub fn cut_in_modifier(own: &Self, opponent: &Self) -> f64 {
+        let mut modifier = 0.0;
+        if own.star_shell {
+            modifier += OWN_STAR_SHELL_BONUS;
+        }
+        if opponent.star_shell {
+            modifier += OPPONENT_STAR_SHELL_PENALTY;
+        }
+        if own.searchlight.is_some() {
+            modifier += OWN_SEARCHLIGHT_BONUS;
+        }
+        if opponent.searchlight.is_some() {
+            modifier += OPPONENT_SEARCHLIGHT_PENALTY;
+        }
+        modifier
+    }
+
+    /// この艦隊の `idx` 番目の艦が、相手から夜戦の攻撃対象に選ばれる際の重みを取得する。
+    pub fn target_weight(&self, idx: usize) -> f64 {
+        if self.searchlight == Some(idx) {
+            SEARCHLIGHT_TARGET_WEIGHT
+        } else {
+            1.0
+        }
+    }
+}
@@ -0,0 +1,47 @@
+use crate::battle::{ActionLog, AttackLog, Battle, BattleLog, BattleResult, FleetSide};
+use crate::fleet::{EnemyFleet, Fleet};
+
+/// 戦闘中の出来事を受け取るフック。
+/// ライブラリの利用者が実装することで、エンジンを変更せずに独自の集計や可視化を行える。
+/// すべてのメソッドは何もしない既定の実装を持ち、`()` は何も受け取らないオブザーバーとして使える。
+pub trait BattleObserver {
+    /// 戦闘の開始時に呼ばれる。`enemy_index` は、入力された敵編成のうち何番目と戦うかを表す。
+    fn on_battle_start(&mut self, _friend: &Fleet, _enemy: &EnemyFleet, _enemy_index: usize) {}
+
+    /// 攻撃が行われるたびに、発生順に呼ばれる。
+    fn on_attack(&mut self, _attack: &AttackLog) {}
+
+    /// 艦が撃沈されたときに呼ばれる。
+    fn on_ship_sunk(&mut self, _side: FleetSide, _index: usize) {}
+
+    /// 戦闘の終了時に呼ばれる。
+    fn on_battle_end(&mut self, _log: &BattleLog, _result: &BattleResult) {}
+}
+
+impl BattleObserver for () {}
+
+/// 終了した戦闘の行動ログを先頭から再生し、発生順に `observer` へ通知する。
+pub fn notify(observer: &mut impl BattleObserver, battle: &Battle, enemy_index: usize) {
+    let setup = battle.setup();
+    observer.on_battle_start(&setup.friend_fleet, &setup.enemy_fleet, enemy_index);
+
+    for action in battle.log().actions() {
+        match action {
+            ActionLog::Attack(attack) => observer.on_attack(attack),
+            ActionLog::Sunk {
+                is_friend,
+                ship_idx,
+            } => {
+                let side = if *is_friend {
+                    FleetSide::Friend
+                } else {
+                    FleetSide::Enemy
+                };
+                observer.on_ship_sunk(side, *ship_idx);
+            }
+            _ => {}
+        }
+    }
+
+    observer.on_battle_end(battle.log(), &BattleResult::calculate(battle));
+}
@@ -0,0 +1,202 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{Battle, Phase};
+use crate::interface::SimulationOptions;
+use crate::profiling::Span;
+
+/// 戦闘を構成するフェーズ。
+/// 各フェーズは `Battle` の状態を受け取って更新し、フェーズの開始などを戦闘ログに記録する。
+pub trait BattlePhase {
+    /// プロファイリングに使うフェーズ名。
+    fn name(&self) -> &'static str;
+
+    /// フェーズを実行する。
+    fn execute(&self, battle: &mut Battle);
+}
+
+/// 基地航空隊の攻撃。
+pub struct LandBasePhase;
+
+impl BattlePhase for LandBasePhase {
+    fn name(&self) -> &'static str {
+        "land_base_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.land_base_phase();
+    }
+}
+
+/// 航空戦。
+pub struct AirCombatPhase;
+
+impl BattlePhase for AirCombatPhase {
+    fn name(&self) -> &'static str {
+        "air_combat_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.air_combat_phase();
+    }
+}
+
+/// 砲撃戦 (1巡目・2巡目)。
+pub struct ArtilleryPhase;
+
+impl BattlePhase for ArtilleryPhase {
+    fn name(&self) -> &'static str {
+        "artillery_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.artillery_phase();
+    }
+}
+
+/// 開幕雷撃。
+pub struct OpeningTorpedoPhase;
+
+impl BattlePhase for OpeningTorpedoPhase {
+    fn name(&self) -> &'static str {
+        "opening_torpedo_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.opening_torpedo_phase();
+    }
+}
+
+/// 閉幕雷撃。
+pub struct ClosingTorpedoPhase;
+
+impl BattlePhase for ClosingTorpedoPhase {
+    fn name(&self) -> &'static str {
+        "closing_torpedo_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.closing_torpedo_phase();
+    }
+}
+
+/// 夜戦。
+pub struct NightPhase;
+
+impl BattlePhase for NightPhase {
+    fn name(&self) -> &'static str {
+        "night_phase"
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        battle.night_phase();
+    }
+}
+
+/// 他のフェーズから切り離して単独で実行する1つのフェーズ。
+/// 特定の仕組みの効果だけを調べる場合に使い、前段のフェーズによる損傷は反映されない。
+pub struct SinglePhase(Phase);
+
+impl SinglePhase {
+    /// 単独で実行できるフェーズなら `SinglePhase` を返す。未実装のフェーズでは `None` を返す。
+    pub fn new(phase: Phase) -> Option<Self> {
+        match phase {
+            Phase::LandBase
+            | Phase::AirCombat
+            | Phase::OpeningTorpedo
+            | Phase::FirstArtillery
+            | Phase::SecondArtillery
+            | Phase::ClosingTorpedo
+            | Phase::Night => Some(Self(phase)),
+        }
+    }
+}
+
+impl BattlePhase for SinglePhase {
+    fn name(&self) -> &'static str {
+        match self.0 {
+            Phase::LandBase => "land_base_phase",
+            Phase::AirCombat => "air_combat_phase",
+            Phase::OpeningTorpedo => "opening_torpedo_phase",
+            Phase::FirstArtillery => "first_artillery_round",
+            Phase::SecondArtillery => "second_artillery_round",
+            Phase::ClosingTorpedo => "closing_torpedo_phase",
+            _ => "night_phase",
+        }
+    }
+
+    fn execute(&self, battle: &mut Battle) {
+        match self.0 {
+            Phase::LandBase => battle.land_base_phase(),
+            Phase::AirCombat => battle.air_combat_phase(),
+            Phase::OpeningTorpedo => battle.opening_torpedo_phase(),
+            Phase::FirstArtillery => battle.first_artillery_round(),
+            Phase::SecondArtillery => battle.second_artillery_round(),
+            Phase::ClosingTorpedo => battle.closing_torpedo_phase(),
+            _ => battle.night_phase(),
+        }
+    }
+}
+
+/// マスの種類。実行するフェーズの構成を決める。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeType {
+    /// 通常の昼戦マス
+    #[default]
+    Normal,
+    /// 夜戦のみのマス。戦闘開始時のHPから夜戦だけを行う。
+    NightOnly,
+    /// 空襲マス。基地航空隊の攻撃と航空戦のみを行う。
+    AirRaid,
+}
+
+/// 戦闘で実行するフェーズを順に並べたもの。
+pub struct PhasePipeline {
+    phases: Vec<Box<dyn BattlePhase>>,
+}
+
+impl PhasePipeline {
+    /// マスの種類とオプションから、実行するフェーズの並びを組み立てる。
+    /// `options.phase` が指定されている場合は、マスの種類によらずそのフェーズだけを実行する。
+    /// 単独で実行できないフェーズが指定された場合は何も実行しない (入力の検証で報告する)。
+    pub fn new(node_type: NodeType, options: &SimulationOptions) -> Self {
+        if let Some(phase) = &options.phase {
+            let phases: Vec<Box<dyn BattlePhase>> = SinglePhase::new(phase.clone())
+                .map(|p| Box::new(p) as Box<dyn BattlePhase>)
+                .into_iter()
+                .collect();
+            return Self { phases };
+        }
+        let phases: Vec<Box<dyn BattlePhase>> = match node_type {
+            NodeType::Normal => {
+                let mut phases: Vec<Box<dyn BattlePhase>> = vec![
+                    Box::new(LandBasePhase),
+                    Box::new(AirCombatPhase),
+                    Box::new(OpeningTorpedoPhase),
+                    Box::new(ArtilleryPhase),
+                    Box::new(ClosingTorpedoPhase),
+                ];
+                if options.night_battle {
+                    phases.push(Box::new(NightPhase));
+                }
+                phases
+            }
+            NodeType::NightOnly => vec![Box::new(NightPhase)],
+            NodeType::AirRaid => vec![Box::new(LandBasePhase), Box::new(AirCombatPhase)],
+        };
+        Self { phases }
+    }
+
+    /// 任意のフェーズの並びから組み立てる。独自のフェーズを挟む場合などに使う。
+    pub fn from_phases(phases: Vec<Box<dyn BattlePhase>>) -> Self {
+        Self { phases }
+    }
+
+    /// 各フェーズを順に実行する。
+    pub fn run(&self, battle: &mut Battle) {
+        for phase in &self.phases {
+            let _span = Span::enter(phase.name());
+            phase.execute(battle);
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::ShipSnapshot;
+use crate::fleet::Ship;
+
+/// 艦が所属する側を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FleetSide {
+    Friend,
+    Enemy,
+}
+
+/// 艦の静的なデータを含めず、(艦隊, 位置, 艦船ID) で艦を参照する軽量な構造体。
+/// 呼び出し側で入力の艦隊と突き合わせて使う。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ShipRef {
+    fleet: FleetSide,
+    index: usize,
+    id: u16,
+    #[serde(flatten)]
+    snapshot: ShipSnapshot,
+}
+
+impl ShipRef {
+    pub fn new(fleet: FleetSide, index: usize, ship: &Ship, snapshot: &ShipSnapshot) -> Self {
+        Self {
+            fleet,
+            index,
+            id: ship.id(),
+            snapshot: snapshot.clone(),
+        }
+    }
+}
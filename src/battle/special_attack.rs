@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+/// 連撃・カットインなどの特殊攻撃の種別。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SpecialAttack {
+    /// 夜戦連撃
+    NightDoubleAttack,
+    /// 主魚カットイン (主砲 + 魚雷)
+    MainTorpedoCutIn,
+    /// 魚雷カットイン (魚雷 × 2)
+    TorpedoCutIn,
+    /// 主主副カットイン (主砲 × 2 + 副砲)
+    MainSecondaryCutIn,
+    /// 主砲カットイン (主砲 × 3)
+    MainGunCutIn,
+    /// 弾着観測射撃の連撃 (主砲 × 2)
+    SpottingDoubleAttack,
+    /// 弾着観測射撃の主副カットイン (主砲 + 副砲)
+    SpottingMainSecondary,
+    /// 弾着観測射撃の主電カットイン (主砲 + 副砲 + 電探)
+    SpottingMainRadar,
+    /// 弾着観測射撃の主徹カットイン (主砲 + 副砲 + 徹甲弾)
+    SpottingMainArmorPiercing,
+    /// 弾着観測射撃の主主カットイン (主砲 × 2 + 徹甲弾)
+    SpottingMainMain,
+    /// Nelson Touch (旗艦・3番艦・5番艦による攻撃)
+    NelsonTouch,
+    /// 長門改二の一斉射 (旗艦 × 2 + 2番艦)
+    NagatoBroadside,
+    /// 陸奥改二の一斉射 (旗艦 × 2 + 2番艦)
+    MutsuBroadside,
+    /// 大和改二の一斉射 (旗艦・2番艦・3番艦による攻撃)
+    YamatoBroadside,
+}
+
+impl SpecialAttack {
+    /// 攻撃力の倍率。タッチ系特殊攻撃では旗艦の基本の倍率で、各艦の倍率は `touch_attack::participants` で決まる。
+    pub fn multiplier(&self) -> f64 {
+        match self {
+            SpecialAttack::NightDoubleAttack => 1.2,
+            SpecialAttack::MainTorpedoCutIn => 1.3,
+            SpecialAttack::TorpedoCutIn => 1.5,
+            SpecialAttack::MainSecondaryCutIn => 1.75,
+            SpecialAttack::MainGunCutIn => 2.0,
+            SpecialAttack::SpottingDoubleAttack => 1.2,
+            SpecialAttack::SpottingMainSecondary => 1.1,
+            SpecialAttack::SpottingMainRadar => 1.2,
+            SpecialAttack::SpottingMainArmorPiercing => 1.3,
+            SpecialAttack::SpottingMainMain => 1.5,
+            SpecialAttack::NelsonTouch => 2.0,
+            SpecialAttack::NagatoBroadside | SpecialAttack::MutsuBroadside => 1.4,
+            SpecialAttack::YamatoBroadside => 1.5,
+        }
+    }
+
+    /// 攻撃側の命中項にかかる倍率。夜戦の特殊攻撃とタッチ系特殊攻撃では 1.0 とする。
+    pub fn accuracy_multiplier(&self) -> f64 {
+        match self {
+            SpecialAttack::SpottingDoubleAttack => 1.1,
+            SpecialAttack::SpottingMainSecondary => 1.3,
+            SpecialAttack::SpottingMainRadar => 1.5,
+            SpecialAttack::SpottingMainArmorPiercing => 1.3,
+            SpecialAttack::SpottingMainMain => 1.2,
+            _ => 1.0,
+        }
+    }
+
+    /// 複数の艦が攻撃に参加する、1回の出撃で1度だけのタッチ系特殊攻撃かどうか。
+    pub fn is_touch(&self) -> bool {
+        matches!(
+            self,
+            SpecialAttack::NelsonTouch
+                | SpecialAttack::NagatoBroadside
+                | SpecialAttack::MutsuBroadside
+                | SpecialAttack::YamatoBroadside
+        )
+    }
+
+    /// 1回の特殊攻撃で同じ攻撃対象に行う攻撃の回数。
+    pub fn hits(&self) -> usize {
+        match self {
+            SpecialAttack::NightDoubleAttack
+            | SpecialAttack::MainTorpedoCutIn
+            | SpecialAttack::TorpedoCutIn
+            | SpecialAttack::SpottingDoubleAttack => 2,
+            _ => 1,
+        }
+    }
+}
@@ -0,0 +1,111 @@
+use crate::battle::fixed_point::Scalar;
+use crate::battle::{DamagedLevel, FleetSide};
+use crate::fleet::Ship;
+use crate::formula::DamageCoefficients;
+
+/// 戦意がこの値未満の艦は赤疲労とみなす
+const RED_MORALE: u16 = 20;
+
+/// 轟沈ストッパー (撃沈されるダメージを割合ダメージに置き換える仕組み) が働くかどうかを判定する。
+/// 通常艦隊の味方艦にのみ働き、旗艦には常に、それ以外の艦には戦闘開始時に大破しておらず、
+/// 赤疲労でもない場合に働く。`ship` には戦闘開始時の状態の艦を渡す。
+pub fn is_protected(side: FleetSide, index: usize, ship: &Ship) -> bool {
+    if side == FleetSide::Enemy {
+        return false;
+    }
+    if index == 0 {
+        return true;
+    }
+    DamagedLevel::from_hp(ship.hp(), ship.max_hp()) < DamagedLevel::Heavy
+        && ship.condition() >= RED_MORALE
+}
+
+/// 残りHP `hp` の艦への計算上のダメージに轟沈ストッパーを適用し、実際に減少するHPを返す。
+/// 撃沈されるダメージで `protected` が真の場合のみ、`random` で乱数を引いて割合ダメージに置き換える。
+pub fn apply<N: Scalar>(
+    calculated_damage: u16,
+    hp: u16,
+    protected: bool,
+    coefficients: &DamageCoefficients,
+    random: impl FnOnce() -> N,
+) -> u16 {
+    if calculated_damage < hp || !protected {
+        return calculated_damage.min(hp);
+    }
+    (coefficients
+        .damage(N::from_int(hp as i64), random())
+        .floor()
+        .to_f64() as u16)
+        .min(hp - 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::battle::fixed_point::Fixed;
+
+    const COEFFICIENTS: DamageCoefficients = DamageCoefficients {
+        base: 0.5,
+        random: 0.3,
+    };
+
+    fn ship(hp: u16, max_hp: u16, condition: u16) -> Ship {
+        Ship::test_builder()
+            .hp(max_hp, hp)
+            .condition(condition)
+            .build()
+    }
+
+    #[test]
+    fn enemy_ships_are_never_protected() {
+        assert!(!is_protected(FleetSide::Enemy, 0, &ship(40, 40, 49)));
+        assert!(!is_protected(FleetSide::Enemy, 1, &ship(40, 40, 49)));
+    }
+
+    #[test]
+    fn friend_flagship_is_always_protected() {
+        assert!(is_protected(FleetSide::Friend, 0, &ship(40, 40, 49)));
+        assert!(is_protected(FleetSide::Friend, 0, &ship(5, 40, 49)));
+        assert!(is_protected(FleetSide::Friend, 0, &ship(40, 40, 10)));
+    }
+
+    #[test]
+    fn friend_escort_is_protected_unless_heavily_damaged_or_red_morale() {
+        assert!(is_protected(FleetSide::Friend, 1, &ship(40, 40, 49)));
+        assert!(is_protected(FleetSide::Friend, 1, &ship(11, 40, 49)));
+        assert!(is_protected(FleetSide::Friend, 1, &ship(40, 40, 20)));
+        // 大破 (HP 25% 以下) で戦闘に入った艦
+        assert!(!is_protected(FleetSide::Friend, 1, &ship(10, 40, 49)));
+        // 赤疲労の艦
+        assert!(!is_protected(FleetSide::Friend, 1, &ship(40, 40, 19)));
+    }
+
+    #[test]
+    fn non_lethal_damage_is_unchanged_without_drawing_random() {
+        let damage = apply::<f64>(10, 40, true, &COEFFICIENTS, || unreachable!());
+        assert_eq!(damage, 10);
+    }
+
+    #[test]
+    fn lethal_damage_sinks_unprotected_ship() {
+        let damage = apply::<f64>(100, 40, false, &COEFFICIENTS, || unreachable!());
+        assert_eq!(damage, 40);
+    }
+
+    #[test]
+    fn lethal_damage_is_replaced_for_protected_ship() {
+        // 40 × 0.5 + floor(40 × r) × 0.3
+        assert_eq!(apply(100, 40, true, &COEFFICIENTS, || 0.0), 20);
+        assert_eq!(apply(40, 40, true, &COEFFICIENTS, || 0.99), 31);
+        // HP 1 の艦は沈まない
+        assert_eq!(apply(100, 1, true, &COEFFICIENTS, || 0.99), 0);
+    }
+
+    #[test]
+    fn fixed_point_replacement_matches_float() {
+        let fixed = |r| Fixed::from_random(r);
+        assert_eq!(apply(100, 40, true, &COEFFICIENTS, || fixed(0.0)), 20);
+        assert_eq!(apply(40, 40, true, &COEFFICIENTS, || fixed(0.99)), 31);
+        assert_eq!(apply(100, 1, true, &COEFFICIENTS, || fixed(0.99)), 0);
+    }
+}
@@ -0,0 +1,48 @@
+use crate::battle::fixed_point::{apply_cap, Scalar};
+use crate::battle::{luck, BattleDirection, Phase, ShipSnapshot};
+use crate::fleet::{Equipment, Ship};
+use crate::formula::{FormationFactor, FormulaConstants};
+
+/// `actor` が雷撃戦 `phase` に参加できる装備・ステータスを持つかどうかを判定する。
+/// 先制雷撃は潜水艦と甲標的を装備した艦が、閉幕雷撃は雷装が 1 以上の艦が行う。
+/// 損傷状態による行動制限は含まない。
+pub fn can_attack(phase: &Phase, actor: &Ship) -> bool {
+    match phase {
+        Phase::OpeningTorpedo => {
+            actor.torpedo() > 0 && (actor.is_submarine() || actor.has_midget_submarine())
+        }
+        Phase::ClosingTorpedo => actor.torpedo() > 0,
+        _ => false,
+    }
+}
+
+/// 雷撃戦で `target` を攻撃対象に選べるかどうかを判定する。陸上型と潜水艦は雷撃を受けない。
+pub fn can_target(target: &Ship) -> bool {
+    !target.is_installation() && !target.is_submarine()
+}
+
+/// 雷撃戦での `actor` の命中項を計算する。陣形の命中補正は砲撃戦のものと異なるため、かけない。
+pub fn accuracy(actor: &Ship) -> f64 {
+    let aiming = actor.equipment_aiming() as f64
+        + actor.improvement_bonus(Equipment::accuracy_improvement_bonus);
+    luck::accuracy(luck::TORPEDO_ACCURACY_BASE, actor.luck(), aiming, 1.0)
+}
+
+/// 雷撃戦での `actor` のキャップ適用後の攻撃力を計算する。
+/// 基本攻撃力は `雷装 + 5` で、陣形・交戦形態と雷撃戦用の損傷状態の補正がかかる。
+/// クリティカル補正は含まない。
+pub fn power<N: Scalar>(
+    actor: &Ship,
+    actor_snapshot: &ShipSnapshot,
+    direction: &BattleDirection,
+    formation: &FormationFactor,
+    constants: &FormulaConstants,
+) -> N {
+    let basic = N::from_int(actor.torpedo() as i64 + 5);
+    let precap = basic
+        * N::from_f64(formation.torpedo)
+        * N::from_f64(constants.direction_factor(direction))
+        * N::from_f64(constants.torpedo_damaged_level_factor(&actor.damaged_level(actor_snapshot)));
+    apply_cap(precap, N::from_f64(constants.torpedo_cap))
+        * N::from_f64(actor_snapshot.ammo_factor())
+}
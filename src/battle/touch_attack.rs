@@ -0,0 +1,216 @@
+use crate::battle::{DamagedLevel, ShipSnapshot, SpecialAttack};
+use crate::fleet::{Formation, Ship};
+
+/// Nelson (改装後を含む) の艦船ID。
+const NELSON_IDS: [u16; 2] = [571, 576];
+/// 長門改二の艦船ID。
+const NAGATO_KAI_NI_ID: u16 = 541;
+/// 陸奥改二の艦船ID。
+const MUTSU_KAI_NI_ID: u16 = 573;
+/// 大和改二・大和改二重の艦船ID。
+const YAMATO_KAI_NI_IDS: [u16; 2] = [911, 916];
+/// 長門改二の一斉射で僚艦補正がかかる陸奥 (陸奥改・陸奥改二) の艦船ID。
+const MUTSU_PARTNER_IDS: [u16; 2] = [276, 573];
+/// 陸奥改二の一斉射で僚艦補正がかかる長門 (長門改・長門改二) の艦船ID。
+const NAGATO_PARTNER_IDS: [u16; 2] = [275, 541];
+/// 長門・陸奥の一斉射の (旗艦, 2番艦) の攻撃力の倍率。
+const BROADSIDE_MULTIPLIERS: (f64, f64) = (1.4, 1.2);
+/// 長門改二の一斉射で2番艦が陸奥の場合に、(旗艦, 2番艦) の倍率にかかる補正。
+const NAGATO_PARTNER_BONUS: (f64, f64) = (1.15, 1.35);
+/// 陸奥改二の一斉射で2番艦が長門の場合に、(旗艦, 2番艦) の倍率にかかる補正。
+const MUTSU_PARTNER_BONUS: (f64, f64) = (1.2, 1.4);
+/// 発動に必要な艦隊の艦数。
+const MIN_SHIPS: usize = 6;
+
+/// 旗艦と艦隊の編成から、発動条件を満たすタッチ系特殊攻撃を判定する。
+/// 6隻以上の艦隊で、旗艦が中破未満、攻撃に参加する僚艦が大破未満の生存艦で、潜水艦・空母系でないことが共通の条件。
+/// 陣形の補正 (`formation_factor`) が 0 となる陣形では発動しない。
+pub fn candidate(
+    ships: &[Ship],
+    snapshots: &[ShipSnapshot],
+    formation: &Formation,
+) -> Option<SpecialAttack> {
+    let flagship = ships.first()?;
+    if ships.len() < MIN_SHIPS || *snapshots[0].damaged_level() >= DamagedLevel::Moderate {
+        return None;
+    }
+    let kind = if NELSON_IDS.contains(&flagship.id()) {
+        SpecialAttack::NelsonTouch
+    } else if flagship.id() == NAGATO_KAI_NI_ID {
+        SpecialAttack::NagatoBroadside
+    } else if flagship.id() == MUTSU_KAI_NI_ID {
+        SpecialAttack::MutsuBroadside
+    } else if YAMATO_KAI_NI_IDS.contains(&flagship.id()) {
+        SpecialAttack::YamatoBroadside
+    } else {
+        return None;
+    };
+    if formation_factor(&kind, formation) == 0.0 {
+        return None;
+    }
+
+    let can_join = |idx: usize| {
+        let (ship, snapshot) = (&ships[idx], &snapshots[idx]);
+        let escort_ok = match kind {
+            SpecialAttack::NelsonTouch => !ship.is_carrier_class() && !ship.is_submarine(),
+            _ => ship.is_battleship_class(),
+        };
+        escort_ok && snapshot.is_alive() && *snapshot.damaged_level() < DamagedLevel::Heavy
+    };
+    participants(&kind, ships)
+        .iter()
+        .filter(|(idx, _)| *idx != 0)
+        .all(|(idx, _)| can_join(*idx))
+        .then_some(kind)
+}
+
+/// 旗艦の戦意による発動率の補正。
+fn condition_factor(condition: u16) -> f64 {
+    match condition {
+        50.. => 1.2,
+        30..=49 => 1.0,
+        20..=29 => 0.8,
+        _ => 0.5,
+    }
+}
+
+/// 陣形による発動率の補正。発動できない陣形では 0.0。
+fn formation_factor(kind: &SpecialAttack, formation: &Formation) -> f64 {
+    match (kind, formation) {
+        (SpecialAttack::NelsonTouch, Formation::DoubleLine) => 1.0,
+        (SpecialAttack::NagatoBroadside | SpecialAttack::MutsuBroadside, Formation::Echelon) => 1.0,
+        (SpecialAttack::YamatoBroadside, Formation::Echelon) => 1.0,
+        (SpecialAttack::YamatoBroadside, Formation::DoubleLine) => 0.9,
+        _ => 0.0,
+    }
+}
+
+/// タッチ系特殊攻撃の発動率 (0.0〜1.0) を、種別ごとの基本発動率に旗艦の戦意と陣形の補正をかけて計算する。
+pub fn rate(kind: &SpecialAttack, flagship_condition: u16, formation: &Formation) -> f64 {
+    let base = match kind {
+        SpecialAttack::NelsonTouch => 0.6,
+        SpecialAttack::NagatoBroadside | SpecialAttack::MutsuBroadside => 0.55,
+        SpecialAttack::YamatoBroadside => 0.6,
+        _ => return 0.0,
+    };
+    (base * condition_factor(flagship_condition) * formation_factor(kind, formation))
+        .clamp(0.0, 1.0)
+}
+
+/// 攻撃に参加する艦の艦隊内の位置と攻撃力の倍率を、攻撃する順に列挙する。
+/// 長門・陸奥の一斉射では旗艦が2回攻撃し、2番艦が相方の場合は種別ごとの補正で両艦の倍率が上がる。
+pub fn participants(kind: &SpecialAttack, ships: &[Ship]) -> Vec<(usize, f64)> {
+    let (partner_ids, bonus) = match kind {
+        SpecialAttack::NelsonTouch => return vec![(0, 2.0), (2, 2.0), (4, 2.0)],
+        SpecialAttack::YamatoBroadside => return vec![(0, 1.5), (1, 1.5), (2, 1.65)],
+        SpecialAttack::NagatoBroadside => (MUTSU_PARTNER_IDS, NAGATO_PARTNER_BONUS),
+        SpecialAttack::MutsuBroadside => (NAGATO_PARTNER_IDS, MUTSU_PARTNER_BONUS),
+        _ => return Vec::new(),
+    };
+    let (mut flagship, mut second) = BROADSIDE_MULTIPLIERS;
+    if ships.get(1).is_some_and(|s| partner_ids.contains(&s.id())) {
+        flagship *= bonus.0;
+        second *= bonus.1;
+    }
+    vec![(0, flagship), (0, flagship), (1, second)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship(id: u16, ship_type_id: u16, now_hp: u16) -> Ship {
+        Ship::test_builder()
+            .id(id)
+            .ship_type(ship_type_id)
+            .hp(80, now_hp)
+            .build()
+    }
+
+    fn fleet(flagship_id: u16, second_id: u16) -> Vec<Ship> {
+        let mut ships = vec![ship(flagship_id, 9, 80), ship(second_id, 9, 80)];
+        ships.extend((0..4).map(|_| ship(1, 2, 30)));
+        ships
+    }
+
+    fn snapshots(ships: &[Ship]) -> Vec<ShipSnapshot> {
+        ships.iter().map(ShipSnapshot::from).collect()
+    }
+
+    #[test]
+    fn candidate_conditions() {
+        let ships = fleet(NAGATO_KAI_NI_ID, 276);
+        let snaps = snapshots(&ships);
+        assert_eq!(
+            candidate(&ships, &snaps, &Formation::Echelon),
+            Some(SpecialAttack::NagatoBroadside)
+        );
+        // 発動できない陣形
+        assert_eq!(candidate(&ships, &snaps, &Formation::LineAhead), None);
+        // 艦数不足
+        assert_eq!(
+            candidate(&ships[..5], &snaps[..5], &Formation::Echelon),
+            None
+        );
+
+        // 2番艦が戦艦でない
+        let mut escort = ships.clone();
+        escort[1] = ship(276, 2, 30);
+        assert_eq!(
+            candidate(&escort, &snapshots(&escort), &Formation::Echelon),
+            None
+        );
+
+        // 旗艦が中破
+        let mut damaged = ships.clone();
+        damaged[0] = ship(NAGATO_KAI_NI_ID, 9, 40);
+        assert_eq!(
+            candidate(&damaged, &snapshots(&damaged), &Formation::Echelon),
+            None
+        );
+
+        // Nelson Touch は複縦陣で、3・5番艦が空母・潜水艦でなければよい
+        let nelson = fleet(NELSON_IDS[0], 1);
+        assert_eq!(
+            candidate(&nelson, &snapshots(&nelson), &Formation::DoubleLine),
+            Some(SpecialAttack::NelsonTouch)
+        );
+    }
+
+    #[test]
+    fn rate_factors() {
+        let kind = SpecialAttack::NagatoBroadside;
+        assert!((rate(&kind, 49, &Formation::Echelon) - 0.55).abs() < 1e-9);
+        assert!((rate(&kind, 50, &Formation::Echelon) - 0.66).abs() < 1e-9);
+        assert!((rate(&kind, 10, &Formation::Echelon) - 0.275).abs() < 1e-9);
+        assert_eq!(rate(&kind, 49, &Formation::LineAhead), 0.0);
+        let yamato = SpecialAttack::YamatoBroadside;
+        assert!((rate(&yamato, 49, &Formation::DoubleLine) - 0.54).abs() < 1e-9);
+    }
+
+    #[test]
+    fn partner_bonus_depends_on_kind() {
+        let multipliers = |kind, ships: &[Ship]| -> Vec<f64> {
+            participants(&kind, ships)
+                .into_iter()
+                .map(|(_, m)| m)
+                .collect()
+        };
+        let nagato = multipliers(
+            SpecialAttack::NagatoBroadside,
+            &fleet(NAGATO_KAI_NI_ID, 276),
+        );
+        assert!((nagato[0] - 1.4 * 1.15).abs() < 1e-9);
+        assert!((nagato[2] - 1.2 * 1.35).abs() < 1e-9);
+
+        let mutsu = multipliers(SpecialAttack::MutsuBroadside, &fleet(MUTSU_KAI_NI_ID, 541));
+        assert!((mutsu[0] - 1.4 * 1.2).abs() < 1e-9);
+        assert!((mutsu[2] - 1.2 * 1.4).abs() < 1e-9);
+
+        let alone = participants(
+            &SpecialAttack::NagatoBroadside,
+            &fleet(NAGATO_KAI_NI_ID, 80),
+        );
+        assert_eq!(alone, vec![(0, 1.4), (0, 1.4), (1, 1.2)]);
+    }
+}
@@ -0,0 +1,476 @@
+//! WASI などの非ブラウザ環境向けのエントリーポイント。
+//! 標準入力から `SimulationRequest` の JSON を受け取り、結果の JSON を標準出力に書き出す。
+//! `options.compression` が指定された場合は、圧縮したバイト列を書き出す。
+//!
+//! `--format markdown` または `--format html` を指定すると、集計モードで実行し、
+//! 結果を共有用の文書として書き出す。
+//! `--format ndjson` を指定すると、最初の行に実行の設定の控えを、以降は戦闘ごとの結果を生成されるたびに
+//! 1行の JSON として書き出す。
+//! この場合、集計モードと `options.compression` は無視される。
+//!
+//! `--input` でリクエストのファイルを、`--friend` と `--enemy` で味方艦隊と敵編成のファイルを指定できる。
+//! `--friend` と `--enemy` はリクエストの内容を上書きする。
+//! `--watch` を指定すると、指定したファイルが変更されるたびに集計モードで再実行し、
+//! 主要な指標の前回からの変化を書き出す。
+//!
+//! `--scenario` を指定すると、シナリオファイル (`Scenario` の JSON、`scenario-toml` フィーチャーが
+//! 有効な場合は拡張子 `.toml` の TOML も可) に書かれた複数の味方編成を同じ条件で実行し、
+//! 指定された出力を書き出す。他の入力の指定とは併用できない。
+//!
+//! 入力のエラーは `ErrorReport` の JSON として 1 行ずつ標準エラー出力に書き出す。
+//!
+//! ```sh
+//! cargo build --release --target wasm32-wasip1 --no-default-features --bin sim-core-wasi
+//! wasmtime sim-core-wasi.wasm < request.json > result.json
+//! wasmtime sim-core-wasi.wasm -- --format markdown < request.json > result.md
+//! wasmtime sim-core-wasi.wasm -- --format ndjson < request.json > battles.ndjson
+//! sim-core-wasi --input request.json --friend fleet.json --watch
+//! sim-core-wasi --scenario scenario.json
+//! ```
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+use std::time::{Duration, SystemTime};
+
+use sim_core::interface::{
+    self, Compression, ErrorCode, ErrorReport, Scenario, ScenarioOutputKind, SimulationRequest,
+};
+
+/// 監視モードでファイルの更新を確認する間隔
+const WATCH_INTERVAL: Duration = Duration::from_millis(500);
+
+/// 出力の形式。
+#[derive(PartialEq)]
+enum Format {
+    Json,
+    /// 戦闘ごとの結果を1行ずつ書き出す
+    Ndjson,
+    Markdown,
+    Html,
+}
+
+/// コマンドライン引数。
+struct Args {
+    format: Format,
+    /// リクエストのファイル。省略した場合は標準入力から読み込む
+    input: Option<PathBuf>,
+    /// リクエストの味方艦隊を置き換えるファイル
+    friend: Option<PathBuf>,
+    /// リクエストの敵編成を置き換えるファイル
+    enemy: Option<PathBuf>,
+    watch: bool,
+    /// シナリオファイル
+    scenario: Option<PathBuf>,
+}
+
+impl Args {
+    /// 監視対象のファイル。
+    fn watched_files(&self) -> Vec<&Path> {
+        vec![
+            self.input.as_deref(),
+            self.friend.as_deref(),
+            self.enemy.as_deref(),
+        ]
+        .into_iter()
+        .flatten()
+        .collect()
+    }
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut args = std::env::args().skip(1);
+    let mut parsed = Args {
+        format: Format::Json,
+        input: None,
+        friend: None,
+        enemy: None,
+        watch: false,
+        scenario: None,
+    };
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                parsed.format = match args.next().as_deref() {
+                    Some("json") => Format::Json,
+                    Some("ndjson") => Format::Ndjson,
+                    Some("markdown") | Some("md") => Format::Markdown,
+                    Some("html") => Format::Html,
+                    other => return Err(format!("Unknown format: {:?}", other)),
+                }
+            }
+            "--input" | "--friend" | "--enemy" | "--scenario" => {
+                let path = args
+                    .next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| format!("Missing path for {}", arg))?;
+                match arg.as_str() {
+                    "--input" => parsed.input = Some(path),
+                    "--friend" => parsed.friend = Some(path),
+                    "--scenario" => parsed.scenario = Some(path),
+                    _ => parsed.enemy = Some(path),
+                }
+            }
+            "--watch" => parsed.watch = true,
+            other => return Err(format!("Unknown argument: {}", other)),
+        }
+    }
+    if parsed.scenario.is_some() && (parsed.watch || !parsed.watched_files().is_empty()) {
+        return Err("--scenario cannot be combined with other inputs".to_string());
+    }
+    if parsed.watch && parsed.watched_files().is_empty() {
+        return Err("--watch requires --input, --friend or --enemy".to_string());
+    }
+    Ok(parsed)
+}
+
+/// エラー情報を JSON として標準エラー出力に書き出す。
+fn print_error(report: &ErrorReport) {
+    match serde_json::to_string(report) {
+        Ok(json) => eprintln!("{}", json),
+        Err(_) => eprintln!("{}", report.message),
+    }
+}
+
+fn read_file(path: &Path) -> Result<String, ErrorReport> {
+    std::fs::read_to_string(path).map_err(|err| {
+        ErrorReport::new(
+            ErrorCode::RequestParseFailed,
+            format!("Failed to read {}: {}", path.display(), err),
+        )
+    })
+}
+
+/// リクエストを読み込み、`--friend` と `--enemy` のファイルで味方艦隊と敵編成を置き換える。
+/// `stdin` は、`--input` を省略した場合に標準入力から読み込んだリクエスト。
+fn load_request(args: &Args, stdin: &str) -> Result<SimulationRequest, ErrorReport> {
+    let input = match &args.input {
+        Some(path) => read_file(path)?,
+        None => stdin.to_string(),
+    };
+    let request_error = |err: serde_json::Error| {
+        ErrorReport::new(
+            ErrorCode::RequestParseFailed,
+            format!("Failed to parse simulation request: {}", err),
+        )
+    };
+    let mut value: serde_json::Value = serde_json::from_str(&input).map_err(request_error)?;
+
+    // 置き換える値はリクエストの一部として読み込むため、未知のフィールドも同じように検出される
+    let overrides = [
+        (&args.friend, "friend", ErrorCode::FriendFleetParseFailed),
+        (&args.enemy, "enemies", ErrorCode::EnemyFleetsParseFailed),
+    ];
+    for (path, key, code) in overrides {
+        let Some(path) = path else {
+            continue;
+        };
+        let replaced = serde_json::from_str(&read_file(path)?).map_err(|err| {
+            ErrorReport::new(code, format!("Failed to parse {}: {}", path.display(), err))
+        })?;
+        match value.as_object_mut() {
+            Some(request) => request.insert(key.to_string(), replaced),
+            None => {
+                return Err(request_error(serde::de::Error::custom(
+                    "expected an object",
+                )))
+            }
+        };
+    }
+
+    let (request, unknown) = interface::deserialize_tracking_unknown::<SimulationRequest, _>(value)
+        .map_err(request_error)?;
+    interface::check_unknown_fields("simulation request", &unknown, request.options.strict)?;
+    request
+        .migrate()
+        .map_err(|err| ErrorReport::new(ErrorCode::SchemaVersionUnsupported, err))
+}
+
+/// シナリオファイルを読み込む。拡張子が `.toml` の場合は TOML として読み込む。
+fn load_scenario(path: &Path) -> Result<Scenario, ErrorReport> {
+    let text = read_file(path)?;
+    let scenario_error = |message: String| {
+        ErrorReport::new(
+            ErrorCode::RequestParseFailed,
+            format!("Failed to parse scenario: {}", message),
+        )
+    };
+    let value: serde_json::Value = if path.extension().is_some_and(|ext| ext == "toml") {
+        parse_toml(&text).map_err(scenario_error)?
+    } else {
+        serde_json::from_str(&text).map_err(|err| scenario_error(err.to_string()))?
+    };
+    let (scenario, unknown) = interface::deserialize_tracking_unknown::<Scenario, _>(value)
+        .map_err(|err| scenario_error(err.to_string()))?;
+    interface::check_unknown_fields("scenario", &unknown, scenario.options.strict)?;
+    Ok(scenario)
+}
+
+#[cfg(feature = "scenario-toml")]
+fn parse_toml(text: &str) -> Result<serde_json::Value, String> {
+    toml::from_str(text).map_err(|err| err.to_string())
+}
+
+#[cfg(not(feature = "scenario-toml"))]
+fn parse_toml(_text: &str) -> Result<serde_json::Value, String> {
+    Err("TOML scenarios require the `scenario-toml` feature".to_string())
+}
+
+/// シナリオの味方編成をすべて実行し、指定された出力を書き出す。
+fn run_scenario(mut scenario: Scenario) -> Result<(), String> {
+    if let Some(master) = scenario.master.take() {
+        sim_core::load_master_data(master);
+    }
+    if let Some(constants) = scenario.formula_constants.take() {
+        sim_core::load_formula_constants(constants);
+    }
+    let options = interface::SimulationOptions {
+        aggregate: true,
+        ..scenario.options.clone()
+    };
+
+    let mut summaries = Vec::new();
+    for entry in &scenario.fleets {
+        let output = sim_core::run_simulation(
+            entry.fleet.clone(),
+            scenario.enemies.clone(),
+            scenario.count,
+            &options,
+        );
+        let summary = output
+            .summary()
+            .cloned()
+            .ok_or("Aggregate summary is not available")?;
+        summaries.push((entry.name.as_str(), summary));
+    }
+
+    for output in &scenario.outputs {
+        let text = match output.kind {
+            ScenarioOutputKind::Summary => {
+                let map: serde_json::Map<String, serde_json::Value> = summaries
+                    .iter()
+                    .map(|(name, summary)| {
+                        serde_json::to_value(summary).map(|value| (name.to_string(), value))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| err.to_string())?;
+                serde_json::to_string(&map).map_err(|err| err.to_string())? + "\n"
+            }
+            ScenarioOutputKind::Markdown => summaries
+                .iter()
+                .map(|(name, summary)| format!("<!-- {} -->\n{}", name, summary.to_markdown()))
+                .collect::<Vec<_>>()
+                .join("\n"),
+            ScenarioOutputKind::Comparison => {
+                let Some((base, others)) = scenario.fleets.split_first() else {
+                    return Err("Comparison requires at least one fleet".to_string());
+                };
+                let comparisons: serde_json::Map<String, serde_json::Value> = others
+                    .iter()
+                    .map(|other| {
+                        let comparison = sim_core::run_comparison(
+                            other.fleet.clone(),
+                            base.fleet.clone(),
+                            scenario.enemies.clone(),
+                            scenario.count,
+                            &scenario.options,
+                        );
+                        serde_json::to_value(comparison).map(|value| (other.name.clone(), value))
+                    })
+                    .collect::<Result<_, _>>()
+                    .map_err(|err| err.to_string())?;
+                serde_json::to_string(&comparisons).map_err(|err| err.to_string())? + "\n"
+            }
+        };
+        let written = match &output.path {
+            Some(path) => std::fs::write(path, text),
+            None => std::io::stdout().lock().write_all(text.as_bytes()),
+        };
+        written.map_err(|err| format!("Failed to write scenario output: {}", err))?;
+    }
+    Ok(())
+}
+
+/// マスターデータと計算式の定数を読み込んだ上でシミュレーションを行い、集計結果を返す。
+fn run_aggregate(mut request: SimulationRequest) -> interface::SimulationOutput {
+    request.options.aggregate = true;
+    load_request_data(&mut request);
+    sim_core::run_simulation(
+        request.friend,
+        request.enemies,
+        request.count,
+        &request.options,
+    )
+}
+
+fn load_request_data(request: &mut SimulationRequest) {
+    if let Some(master) = request.master.take() {
+        sim_core::load_master_data(master);
+    }
+    if let Some(constants) = request.formula_constants.take() {
+        sim_core::load_formula_constants(constants);
+    }
+}
+
+/// 指定したファイルの更新日時。読み取れないファイルは `None` とする。
+fn modified_times(paths: &[&Path]) -> Vec<Option<SystemTime>> {
+    paths
+        .iter()
+        .map(|path| std::fs::metadata(path).and_then(|m| m.modified()).ok())
+        .collect()
+}
+
+/// 監視モード。ファイルが更新されるたびに再実行し、主要な指標を前回の値と並べて書き出す。
+fn watch(args: &Args, stdin: &str) -> ExitCode {
+    let paths = args.watched_files();
+    let mut last_modified = None;
+    let mut previous: Vec<(String, f64)> = Vec::new();
+    loop {
+        let modified = modified_times(&paths);
+        if last_modified.as_ref() == Some(&modified) {
+            std::thread::sleep(WATCH_INTERVAL);
+            continue;
+        }
+        last_modified = Some(modified);
+
+        let request = match load_request(args, stdin) {
+            Ok(r) => r,
+            Err(report) => {
+                print_error(&report);
+                continue;
+            }
+        };
+        let output = run_aggregate(request);
+        let Some(summary) = output.summary() else {
+            eprintln!("Aggregate summary is not available");
+            continue;
+        };
+
+        let metrics = summary.key_metrics();
+        let mut stdout = std::io::stdout().lock();
+        let mut lines = String::from("---\n");
+        for (name, value) in &metrics {
+            match previous.iter().find(|(prev_name, _)| prev_name == name) {
+                Some((_, prev)) => lines.push_str(&format!(
+                    "{}: {:.2} -> {:.2} ({:+.2})\n",
+                    name,
+                    prev,
+                    value,
+                    value - prev
+                )),
+                _ => lines.push_str(&format!("{}: {:.2}\n", name, value)),
+            }
+        }
+        if let Err(err) = stdout
+            .write_all(lines.as_bytes())
+            .and_then(|_| stdout.flush())
+        {
+            eprintln!("Failed to write simulation result: {}", err);
+            return ExitCode::FAILURE;
+        }
+        previous = metrics;
+    }
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args() {
+        Ok(a) => a,
+        Err(err) => {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let mut stdin = String::new();
+    if args.input.is_none() && args.scenario.is_none() {
+        if let Err(err) = std::io::stdin().read_to_string(&mut stdin) {
+            eprintln!("Failed to read stdin: {}", err);
+            return ExitCode::FAILURE;
+        }
+    }
+
+    sim_core::set_error_reporter(print_error);
+
+    if let Some(path) = &args.scenario {
+        let scenario = match load_scenario(path) {
+            Ok(s) => s,
+            Err(report) => {
+                print_error(&report);
+                return ExitCode::FAILURE;
+            }
+        };
+        if let Err(err) = run_scenario(scenario) {
+            eprintln!("{}", err);
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+    if args.watch {
+        return watch(&args, &stdin);
+    }
+
+    let mut request = match load_request(&args, &stdin) {
+        Ok(r) => r,
+        Err(report) => {
+            print_error(&report);
+            return ExitCode::FAILURE;
+        }
+    };
+    let format = args.format;
+
+    // 文書は集計結果から作成する
+    if format == Format::Markdown || format == Format::Html {
+        let output = run_aggregate(request);
+        let Some(summary) = output.summary() else {
+            eprintln!("Aggregate summary is not available");
+            return ExitCode::FAILURE;
+        };
+        let document = match format {
+            Format::Html => summary.to_html(),
+            _ => summary.to_markdown(),
+        };
+        if let Err(err) = std::io::stdout().lock().write_all(document.as_bytes()) {
+            eprintln!("Failed to write simulation report: {}", err);
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    load_request_data(&mut request);
+    if format == Format::Ndjson {
+        let stdout = BufWriter::new(std::io::stdout().lock());
+        if let Err(err) = sim_core::write_ndjson(
+            request.friend,
+            request.enemies,
+            request.count,
+            &request.options,
+            stdout,
+        ) {
+            eprintln!("Failed to write simulation result: {}", err);
+            return ExitCode::FAILURE;
+        }
+        return ExitCode::SUCCESS;
+    }
+
+    let compression = request.options.compression;
+    let output = sim_core::run_simulation(
+        request.friend,
+        request.enemies,
+        request.count,
+        &request.options,
+    );
+    let written = sim_core::encode_output(&output, compression).and_then(|mut bytes| {
+        // 圧縮しない場合は、行単位で扱えるように改行を付ける
+        if compression == Compression::None {
+            bytes.push(b'\n');
+        }
+        std::io::stdout()
+            .lock()
+            .write_all(&bytes)
+            .map_err(serde_json::Error::io)
+    });
+    if let Err(err) = written {
+        eprintln!("Failed to write simulation result: {}", err);
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,251 @@
+//! 出力の圧縮。
+//! 外部クレートに依存しない、固定ハフマン符号による簡易的な DEFLATE 実装で gzip 形式を出力する。
+//! 圧縮率は専用のライブラリに劣るが、繰り返しの多い JSON の出力であれば十分に小さくなる。
+
+const WINDOW_SIZE: usize = 32 * 1024;
+const MIN_MATCH: usize = 3;
+const MAX_MATCH: usize = 258;
+/// 一致候補を辿る回数の上限。大きくすると圧縮率が上がるが遅くなる。
+const MAX_CHAIN: usize = 32;
+const HASH_BITS: u32 = 15;
+
+const LENGTH_BASE: [u16; 29] = [
+    3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115, 131,
+    163, 195, 227, 258,
+];
+const LENGTH_EXTRA: [u8; 29] = [
+    0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+];
+const DISTANCE_BASE: [u16; 30] = [
+    1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+    2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+];
+const DISTANCE_EXTRA: [u8; 30] = [
+    0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12, 13,
+    13,
+];
+
+/// データを gzip 形式で圧縮する。
+pub fn gzip(data: &[u8]) -> Vec<u8> {
+    // ID1, ID2, CM (deflate), FLG, MTIME (4 bytes), XFL, OS (unknown)
+    let mut out = vec![0x1f, 0x8b, 0x08, 0x00, 0, 0, 0, 0, 0x00, 0xff];
+    out.extend(deflate(data));
+    out.extend(crc32(data).to_le_bytes());
+    out.extend((data.len() as u32).to_le_bytes());
+    out
+}
+
+/// 固定ハフマン符号の 1 ブロックとして DEFLATE 圧縮する。
+fn deflate(data: &[u8]) -> Vec<u8> {
+    let mut writer = BitWriter::default();
+    // BFINAL = 1, BTYPE = 01 (固定ハフマン符号)
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![usize::MAX; 1 << HASH_BITS];
+    let mut prev = vec![usize::MAX; WINDOW_SIZE];
+
+    let mut pos = 0;
+    while pos < data.len() {
+        let (length, distance) = longest_match(data, pos, &head, &prev);
+        if length >= MIN_MATCH {
+            writer.write_length(length);
+            writer.write_distance(distance);
+            for p in pos..pos + length {
+                insert(data, p, &mut head, &mut prev);
+            }
+            pos += length;
+        } else {
+            writer.write_literal(data[pos]);
+            insert(data, pos, &mut head, &mut prev);
+            pos += 1;
+        }
+    }
+    // ブロックの終端
+    writer.write_literal_code(256);
+    writer.finish()
+}
+
+/// `pos` から始まる 3 バイトを一致候補のハッシュ表に登録する。
+fn insert(data: &[u8], pos: usize, head: &mut [usize], prev: &mut [usize]) {
+    if pos + MIN_MATCH <= data.len() {
+        let h = hash(&data[pos..pos + MIN_MATCH]);
+        prev[pos % WINDOW_SIZE] = head[h];
+        head[h] = pos;
+    }
+}
+
+fn hash(bytes: &[u8]) -> usize {
+    let v = u32::from(bytes[0]) << 16 | u32::from(bytes[1]) << 8 | u32::from(bytes[2]);
+    (v.wrapping_mul(0x9E37_79B1) >> (32 - HASH_BITS)) as usize
+}
+
+/// `pos` から始まる最長一致を探し、(長さ, 距離) を返す。
+fn longest_match(data: &[u8], pos: usize, head: &[usize], prev: &[usize]) -> (usize, usize) {
+    if pos + MIN_MATCH > data.len() {
+        return (0, 0);
+    }
+    let max_length = MAX_MATCH.min(data.len() - pos);
+    let mut best = (0, 0);
+    let mut candidate = head[hash(&data[pos..pos + MIN_MATCH])];
+    for _ in 0..MAX_CHAIN {
+        if candidate == usize::MAX || candidate >= pos || pos - candidate > WINDOW_SIZE {
+            break;
+        }
+        let length = data[candidate..]
+            .iter()
+            .zip(&data[pos..pos + max_length])
+            .take_while(|(a, b)| a == b)
+            .count();
+        if length > best.0 {
+            best = (length, pos - candidate);
+            if length == max_length {
+                break;
+            }
+        }
+        candidate = prev[candidate % WINDOW_SIZE];
+    }
+    best
+}
+
+/// 下位ビットから順に詰めていくビット列の書き込み器。
+#[derive(Default)]
+struct BitWriter {
+    out: Vec<u8>,
+    buffer: u32,
+    count: u32,
+}
+
+impl BitWriter {
+    fn write_bits(&mut self, value: u32, bits: u32) {
+        self.buffer |= value << self.count;
+        self.count += bits;
+        while self.count >= 8 {
+            self.out.push(self.buffer as u8);
+            self.buffer >>= 8;
+            self.count -= 8;
+        }
+    }
+
+    /// ハフマン符号は上位ビットから書き込む。
+    fn write_code(&mut self, code: u32, bits: u32) {
+        let reversed = code.reverse_bits() >> (32 - bits);
+        self.write_bits(reversed, bits);
+    }
+
+    fn write_literal(&mut self, byte: u8) {
+        self.write_literal_code(u16::from(byte));
+    }
+
+    fn write_literal_code(&mut self, symbol: u16) {
+        let symbol = u32::from(symbol);
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xc0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_length(&mut self, length: usize) {
+        let i = LENGTH_BASE
+            .iter()
+            .rposition(|&base| usize::from(base) <= length)
+            .unwrap();
+        self.write_literal_code(257 + i as u16);
+        let extra = length - usize::from(LENGTH_BASE[i]);
+        self.write_bits(extra as u32, u32::from(LENGTH_EXTRA[i]));
+    }
+
+    fn write_distance(&mut self, distance: usize) {
+        let i = DISTANCE_BASE
+            .iter()
+            .rposition(|&base| usize::from(base) <= distance)
+            .unwrap();
+        self.write_code(i as u32, 5);
+        let extra = distance - usize::from(DISTANCE_BASE[i]);
+        self.write_bits(extra as u32, u32::from(DISTANCE_EXTRA[i]));
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.count > 0 {
+            self.out.push(self.buffer as u8);
+        }
+        self.out
+    }
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut table = [0u32; 256];
+    for (i, entry) in table.iter_mut().enumerate() {
+        let mut c = i as u32;
+        for _ in 0..8 {
+            c = if c & 1 != 0 {
+                0xedb8_8320 ^ (c >> 1)
+            } else {
+                c >> 1
+            };
+        }
+        *entry = c;
+    }
+    !data.iter().fold(!0u32, |crc, &b| {
+        table[((crc ^ u32::from(b)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    fn gunzip(compressed: &[u8]) -> Vec<u8> {
+        let mut out = Vec::new();
+        flate2::read::GzDecoder::new(compressed)
+            .read_to_end(&mut out)
+            .unwrap();
+        out
+    }
+
+    fn assert_round_trip(data: &[u8]) {
+        assert_eq!(gunzip(&gzip(data)), data);
+    }
+
+    #[test]
+    fn round_trip_short_inputs() {
+        assert_round_trip(b"");
+        assert_round_trip(b"a");
+        assert_round_trip(b"abcabcabcabc");
+        // 9 ビットで符号化されるリテラル (144〜255) を含む
+        assert_round_trip(&(0..=255).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn round_trip_long_matches() {
+        // 最大一致長 (258) を超える連続
+        assert_round_trip(&[b'x'; 1000]);
+        // ウィンドウサイズを超える繰り返しの多い JSON
+        let json = (0..5000)
+            .map(|i| format!(r#"{{"seed":{},"result":"S","hp":[35,0,12]}}"#, i % 97))
+            .collect::<Vec<_>>()
+            .join("\n");
+        assert!(json.len() > WINDOW_SIZE);
+        let compressed = gzip(json.as_bytes());
+        assert!(compressed.len() < json.len() / 4);
+        assert_eq!(gunzip(&compressed), json.as_bytes());
+    }
+
+    #[test]
+    fn round_trip_pseudo_random_bytes() {
+        let mut state = 0x2545_f491_u32;
+        let data = (0..50_000)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 17;
+                state ^= state << 5;
+                // 一致が適度に発生するように値の範囲を狭める
+                (state % 16) as u8 * 17
+            })
+            .collect::<Vec<_>>();
+        assert_round_trip(&data);
+    }
+}
@@ -0,0 +1,176 @@
+//! クラッシュやエラーの報告に添える診断情報。
+//! 実行中の入力のダイジェストと反復回数をスレッドローカルに保持し、
+//! パニック時にホストへ渡せるようにする。
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+/// エラー情報を受け取る関数。
+pub type Reporter = Rc<dyn Fn(&ErrorReport)>;
+
+thread_local! {
+    static CONTEXT: RefCell<ErrorContext> = RefCell::new(ErrorContext::default());
+    static REPORTER: RefCell<Option<Reporter>> = const { RefCell::new(None) };
+}
+
+#[derive(Debug, Clone, Default)]
+struct ErrorContext {
+    input_digest: Option<String>,
+    iteration: Option<u32>,
+}
+
+/// ホストに渡すエラー情報。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ErrorReport {
+    pub kind: ErrorKind,
+    /// フロントエンドでメッセージを翻訳するための、バージョン間で変わらない識別子
+    pub code: ErrorCode,
+    pub message: String,
+    /// 実行中だった入力のダイジェスト。入力の受け取り前に起きたエラーでは `None`。
+    pub input_digest: Option<String>,
+    /// 0 始まりの反復回数。戦闘の実行中以外に起きたエラーでは `None`。
+    pub iteration: Option<u32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    Panic,
+    InvalidInput,
+}
+
+/// エラーの種類ごとの機械可読なコード。
+/// シリアライズ後の値 (`FLEET_EMPTY` など) は互換性のため変更しないこと。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// シミュレーション中のパニック
+    Panic,
+    /// 味方艦隊のデシリアライズに失敗した
+    FriendFleetParseFailed,
+    /// 敵艦隊のデシリアライズに失敗した
+    EnemyFleetsParseFailed,
+    /// オプションのデシリアライズに失敗した
+    OptionsParseFailed,
+    /// マスターデータのデシリアライズに失敗した
+    MasterDataParseFailed,
+    /// マスターデータの取得に失敗した
+    MasterDataFetchFailed,
+    /// 計算式の定数のデシリアライズに失敗した
+    FormulaConstantsParseFailed,
+    /// 海域マップのデシリアライズに失敗した
+    MapParseFailed,
+    /// 設定の探索条件 (`PlanQuery`) のデシリアライズに失敗した
+    PlanQueryParseFailed,
+    /// 入力一式 (`SimulationRequest`) のデシリアライズに失敗した
+    RequestParseFailed,
+    /// 戦闘 API のレスポンスの読み込みに失敗した
+    ApiLogParseFailed,
+    /// 厳格モードで、入力に未知のフィールドが含まれていた
+    UnknownField,
+    /// 指定された計算式の定数セットが存在しない
+    FormulaSetUnknown,
+    /// 単独で実行できないフェーズが指定された
+    PhaseUnsupported,
+    /// スクリプトによるフックの構文解析に失敗した
+    ScriptCompileFailed,
+    /// 対応していないスキーマバージョンが指定された
+    SchemaVersionUnsupported,
+    /// 艦隊に艦がいない
+    FleetEmpty,
+    /// 艦隊の艦数が上限を超えている
+    FleetTooLarge,
+    /// 現在HPが最大HPを超えている艦がいる
+    ShipHpExceedsMax,
+    /// 艦のステータスがマスターデータの範囲から大きく外れている
+    ShipStatImplausible,
+    /// 装備の数がスロット数を超えている艦がいる
+    EquipmentExceedsSlots,
+    /// 装備の改修値 (★) が上限の 10 を超えている
+    EquipmentImprovementOutOfRange,
+    /// 装甲が 0 の深海棲艦の鬼級・姫級がいる
+    BossArmorZero,
+    /// 敵艦隊の候補がない
+    EnemyFleetsEmpty,
+    /// 敵艦隊の出現確率が不正 (0 以下、または同じマスでの合計が 1 でない)
+    ProbabilitySumInvalid,
+    /// 連合艦隊が種別ごとの編成条件を満たしていない
+    CombinedFleetCompositionInvalid,
+    /// 基地航空隊に中隊が配備されていない
+    LandBaseEmpty,
+    /// 支援艦隊が編成条件を満たしていない
+    SupportFleetInvalid,
+    /// 友軍艦隊の出現候補が不正
+    FriendlyFleetInvalid,
+}
+
+impl ErrorCode {
+    pub fn kind(&self) -> ErrorKind {
+        match self {
+            ErrorCode::Panic => ErrorKind::Panic,
+            _ => ErrorKind::InvalidInput,
+        }
+    }
+}
+
+impl ErrorReport {
+    /// 現在の診断情報を添えてエラー情報を作る。
+    pub fn new(code: ErrorCode, message: String) -> Self {
+        let context = CONTEXT.with(|c| c.borrow().clone());
+        Self {
+            kind: code.kind(),
+            code,
+            message,
+            input_digest: context.input_digest,
+            iteration: context.iteration,
+        }
+    }
+}
+
+/// エラー情報を受け取る関数を登録する。`None` を渡すと登録を解除する。
+pub fn set_reporter(reporter: Option<Reporter>) {
+    REPORTER.with(|r| *r.borrow_mut() = reporter);
+}
+
+/// 登録された関数にエラー情報を渡す。関数が登録されていない場合は何もしない。
+pub fn report(report: ErrorReport) {
+    // 報告の中で再び登録し直される場合に備えて、呼び出し前に借用を解放する
+    let reporter = REPORTER.with(|r| r.borrow().clone());
+    if let Some(reporter) = reporter {
+        reporter(&report);
+    }
+}
+
+/// シミュレーションの開始時に、入力のダイジェストを記録する。
+pub fn begin(input_digest: String) {
+    CONTEXT.with(|c| {
+        *c.borrow_mut() = ErrorContext {
+            input_digest: Some(input_digest),
+            iteration: None,
+        }
+    });
+}
+
+pub fn set_iteration(iteration: u32) {
+    CONTEXT.with(|c| c.borrow_mut().iteration = Some(iteration));
+}
+
+/// シミュレーションが正常に終了したときに診断情報を消去する。
+pub fn finish() {
+    CONTEXT.with(|c| *c.borrow_mut() = ErrorContext::default());
+}
+
+/// 入力を JSON にした上での FNV-1a (64 bit) ハッシュを 16 進文字列で返す。
+/// 同じ入力で再現できるかの確認や、バグ報告の突き合わせに使う。
+pub fn input_digest<T: Serialize + ?Sized>(input: &T) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01b3;
+
+    let bytes = serde_json::to_vec(input).unwrap_or_default();
+    let hash = bytes.iter().fold(OFFSET_BASIS, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(PRIME)
+    });
+    format!("{:016x}", hash)
+}
@@ -0,0 +1,46 @@
+//! 戦闘エンジンを他の Rust ツールに組み込むための公開 API。
+//! `run_simulation` などのエントリーポイントを介さずに、戦闘を1回ずつ組み立てて実行できる。
+//!
+//! このモジュールから公開する型とメソッドは互換性を保つ対象とする。
+//! 戦闘の計算式を変更した場合は `FORMULA_VERSION` で区別する。
+//! `Battle::with_seed` (または `SimulationOptions.seed`) で同じシードを与えた戦闘は、
+//! `FORMULA_VERSION` が同じ間は wasm とネイティブのどちらのビルドでも同じ結果になる。
+//!
+//! 入力の検証や既定値の補完は行わないため、必要に応じて事前に `sim_core::check_input` で確認すること。
+//!
+//! ```
+//! use sim_core::engine::{Battle, BattleResult, NodeType, PhasePipeline};
+//! use sim_core::interface::{EnemyFleet, Fleet, SimulationOptions};
+//!
+//! let ship = serde_json::json!({
+//!     "id": 1, "name": "長門", "shipTypeId": 9,
+//!     "status": {
+//!         "maxHp": 90, "nowHp": 90, "firepower": 90, "armor": 90,
+//!         "torpedo": 0, "antiAircraft": 50, "condition": 49, "range": "long"
+//!     },
+//!     "equips": []
+//! });
+//! let friend: Fleet =
+//!     serde_json::from_value(serde_json::json!({ "ships": [ship], "formation": "line_ahead" }))
+//!         .unwrap();
+//! let enemy: EnemyFleet = serde_json::from_value(serde_json::json!({
+//!     "area": 1, "map": 1, "node": "A", "probability": 1.0,
+//!     "ships": [ship], "formation": "line_ahead"
+//! }))
+//! .unwrap();
+//!
+//! let options = SimulationOptions::default();
+//! let mut battle = Battle::new(&friend, &enemy, &options);
+//! PhasePipeline::new(NodeType::Normal, &options).run(&mut battle);
+//!
+//! let result = BattleResult::calculate(&battle);
+//! let remaining_hp = battle.log().friend_snapshots[0].hp();
+//! # let _ = (result, remaining_hp);
+//! ```
+pub use crate::battle::{
+    ActionLog, AirCombatPhase, AirState, AntiAirCutIn, ArtilleryPhase, AttackLog, AttackType,
+    Battle, BattleDirection, BattleLog, BattlePhase, BattleReport, BattleResult, BattleSetup,
+    ClosingTorpedoPhase, FleetSide, LandBasePhase, LogEntry, NightPhase, NodeType,
+    OpeningTorpedoPhase, Phase, PhasePipeline, RngLabel, ShipRef, ShipSnapshot, SinglePhase,
+    SpecialAttack, FORMULA_VERSION,
+};
@@ -0,0 +1,50 @@
+use serde::{Deserialize, Serialize};
+
+/// 深海棲艦の階級を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AbyssalClass {
+    /// 無印
+    Normal,
+    /// elite
+    Elite,
+    /// flagship
+    Flagship,
+    /// 鬼級
+    Demon,
+    /// 姫級
+    Princess,
+}
+
+impl AbyssalClass {
+    /// 深海棲艦の艦船固有IDの下限。これ未満のIDは艦娘を表す。
+    pub const MIN_ID: u16 = 1501;
+
+    /// 艦船固有IDと艦名から階級を推定する。
+    /// 艦娘のIDが与えられた場合は `None` を返す。
+    ///
+    /// 艦名は「駆逐イ級 elite」「戦艦ル級 flagship改」「戦艦棲姫」「空母水鬼」のような
+    /// ゲーム内表記を前提とする。
+    pub fn infer(id: u16, name: &str) -> Option<Self> {
+        if id < Self::MIN_ID {
+            return None;
+        }
+        let class = if name.contains('姫') {
+            AbyssalClass::Princess
+        } else if name.contains('鬼') {
+            AbyssalClass::Demon
+        } else if name.contains("flagship") {
+            AbyssalClass::Flagship
+        } else if name.contains("elite") {
+            AbyssalClass::Elite
+        } else {
+            AbyssalClass::Normal
+        };
+        Some(class)
+    }
+
+    /// 鬼級または姫級かどうかを判定する。
+    pub fn is_boss(&self) -> bool {
+        matches!(self, AbyssalClass::Demon | AbyssalClass::Princess)
+    }
+}
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::fleet::{Fleet, FleetLike};
+
+/// 1艦隊に編成できる艦の数。
+const MAX_SHIPS: usize = 6;
+
+// 編成条件の判定に使う艦種ID
+const CARRIERS: &[u16] = &[7, 11, 18]; // 軽空母、正規空母、装甲空母
+const BATTLESHIPS: &[u16] = &[8, 9, 10]; // 高速戦艦、戦艦、航空戦艦
+const LIGHT_CRUISERS: &[u16] = &[3]; // 軽巡洋艦
+const DESTROYERS: &[u16] = &[2]; // 駆逐艦
+const ESCORTS: &[u16] = &[1, 2]; // 海防艦、駆逐艦
+
+/// 連合艦隊を受け取る構造体。
+/// 子に連合艦隊の種別、第1艦隊 (主力) と第2艦隊 (随伴) を持つ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct CombinedFleet {
+    fleet_type: CombinedFleetType,
+    main: Fleet,
+    escort: Fleet,
+}
+
+/// 連合艦隊の種別を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum CombinedFleetType {
+    /// 空母機動部隊
+    CarrierTaskForce,
+    /// 水上打撃部隊
+    SurfaceTaskForce,
+    /// 輸送護衛部隊
+    TransportEscort,
+}
+
+/// 艦種ごとの編成条件。`ship_types` に該当する艦の数が `min` 以上 `max` 以下でなければならない。
+struct Requirement {
+    ship_types: &'static [u16],
+    min: usize,
+    max: usize,
+    description: &'static str,
+}
+
+impl CombinedFleetType {
+    /// 第1艦隊と第2艦隊それぞれの編成条件を取得する。
+    fn requirements(&self) -> (Vec<Requirement>, Vec<Requirement>) {
+        let req = |ship_types, min, max, description| Requirement {
+            ship_types,
+            min,
+            max,
+            description,
+        };
+        match self {
+            CombinedFleetType::CarrierTaskForce => (
+                vec![
+                    req(CARRIERS, 2, 4, "carriers"),
+                    req(BATTLESHIPS, 0, 2, "battleships"),
+                ],
+                vec![
+                    req(LIGHT_CRUISERS, 1, MAX_SHIPS, "light cruisers"),
+                    req(DESTROYERS, 2, MAX_SHIPS, "destroyers"),
+                    req(CARRIERS, 0, 1, "carriers"),
+                ],
+            ),
+            CombinedFleetType::SurfaceTaskForce => (
+                vec![
+                    req(BATTLESHIPS, 2, 4, "battleships"),
+                    req(CARRIERS, 0, 2, "carriers"),
+                ],
+                vec![
+                    req(LIGHT_CRUISERS, 1, MAX_SHIPS, "light cruisers"),
+                    req(DESTROYERS, 2, MAX_SHIPS, "destroyers"),
+                ],
+            ),
+            CombinedFleetType::TransportEscort => (
+                vec![req(ESCORTS, 4, MAX_SHIPS, "destroyers or escorts")],
+                vec![
+                    req(LIGHT_CRUISERS, 1, MAX_SHIPS, "light cruisers"),
+                    req(ESCORTS, 3, MAX_SHIPS, "destroyers or escorts"),
+                ],
+            ),
+        }
+    }
+}
+
+impl CombinedFleet {
+    pub fn fleet_type(&self) -> &CombinedFleetType {
+        &self.fleet_type
+    }
+
+    pub fn main(&self) -> &Fleet {
+        &self.main
+    }
+
+    pub fn escort(&self) -> &Fleet {
+        &self.escort
+    }
+
+    /// フロントエンドから受けとったデータの妥当性を検証し、必要に応じて修正する。
+    /// 各艦隊には `FleetLike::validate` と同じ検証を行う。
+    ///
+    /// 修正不能な例外 (最初に見つかったもののエラー情報を返す)
+    /// - いずれかの艦隊が空、または 6 隻を超えている
+    /// - 連合艦隊の種別ごとの艦種の編成条件を満たしていない
+    pub fn validate(&mut self) -> Result<(), ErrorReport> {
+        let (main_requirements, escort_requirements) = self.fleet_type.requirements();
+        for (label, fleet, requirements) in [
+            ("main", &mut self.main, main_requirements),
+            ("escort", &mut self.escort, escort_requirements),
+        ] {
+            fleet.validate()?;
+            if fleet.ships().len() > MAX_SHIPS {
+                return Err(ErrorReport::new(
+                    ErrorCode::FleetTooLarge,
+                    format!(
+                        "Combined {} fleet has too many ships: {}",
+                        label,
+                        fleet.ships().len()
+                    ),
+                ));
+            }
+            for requirement in requirements {
+                let count = fleet
+                    .ships()
+                    .iter()
+                    .filter(|s| requirement.ship_types.contains(&s.ship_type_id()))
+                    .count();
+                if count < requirement.min || count > requirement.max {
+                    return Err(ErrorReport::new(
+                        ErrorCode::CombinedFleetCompositionInvalid,
+                        format!(
+                            "Combined {} fleet ({:?}) must have {} to {} {}, but has {}",
+                            label,
+                            self.fleet_type,
+                            requirement.min,
+                            requirement.max,
+                            requirement.description,
+                            count
+                        ),
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
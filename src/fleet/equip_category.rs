@@ -0,0 +1,184 @@
+use serde::{Deserialize, Serialize};
+
+/// 装備の種別を表す列挙型。
+/// 装備種別ID (`equipTypeId` の3番目の要素) に対応する。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EquipCategory {
+    SmallCaliberMainGun,
+    MediumCaliberMainGun,
+    LargeCaliberMainGun,
+    SecondaryGun,
+    Torpedo,
+    CarrierBasedFighter,
+    CarrierBasedDiveBomber,
+    CarrierBasedTorpedoBomber,
+    CarrierBasedRecon,
+    SeaplaneRecon,
+    SeaplaneBomber,
+    SmallRadar,
+    LargeRadar,
+    Sonar,
+    DepthCharge,
+    AntiAircraftShell,
+    ArmorPiercingShell,
+    AntiAircraftGun,
+    MidgetSubmarine,
+    LandingCraft,
+    Autogyro,
+    AntiSubmarinePatrolAircraft,
+    Searchlight,
+    SubmarineTorpedo,
+    StarShell,
+    FlyingBoat,
+    LargeSearchlight,
+    SeaplaneFighter,
+    LandBasedAttackAircraft,
+    InterceptorFighter,
+    JetFighter,
+    JetFighterBomber,
+    /// 高射装置
+    AntiAircraftFireDirector,
+    /// 上記以外の種別
+    Other,
+}
+
+impl EquipCategory {
+    /// 装備種別IDから種別を判定する。
+    pub fn from_type_id(id: u16) -> Self {
+        match id {
+            1 => EquipCategory::SmallCaliberMainGun,
+            2 => EquipCategory::MediumCaliberMainGun,
+            3 | 38 => EquipCategory::LargeCaliberMainGun,
+            4 => EquipCategory::SecondaryGun,
+            5 => EquipCategory::Torpedo,
+            6 => EquipCategory::CarrierBasedFighter,
+            7 => EquipCategory::CarrierBasedDiveBomber,
+            8 => EquipCategory::CarrierBasedTorpedoBomber,
+            9 | 94 => EquipCategory::CarrierBasedRecon,
+            10 => EquipCategory::SeaplaneRecon,
+            11 => EquipCategory::SeaplaneBomber,
+            12 => EquipCategory::SmallRadar,
+            13 | 93 => EquipCategory::LargeRadar,
+            14 | 40 => EquipCategory::Sonar,
+            15 => EquipCategory::DepthCharge,
+            18 => EquipCategory::AntiAircraftShell,
+            19 => EquipCategory::ArmorPiercingShell,
+            21 => EquipCategory::AntiAircraftGun,
+            22 => EquipCategory::MidgetSubmarine,
+            24 | 46 => EquipCategory::LandingCraft,
+            25 => EquipCategory::Autogyro,
+            26 => EquipCategory::AntiSubmarinePatrolAircraft,
+            29 => EquipCategory::Searchlight,
+            32 => EquipCategory::SubmarineTorpedo,
+            33 => EquipCategory::StarShell,
+            36 => EquipCategory::AntiAircraftFireDirector,
+            41 => EquipCategory::FlyingBoat,
+            42 => EquipCategory::LargeSearchlight,
+            45 => EquipCategory::SeaplaneFighter,
+            47 => EquipCategory::LandBasedAttackAircraft,
+            48 => EquipCategory::InterceptorFighter,
+            56 => EquipCategory::JetFighter,
+            57 => EquipCategory::JetFighterBomber,
+            _ => EquipCategory::Other,
+        }
+    }
+
+    /// 小口径・中口径・大口径主砲かどうかを判定する。
+    pub fn is_main_gun(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::SmallCaliberMainGun
+                | EquipCategory::MediumCaliberMainGun
+                | EquipCategory::LargeCaliberMainGun
+        )
+    }
+
+    /// 魚雷・潜水艦魚雷かどうかを判定する。
+    pub fn is_torpedo(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::Torpedo | EquipCategory::SubmarineTorpedo
+        )
+    }
+
+    /// 艦上攻撃機・艦上爆撃機など、空母の砲撃戦の攻撃手段となる艦載機かどうかを判定する。
+    /// 水上爆撃機はここに含まれない。
+    pub fn is_carrier_attack_aircraft(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::CarrierBasedDiveBomber
+                | EquipCategory::CarrierBasedTorpedoBomber
+                | EquipCategory::JetFighterBomber
+        )
+    }
+
+    /// 水上爆撃機・水上戦闘機かどうかを判定する。
+    pub fn is_seaplane_combat_aircraft(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::SeaplaneBomber | EquipCategory::SeaplaneFighter
+        )
+    }
+
+    /// 制空値の計算に含まれる航空機かどうかを判定する。
+    pub fn counts_for_air_power(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::CarrierBasedFighter
+                | EquipCategory::CarrierBasedDiveBomber
+                | EquipCategory::CarrierBasedTorpedoBomber
+                | EquipCategory::SeaplaneBomber
+                | EquipCategory::SeaplaneFighter
+                | EquipCategory::InterceptorFighter
+                | EquipCategory::JetFighter
+                | EquipCategory::JetFighterBomber
+        )
+    }
+
+    /// 航空戦の航空攻撃 (開幕航空攻撃) に参加する航空機かどうかを判定する。
+    pub fn participates_in_airstrike(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::CarrierBasedDiveBomber
+                | EquipCategory::CarrierBasedTorpedoBomber
+                | EquipCategory::SeaplaneBomber
+                | EquipCategory::JetFighterBomber
+        )
+    }
+
+    /// 艦載機・水上機による対潜攻撃に使える航空機の種別かどうかを判定する。
+    /// オートジャイロと対潜哨戒機は別の攻撃種別として扱うため、ここには含まれない。
+    /// 実際に攻撃できるかは装備の対潜値にもよる。
+    pub fn is_asw_capable_aircraft(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::CarrierBasedDiveBomber
+                | EquipCategory::CarrierBasedTorpedoBomber
+                | EquipCategory::SeaplaneRecon
+                | EquipCategory::SeaplaneBomber
+                | EquipCategory::FlyingBoat
+        )
+    }
+
+    /// 弾着観測射撃の発動条件となる水上機かどうかを判定する。
+    pub fn enables_artillery_spotting(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::SeaplaneRecon | EquipCategory::SeaplaneBomber
+        )
+    }
+
+    /// 小型電探・大型電探かどうかを判定する。
+    pub fn is_radar(&self) -> bool {
+        matches!(self, EquipCategory::SmallRadar | EquipCategory::LargeRadar)
+    }
+
+    /// 探照灯・大型探照灯かどうかを判定する。
+    pub fn is_searchlight(&self) -> bool {
+        matches!(
+            self,
+            EquipCategory::Searchlight | EquipCategory::LargeSearchlight
+        )
+    }
+}
@@ -1,18 +1,110 @@
 use serde::{Deserialize, Serialize};
 
+use crate::fleet::equip_category::EquipCategory;
 use crate::fleet::status::Range;
 
+/// 陸上型を攻撃できる艦上爆撃機の装備ID。
+/// 艦上爆撃機 (噴式戦闘爆撃機を含む) のうち、これら以外は陸上型を攻撃できない。
+const INSTALLATION_CAPABLE_BOMBER_IDS: [u16; 8] = [
+    148, // 試製南山
+    154, // 零式艦戦62型(爆戦/岩井隊)
+    233, // F4U-1D
+    277, // FM-2
+    305, // Ju87C改二(KMX搭載機)
+    306, // Ju87C改二(KMX搭載機/熟練)
+    319, // 彗星一二型(六三四空/三号爆弾搭載機)
+    320, // 彗星一二型(三一号光電管爆弾搭載機)
+];
+
+/// 大口径主砲の口径 (mm) ごとの装備ID。戦艦のフィット砲補正の判定に使う。
+const LARGE_GUN_CALIBERS: [(u16, &[u16]); 5] = [
+    (
+        356,
+        &[
+            7,   // 35.6cm連装砲
+            103, // 試製35.6cm三連装砲
+            104, // 35.6cm連装砲(ダズル迷彩)
+            289, // 35.6cm三連装砲改(ダズル迷彩仕様)
+            328, // 35.6cm連装砲改
+            329, // 35.6cm連装砲改二
+        ],
+    ),
+    (
+        381,
+        &[
+            76,  // 38cm連装砲
+            114, // 38cm連装砲改
+            190, // 38.1cm Mk.I連装砲
+            192, // 38.1cm Mk.I/N連装砲改
+            245, // 38cm四連装砲
+            246, // 38cm四連装砲改
+        ],
+    ),
+    (
+        410,
+        &[
+            8,   // 41cm連装砲
+            105, // 試製41cm三連装砲
+            236, // 41cm三連装砲改
+            290, // 41cm三連装砲改二
+            318, // 41cm連装砲改二
+        ],
+    ),
+    (
+        460,
+        &[
+            9,   // 46cm三連装砲
+            117, // 試製46cm連装砲
+            276, // 46cm三連装砲改
+        ],
+    ),
+    (
+        510,
+        &[
+            128, // 試製51cm連装砲
+            281, // 51cm連装砲
+        ],
+    ),
+];
+
+/// 軽巡洋艦のフィット砲補正の対象となる単装砲の装備ID。
+const LIGHT_CRUISER_SINGLE_GUN_IDS: [u16; 2] = [
+    4,  // 14cm単装砲
+    11, // 15.2cm単装砲
+];
+
+/// 軽巡洋艦のフィット砲補正の対象となる連装砲の装備ID。
+const LIGHT_CRUISER_TWIN_GUN_IDS: [u16; 4] = [
+    65,  // 15.2cm連装砲
+    119, // 14cm連装砲
+    139, // 15.2cm連装砲改
+    310, // 14cm連装砲改
+];
+
+/// 改修値 (★) の上限。
+const MAX_IMPROVEMENT: u8 = 10;
+
 /// 艦娘が装備している各装備品を表す構造体。
-/// 外部には公開されない。
+/// ステータスはデシリアライズ時に未設定の可能性があるため陰蔽されており、ゲッターメソッドを通じてのみアクセス可能。
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 #[serde(rename_all = "camelCase", default)]
-pub(super) struct Equipment {
+pub struct Equipment {
     id: u16,
     name: Option<String>,
     equip_type_id: Option<Vec<u16>>,
     status: Option<EquipmentStatus>,
+    /// 改修値 (★)。未改修の場合は 0
+    improvement: u8,
 }
 impl Equipment {
+    /// 装備IDを取得する。
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+    /// ステータスが入力されているかどうかを判定する。未入力の場合、各ステータスは 0 とみなされる。
+    pub fn has_status(&self) -> bool {
+        self.status.is_some()
+    }
     /// 火力ステータスを取得する。
     pub fn firepower(&self) -> u16 {
         self.status.as_ref().map_or(0, |s| s.firepower)
@@ -70,12 +162,138 @@ impl Equipment {
         self.status.as_ref().map_or(0, |s| s.aircraft_cost)
     }
 
-    /// この装備が攻撃可能な航空機かどうかを判定する。
-    pub fn is_attack_aircraft(&self) -> bool {
-        let Some(id) = &self.equip_type_id else {
-            return false;
+    /// 大口径主砲の口径 (mm) を取得する。分類表にない装備は `None` を返す。
+    pub fn large_gun_caliber(&self) -> Option<u16> {
+        LARGE_GUN_CALIBERS
+            .iter()
+            .find(|(_, ids)| ids.contains(&self.id))
+            .map(|(caliber, _)| *caliber)
+    }
+
+    /// 軽巡洋艦のフィット砲補正の対象となる単装砲かどうかを判定する。
+    pub fn is_light_cruiser_single_gun(&self) -> bool {
+        LIGHT_CRUISER_SINGLE_GUN_IDS.contains(&self.id)
+    }
+
+    /// 軽巡洋艦のフィット砲補正の対象となる連装砲かどうかを判定する。
+    pub fn is_light_cruiser_twin_gun(&self) -> bool {
+        LIGHT_CRUISER_TWIN_GUN_IDS.contains(&self.id)
+    }
+
+    /// 改修値 (★) を取得する。上限の 10 を超える値は入力の検証で報告し、10 とみなす。
+    pub fn improvement(&self) -> u8 {
+        self.improvement.min(MAX_IMPROVEMENT)
+    }
+
+    /// 入力された改修値 (★) が上限の 10 を超えている場合に、その値を返す。
+    pub fn improvement_out_of_range(&self) -> Option<u8> {
+        (self.improvement > MAX_IMPROVEMENT).then_some(self.improvement)
+    }
+
+    /// 改修による昼戦の火力ボーナス `係数 × √★` を計算する。
+    pub fn firepower_improvement_bonus(&self) -> f64 {
+        let coefficient = match self.category() {
+            EquipCategory::LargeCaliberMainGun => 1.5,
+            EquipCategory::SmallCaliberMainGun
+            | EquipCategory::MediumCaliberMainGun
+            | EquipCategory::SecondaryGun
+            | EquipCategory::AntiAircraftShell
+            | EquipCategory::ArmorPiercingShell => 1.0,
+            EquipCategory::Sonar | EquipCategory::DepthCharge => 0.75,
+            _ => 0.0,
+        };
+        coefficient * self.improvement_sqrt()
+    }
+
+    /// 改修による対潜攻撃力のボーナス `係数 × √★` を計算する。
+    pub fn anti_submarine_improvement_bonus(&self) -> f64 {
+        let coefficient = match self.category() {
+            EquipCategory::Sonar | EquipCategory::DepthCharge => 2.0 / 3.0,
+            _ => 0.0,
+        };
+        coefficient * self.improvement_sqrt()
+    }
+
+    /// 改修による命中項のボーナス `係数 × √★` を計算する。
+    pub fn accuracy_improvement_bonus(&self) -> f64 {
+        let coefficient = match self.category() {
+            category if category.is_main_gun() => 1.0,
+            EquipCategory::SecondaryGun | EquipCategory::ArmorPiercingShell => 1.0,
+            category if category.is_radar() => 1.7,
+            _ => 0.0,
+        };
+        coefficient * self.improvement_sqrt()
+    }
+
+    /// 改修による夜戦の攻撃力のボーナス `係数 × √★` を計算する。
+    pub fn night_improvement_bonus(&self) -> f64 {
+        let coefficient = match self.category() {
+            category if category.is_main_gun() || category.is_torpedo() => 1.0,
+            EquipCategory::SecondaryGun
+            | EquipCategory::AntiAircraftShell
+            | EquipCategory::ArmorPiercingShell => 1.0,
+            _ => 0.0,
         };
-        matches!(id[2], 7 | 8)
+        coefficient * self.improvement_sqrt()
+    }
+
+    fn improvement_sqrt(&self) -> f64 {
+        (self.improvement() as f64).sqrt()
+    }
+
+    /// 装備種別を取得する。種別IDが未設定の場合は `EquipCategory::Other` を返す。
+    pub fn category(&self) -> EquipCategory {
+        self.equip_type_id
+            .as_ref()
+            .and_then(|id| id.get(2))
+            .map_or(EquipCategory::Other, |id| EquipCategory::from_type_id(*id))
+    }
+
+    /// 夜間戦闘機・夜間攻撃機かどうかを判定する。
+    /// 種別IDでは通常の艦上機と区別できないため、アイコン種別 (`equipTypeId` の4番目の要素) で判定する。
+    pub fn is_night_aircraft(&self) -> bool {
+        matches!(
+            self.equip_type_id.as_ref().and_then(|id| id.get(3)),
+            Some(45 | 46)
+        )
+    }
+
+    /// 高角砲かどうかを判定する。
+    /// 高角砲は小口径主砲・副砲の種別に含まれるため、アイコン種別 (`equipTypeId` の4番目の要素) で判定する。
+    pub fn is_high_angle_gun(&self) -> bool {
+        self.equip_type_id.as_ref().and_then(|id| id.get(3)) == Some(&16)
+    }
+
+    /// 高射装置を内蔵した高角砲かどうかを判定する。対空値が 8 以上の高角砲をこれとみなす。
+    pub fn is_high_angle_gun_with_fire_director(&self) -> bool {
+        self.is_high_angle_gun() && self.anti_aircraft() >= 8
+    }
+
+    /// 対空電探 (対空値が 2 以上の電探) かどうかを判定する。
+    pub fn is_anti_aircraft_radar(&self) -> bool {
+        self.category().is_radar() && self.anti_aircraft() >= 2
+    }
+
+    /// 集中配備の機銃 (対空値が 9 以上の対空機銃) かどうかを判定する。
+    pub fn is_concentrated_anti_aircraft_gun(&self) -> bool {
+        self.category() == EquipCategory::AntiAircraftGun && self.anti_aircraft() >= 9
+    }
+
+    /// この装備が空母の攻撃手段となる艦載機かどうかを判定する。
+    /// 水上爆撃機は航空攻撃には参加するが、ここには含まれない。
+    pub fn is_attack_aircraft(&self) -> bool {
+        self.category().is_carrier_attack_aircraft()
+    }
+
+    /// この装備で陸上型を攻撃できるかどうかを判定する。
+    /// 艦上爆撃機は一部の機種のみが対地攻撃でき、それ以外の装備は制限を受けない。
+    pub fn can_attack_installation(&self) -> bool {
+        match self.category() {
+            EquipCategory::CarrierBasedDiveBomber | EquipCategory::JetFighterBomber => {
+                INSTALLATION_CAPABLE_BOMBER_IDS.contains(&self.id)
+            }
+            _ => true,
+        }
     }
 }
 
@@ -1,8 +1,11 @@
 use crate::fleet::ship::Ship;
-use log::warn;
 use serde::{Deserialize, Serialize};
 
 use crate::battle::ShipSnapshot;
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::formula::SynergyRule;
+use crate::interface::{AppliedDefault, Locale};
+use crate::master::MasterData;
 
 /// `FleetLike`トレイトは、敵艦隊と味方艦隊に共通するインターフェースを定義、実装する。
 pub trait FleetLike {
@@ -23,22 +26,127 @@ pub trait FleetLike {
         self.ships().is_empty()
     }
 
+    /// 艦隊の制空値を計算する。各艦の制空値の合計。
+    fn fighter_power(&self) -> u32 {
+        self.ships().iter().map(|s| s.fighter_power()).sum()
+    }
+
     /// フロントエンドから受けとったデータの妥当性を検証し、必要に応じて修正する。
     /// 修正可能な例外
     /// - 陣形が未設定
     ///
-    /// 修正不能な例外
+    /// 修正不能な例外 (エラー情報を返す)
     /// - 艦隊が空
-    fn validate(&mut self) -> bool {
+    /// - 現在HPが最大HPを超えている艦がいる
+    fn validate(&mut self) -> Result<(), ErrorReport> {
         if self.is_empty() {
-            warn!("Fleet is empty:  {:?}", self.ships());
-            return false;
+            return Err(ErrorReport::new(
+                ErrorCode::FleetEmpty,
+                "Fleet is empty".to_string(),
+            ));
+        }
+        if let Some(ship) = self.ships().iter().find(|s| s.hp() > s.max_hp()) {
+            return Err(ErrorReport::new(
+                ErrorCode::ShipHpExceedsMax,
+                format!(
+                    "Ship HP exceeds max HP: {} ({}/{})",
+                    ship.name(),
+                    ship.hp(),
+                    ship.max_hp()
+                ),
+            ));
         }
         if self.formation().is_none() {
             warn!("Fleet formation is not set:  {:?}", self.ships());
             self.set_formation_default();
         }
-        true
+        Ok(())
+    }
+
+    /// 入力を変更せずに検証し、見つかった問題をすべて返す。
+    /// `validate` と異なり最初の問題で打ち切らず、装備数がスロット数を超えている艦や、改修値が上限を超えている装備も報告する。
+    fn check(&self) -> Vec<ErrorReport> {
+        if self.is_empty() {
+            return vec![ErrorReport::new(
+                ErrorCode::FleetEmpty,
+                "Fleet is empty".to_string(),
+            )];
+        }
+        let mut errors = Vec::new();
+        for ship in self.ships() {
+            if ship.hp() > ship.max_hp() {
+                errors.push(ErrorReport::new(
+                    ErrorCode::ShipHpExceedsMax,
+                    format!(
+                        "Ship HP exceeds max HP: {} ({}/{})",
+                        ship.name(),
+                        ship.hp(),
+                        ship.max_hp()
+                    ),
+                ));
+            }
+            errors.extend(ship.check_equipment_slots().err());
+            errors.extend(ship.check_improvements().err());
+        }
+        errors
+    }
+
+    /// 入力になかったために既定値で補われる値 (陣形、艦のステータス) を列挙する。
+    /// `validate` による補完の前に呼び出すこと。`path` は艦隊の入力上の位置。
+    fn applied_defaults(&self, path: &str) -> Vec<AppliedDefault> {
+        let mut defaults = Vec::new();
+        if self.formation().is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.formation", path),
+                Formation::default(),
+            ));
+        }
+        for (i, ship) in self.ships().iter().enumerate() {
+            defaults.extend(ship.applied_defaults(&format!("{}.ships[{}]", path, i)));
+        }
+        defaults
+    }
+
+    /// 艦隊に所属する艦の艦名を指定された言語の表記に置き換える。
+    fn localize_names(&mut self, master: Option<&MasterData>, locale: &Locale) {
+        let ships = self
+            .ships()
+            .iter()
+            .cloned()
+            .map(|mut ship| {
+                ship.localize_name(master, locale);
+                ship
+            })
+            .collect();
+        self.set_ships(ships);
+    }
+
+    /// 艦隊に所属する艦すべてに、マスターデータの装備ボーナス表を適用する。
+    fn apply_equipment_bonuses(&mut self, master: &MasterData) {
+        let ships = self
+            .ships()
+            .iter()
+            .cloned()
+            .map(|mut ship| {
+                ship.apply_equipment_bonus(master);
+                ship
+            })
+            .collect();
+        self.set_ships(ships);
+    }
+
+    /// 艦隊に所属する艦すべてに、装備のシナジー規則を適用する。
+    fn apply_synergies(&mut self, rules: &[SynergyRule]) {
+        let ships = self
+            .ships()
+            .iter()
+            .cloned()
+            .map(|mut ship| {
+                ship.apply_synergies(rules);
+                ship
+            })
+            .collect();
+        self.set_ships(ships);
     }
 
     fn apply_snapshot(&self, snapshots: &[ShipSnapshot]) -> Self
@@ -73,7 +181,7 @@ impl FleetLike for Fleet {
         self.formation.clone()
     }
     fn set_formation_default(&mut self) {
-        self.formation = Some(Formation::LineAhead);
+        self.formation = Some(Formation::default());
     }
 }
 impl FleetLike for EnemyFleet {
@@ -87,7 +195,7 @@ impl FleetLike for EnemyFleet {
         self.formation.clone()
     }
     fn set_formation_default(&mut self) {
-        self.formation = Some(Formation::LineAhead);
+        self.formation = Some(Formation::default());
     }
 }
 
@@ -111,12 +219,59 @@ pub struct EnemyFleet {
     pub probability: f64,
     ships: Vec<Ship>,
     formation: Option<Formation>,
+    /// ゲージ破壊前の最終形態 (ラストダンス) の編成かどうか。
+    #[serde(default)]
+    final_form: bool,
+}
+
+impl Fleet {
+    pub fn new(ships: Vec<Ship>, formation: Option<Formation>) -> Self {
+        Self { ships, formation }
+    }
+
+    /// 艦隊の陣形を変更する。
+    pub fn set_formation(&mut self, formation: Formation) {
+        self.formation = Some(formation);
+    }
+}
+
+impl EnemyFleet {
+    pub fn new(
+        area: u16,
+        map: u16,
+        node: String,
+        probability: f64,
+        ships: Vec<Ship>,
+        formation: Option<Formation>,
+    ) -> Self {
+        Self {
+            area,
+            map,
+            node,
+            probability,
+            ships,
+            formation,
+            final_form: false,
+        }
+    }
+
+    /// 出現するマス名。
+    pub fn node(&self) -> &str {
+        &self.node
+    }
+
+    /// ゲージ破壊前の最終形態 (ラストダンス) の編成かどうか。
+    pub fn is_final_form(&self) -> bool {
+        self.final_form
+    }
 }
 
 /// 陣形の種類を表す列挙型。
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
 #[serde(rename_all = "snake_case")]
 pub enum Formation {
+    /// 陣形が未設定の場合はこの陣形とみなす
+    #[default]
     LineAhead,
     DoubleLine,
     Diamond,
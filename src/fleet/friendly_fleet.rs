@@ -0,0 +1,189 @@
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::fleet::fleet_like::{Fleet, Formation};
+use crate::fleet::ship::Ship;
+use crate::interface::Locale;
+use crate::master::MasterData;
+
+/// 出現確率の合計の判定で許容する丸め誤差。
+const PROBABILITY_TOLERANCE: f64 = 0.01;
+
+/// 友軍艦隊の出現候補の一覧を受け取る構造体。
+/// イベント海域で夜戦に駆けつける NPC 艦隊を、出現確率付きで指定する。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FriendlyFleetTable {
+    fleets: Vec<FriendlyFleet>,
+    /// 強友軍を要請するかどうか。要請しない場合、強友軍の候補は出現しない。
+    #[serde(default)]
+    request_strong: bool,
+}
+
+/// 友軍艦隊の出現候補1つ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct FriendlyFleet {
+    ships: Vec<Ship>,
+    /// 出現確率 (0.0〜1.0)。出現しうる候補の確率の合計の残りが、友軍艦隊が出現しない確率になる。
+    /// 以前の形式に合わせて `weight` としても指定できる。
+    #[serde(alias = "weight")]
+    probability: f64,
+    /// 強友軍かどうか。
+    #[serde(default)]
+    strong: bool,
+}
+
+impl FriendlyFleetTable {
+    pub fn fleets(&self) -> &[FriendlyFleet] {
+        &self.fleets
+    }
+
+    pub fn request_strong(&self) -> bool {
+        self.request_strong
+    }
+
+    /// 要請の有無を考慮して、出現しうる候補を列挙する。
+    pub fn candidates(&self) -> impl Iterator<Item = &FriendlyFleet> {
+        self.fleets
+            .iter()
+            .filter(move |f| !f.strong || self.request_strong)
+    }
+
+    /// `[0, 1)` の乱数 `r` に基づいて、出現する友軍艦隊を出現確率に従って選ぶ。
+    /// 選んだ候補の `fleets` 内での位置と共に返す。友軍艦隊が出現しない場合は `None` を返す。
+    pub fn choose(&self, r: f64) -> Option<(usize, &FriendlyFleet)> {
+        let mut cumulative = 0.0;
+        self.fleets
+            .iter()
+            .enumerate()
+            .filter(|(_, f)| !f.strong || self.request_strong)
+            .find(|(_, fleet)| {
+                cumulative += fleet.probability;
+                r < cumulative
+            })
+    }
+
+    /// 各候補の艦に、装備ボーナス表に基づく可視の装備ボーナスを加算する。
+    pub fn apply_equipment_bonuses(&mut self, master: &MasterData) {
+        for ship in self.fleets.iter_mut().flat_map(|f| f.ships.iter_mut()) {
+            ship.apply_equipment_bonus(master);
+        }
+    }
+
+    /// 各候補の艦の名前を `locale` の表記にする。
+    pub fn localize_names(&mut self, master: Option<&MasterData>, locale: &Locale) {
+        for ship in self.fleets.iter_mut().flat_map(|f| f.ships.iter_mut()) {
+            ship.localize_name(master, locale);
+        }
+    }
+
+    /// フロントエンドから受けとったデータの妥当性を検証する。
+    /// 修正不能な例外 (エラー情報を返す)
+    /// - 艦が編成されていない候補がある
+    /// - 出現確率が負、または有限でない候補がある
+    /// - 出現しうる候補の出現確率の合計が 1 を超える
+    pub fn validate(&self) -> Result<(), ErrorReport> {
+        for (i, fleet) in self.fleets.iter().enumerate() {
+            if fleet.ships.is_empty() {
+                return Err(ErrorReport::new(
+                    ErrorCode::FriendlyFleetInvalid,
+                    format!("Friendly fleet candidate {} has no ships", i),
+                ));
+            }
+            if !fleet.probability.is_finite() || fleet.probability < 0.0 {
+                return Err(ErrorReport::new(
+                    ErrorCode::FriendlyFleetInvalid,
+                    format!(
+                        "Friendly fleet candidate {} has an invalid probability: {}",
+                        i, fleet.probability
+                    ),
+                ));
+            }
+        }
+        let total: f64 = self.candidates().map(|f| f.probability).sum();
+        if total > 1.0 + PROBABILITY_TOLERANCE {
+            return Err(ErrorReport::new(
+                ErrorCode::FriendlyFleetInvalid,
+                format!("Friendly fleet probabilities sum to more than 1: {}", total),
+            ));
+        }
+        Ok(())
+    }
+}
+
+impl FriendlyFleet {
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    pub fn probability(&self) -> f64 {
+        self.probability
+    }
+
+    pub fn strong(&self) -> bool {
+        self.strong
+    }
+
+    /// 戦闘に使う艦隊に変換する。陣形は単縦陣とする。
+    pub fn to_fleet(&self) -> Fleet {
+        Fleet::new(self.ships.clone(), Some(Formation::LineAhead))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(probabilities: &[(f64, bool)], request_strong: bool) -> FriendlyFleetTable {
+        let ship = serde_json::to_value(Ship::test_builder().build()).unwrap();
+        let fleets = probabilities
+            .iter()
+            .map(|(p, strong)| serde_json::json!({ "ships": [ship], "probability": p, "strong": strong }))
+            .collect::<Vec<_>>();
+        serde_json::from_value(
+            serde_json::json!({ "fleets": fleets, "requestStrong": request_strong }),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn choose_uses_absolute_probabilities() {
+        let table = table(&[(0.2, false), (0.3, false)], false);
+        assert_eq!(table.choose(0.1).map(|(i, _)| i), Some(0));
+        assert_eq!(table.choose(0.2).map(|(i, _)| i), Some(1));
+        assert_eq!(table.choose(0.49).map(|(i, _)| i), Some(1));
+        // 残りの 0.5 は出現しない
+        assert!(table.choose(0.5).is_none());
+        assert!(table.choose(0.99).is_none());
+    }
+
+    #[test]
+    fn strong_candidates_require_request() {
+        let unrequested = table(&[(0.4, true), (0.4, false)], false);
+        assert_eq!(unrequested.choose(0.1).map(|(i, _)| i), Some(1));
+        assert!(unrequested.choose(0.5).is_none());
+
+        let requested = table(&[(0.4, true), (0.4, false)], true);
+        assert_eq!(requested.choose(0.1).map(|(i, _)| i), Some(0));
+        assert_eq!(requested.choose(0.5).map(|(i, _)| i), Some(1));
+    }
+
+    #[test]
+    fn validate_rejects_probability_sum_above_one() {
+        assert!(table(&[(0.6, false), (0.4, false)], false)
+            .validate()
+            .is_ok());
+        assert!(table(&[(0.6, false), (0.6, false)], false)
+            .validate()
+            .is_err());
+        // 要請しない強友軍は合計に含めない
+        assert!(table(&[(0.6, true), (0.6, false)], false)
+            .validate()
+            .is_ok());
+        assert!(table(&[(0.6, true), (0.6, false)], true)
+            .validate()
+            .is_err());
+        assert!(table(&[(-0.1, false)], false).validate().is_err());
+    }
+}
@@ -0,0 +1,260 @@
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::fleet::equip_category::EquipCategory;
+use crate::fleet::equipment::Equipment;
+
+/// 1つの基地航空隊に配備できる中隊の数。
+const MAX_SQUADRONS: usize = 4;
+/// 1回の出撃で指定できる攻撃目標 (マス) の数。
+const MAX_TARGETS: usize = 2;
+/// 中隊の最大搭載数。
+const MAX_SLOT: u16 = 18;
+/// 熟練度の最大値 (>>)。
+const MAX_PROFICIENCY: u8 = 7;
+/// 熟練度ごとの内部熟練度の下限。
+const INTERNAL_PROFICIENCY: [u8; 8] = [0, 10, 25, 40, 55, 70, 85, 100];
+/// 戦闘機系の熟練度ごとの制空値ボーナス。
+const FIGHTER_PROFICIENCY_BONUS: [u8; 8] = [0, 0, 2, 5, 9, 14, 14, 22];
+/// 水上爆撃機の熟練度ごとの制空値ボーナス。
+const SEAPLANE_BOMBER_PROFICIENCY_BONUS: [u8; 8] = [0, 0, 1, 1, 1, 3, 3, 6];
+
+/// 基地航空隊を受け取る構造体。
+/// 子に配備された中隊のリスト、行動、出撃時の攻撃目標を持つ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct LandBase {
+    squadrons: Vec<Squadron>,
+    #[serde(default)]
+    action: LandBaseAction,
+    /// 出撃時に攻撃するマス名。2つ指定した場合は2波に分けて攻撃する。
+    /// 同じマスを2つ指定すると、そのマスを2回攻撃する (集中運用)。
+    #[serde(default)]
+    targets: Vec<String>,
+}
+
+/// 基地航空隊に配備された1中隊。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Squadron {
+    plane: Equipment,
+    /// 搭載数
+    slot: u16,
+    /// 熟練度 (0 から 7)
+    #[serde(default)]
+    proficiency: u8,
+}
+
+/// 基地航空隊の行動を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LandBaseAction {
+    Sortie,
+    AirDefense,
+    #[default]
+    Standby,
+    Retreat,
+    Rest,
+}
+
+impl LandBase {
+    pub fn squadrons(&self) -> &[Squadron] {
+        &self.squadrons
+    }
+
+    pub fn action(&self) -> &LandBaseAction {
+        &self.action
+    }
+
+    pub fn targets(&self) -> &[String] {
+        &self.targets
+    }
+
+    /// `node` のマスで行う攻撃の回数 (波数) を取得する。出撃していない場合は 0 を返す。
+    pub fn waves_at(&self, node: &str) -> usize {
+        if self.action != LandBaseAction::Sortie {
+            return 0;
+        }
+        self.targets
+            .iter()
+            .take(MAX_TARGETS)
+            .filter(|target| *target == node)
+            .count()
+    }
+
+    /// 出撃する中隊と、その出撃時の搭載数を列挙する。検証で修正される上限はここでも適用する。
+    pub fn sortie_squadrons(&self) -> impl Iterator<Item = (&Squadron, u16)> {
+        self.squadrons
+            .iter()
+            .take(MAX_SQUADRONS)
+            .map(|s| (s, s.slot.min(MAX_SLOT)))
+    }
+
+    /// 基地航空隊の戦闘行動半径を取得する。配備された中隊のうち最も短いものになる。
+    /// 偵察機による行動半径の延長は考慮しない。
+    pub fn radius(&self) -> u16 {
+        self.squadrons
+            .iter()
+            .map(|s| s.plane.aircraft_range())
+            .min()
+            .unwrap_or(0)
+    }
+
+    /// フロントエンドから受けとったデータの妥当性を検証し、必要に応じて修正する。
+    /// 修正可能な例外
+    /// - 中隊や攻撃目標が上限を超えている
+    /// - 搭載数や熟練度が上限を超えている
+    /// - 出撃に設定されているが攻撃目標がない (待機に変更する)
+    ///
+    /// 修正不能な例外 (エラー情報を返す)
+    /// - 中隊が配備されていない
+    pub fn validate(&mut self) -> Result<(), ErrorReport> {
+        if self.squadrons.is_empty() {
+            return Err(ErrorReport::new(
+                ErrorCode::LandBaseEmpty,
+                "Land base has no squadrons".to_string(),
+            ));
+        }
+        if self.squadrons.len() > MAX_SQUADRONS {
+            warn!("Land base has too many squadrons: {}", self.squadrons.len());
+            self.squadrons.truncate(MAX_SQUADRONS);
+        }
+        for squadron in self.squadrons.iter_mut() {
+            if squadron.slot > MAX_SLOT {
+                warn!("Squadron slot is out of range: {}", squadron.slot);
+                squadron.slot = MAX_SLOT;
+            }
+            if squadron.proficiency > MAX_PROFICIENCY {
+                warn!(
+                    "Squadron proficiency is out of range: {}",
+                    squadron.proficiency
+                );
+                squadron.proficiency = MAX_PROFICIENCY;
+            }
+        }
+        if self.targets.len() > MAX_TARGETS {
+            warn!("Land base has too many targets: {:?}", self.targets);
+            self.targets.truncate(MAX_TARGETS);
+        }
+        if self.action == LandBaseAction::Sortie && self.targets.is_empty() {
+            warn!("Land base is set to sortie without targets: {:?}", self);
+            self.action = LandBaseAction::Standby;
+        }
+        Ok(())
+    }
+}
+
+impl Squadron {
+    pub fn plane(&self) -> &Equipment {
+        &self.plane
+    }
+
+    pub fn slot(&self) -> u16 {
+        self.slot
+    }
+
+    pub fn proficiency(&self) -> u8 {
+        self.proficiency
+    }
+
+    /// 配備された機体の戦闘行動半径を取得する。
+    pub fn radius(&self) -> u16 {
+        self.plane.aircraft_range()
+    }
+
+    /// 搭載数が `count` のときの中隊の制空値を計算する。
+    /// `floor(対空 × √搭載数 + √(内部熟練度 / 10) + 種別ボーナス)` で、制空値に寄与しない機体は 0 とする。
+    /// 局地戦闘機の迎撃・対爆は入力にないため考慮しない。
+    pub fn fighter_power(&self, count: u16) -> u32 {
+        let category = self.plane.category();
+        let counts =
+            category.counts_for_air_power() || category == EquipCategory::LandBasedAttackAircraft;
+        if !counts || count == 0 {
+            return 0;
+        }
+        let level = self.proficiency.min(MAX_PROFICIENCY) as usize;
+        let type_bonus = match category {
+            EquipCategory::CarrierBasedFighter
+            | EquipCategory::SeaplaneFighter
+            | EquipCategory::InterceptorFighter
+            | EquipCategory::JetFighter => FIGHTER_PROFICIENCY_BONUS[level],
+            EquipCategory::SeaplaneBomber => SEAPLANE_BOMBER_PROFICIENCY_BONUS[level],
+            _ => 0,
+        };
+        let bonus = (INTERNAL_PROFICIENCY[level] as f64 / 10.0).sqrt() + type_bonus as f64;
+        (self.plane.anti_aircraft() as f64 * (count as f64).sqrt() + bonus).floor() as u32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn squadron(type_id: u16, anti_aircraft: u16, slot: u16, proficiency: u8) -> Squadron {
+        serde_json::from_value(serde_json::json!({
+            "plane": {
+                "id": 1, "equipTypeId": [0, 0, type_id, 0, 0],
+                "status": { "antiAircraft": anti_aircraft }
+            },
+            "slot": slot,
+            "proficiency": proficiency
+        }))
+        .unwrap()
+    }
+
+    fn land_base(action: &str, targets: &[&str]) -> LandBase {
+        serde_json::from_value(serde_json::json!({
+            "squadrons": [
+                { "plane": { "id": 1, "equipTypeId": [0, 0, 47, 0, 0] }, "slot": 18 },
+                { "plane": { "id": 2, "equipTypeId": [0, 0, 6, 0, 0] }, "slot": 30 }
+            ],
+            "action": action,
+            "targets": targets
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn fighter_power() {
+        // floor(10 × √18 + √(100 / 10) + 22) = floor(67.59)
+        assert_eq!(squadron(6, 10, 18, 7).fighter_power(18), 67);
+        assert_eq!(squadron(6, 10, 18, 0).fighter_power(18), 42);
+        // 水上爆撃機は専用の熟練度ボーナス: floor(3 × √18 + √10 + 6) = floor(21.89)
+        assert_eq!(squadron(11, 3, 18, 7).fighter_power(18), 21);
+        // 陸上攻撃機は熟練度の種別ボーナスを持たない
+        assert_eq!(squadron(47, 2, 18, 7).fighter_power(18), 11);
+        // 搭載数 0 や制空値に寄与しない機体は 0
+        assert_eq!(squadron(6, 10, 18, 7).fighter_power(0), 0);
+        assert_eq!(squadron(9, 10, 4, 7).fighter_power(4), 0);
+    }
+
+    #[test]
+    fn waves_at_node() {
+        let split = land_base("sortie", &["A", "B"]);
+        assert_eq!(split.waves_at("A"), 1);
+        assert_eq!(split.waves_at("B"), 1);
+        assert_eq!(split.waves_at("C"), 0);
+
+        // 集中運用では同じマスを2回攻撃する
+        assert_eq!(land_base("sortie", &["A", "A"]).waves_at("A"), 2);
+        // 上限を超えた攻撃目標は数えない
+        assert_eq!(land_base("sortie", &["B", "A", "A"]).waves_at("A"), 1);
+        // 出撃していない基地は攻撃しない
+        assert_eq!(land_base("air_defense", &["A", "A"]).waves_at("A"), 0);
+    }
+
+    #[test]
+    fn validate_corrects_sortie() {
+        let mut base = land_base("sortie", &[]);
+        base.validate().unwrap();
+        assert_eq!(base.action(), &LandBaseAction::Standby);
+        assert_eq!(base.waves_at("A"), 0);
+
+        // 搭載数は出撃時に上限で打ち切る
+        let slots: Vec<u16> = land_base("sortie", &["A"])
+            .sortie_squadrons()
+            .map(|(_, count)| count)
+            .collect();
+        assert_eq!(slots, vec![18, 18]);
+    }
+}
@@ -1,13 +1,29 @@
-use log::warn;
-use serde::{Deserialize, Serialize};
-
 mod fleet_like;
 pub use fleet_like::{EnemyFleet, Fleet, FleetLike, Formation};
 
+mod combined_fleet;
+pub use combined_fleet::{CombinedFleet, CombinedFleetType};
+
 mod ship;
 pub use ship::Ship;
 
+mod friendly_fleet;
+pub use friendly_fleet::{FriendlyFleet, FriendlyFleetTable};
+
+mod support_fleet;
+pub use support_fleet::SupportFleet;
+
 mod status;
 pub use status::Range;
 
 mod equipment;
+pub use equipment::Equipment;
+
+mod land_base;
+pub use land_base::{LandBase, LandBaseAction, Squadron};
+
+mod equip_category;
+pub use equip_category::EquipCategory;
+
+mod abyssal_class;
+pub use abyssal_class::AbyssalClass;
@@ -1,9 +1,45 @@
 use serde::{Deserialize, Serialize};
 
-use crate::battle::{BattleDirection, DamagedLevel, Phase, ShipSnapshot};
+use crate::battle::{DamagedLevel, ShipSnapshot};
 
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::fleet::abyssal_class::AbyssalClass;
+use crate::fleet::equip_category::EquipCategory;
 use crate::fleet::equipment::Equipment;
 use crate::fleet::status::Range;
+use crate::formula::{SynergyBonus, SynergyRule};
+use crate::interface::{AppliedDefault, Locale};
+use crate::master::{MasterData, MasterShip, StatBonus};
+
+/// マスターデータのステータスの範囲からのずれとして許容する量。
+/// 装備の改修による上昇や、入力に反映済みの装備ボーナスを見込む。
+const STAT_TOLERANCE: u16 = 10;
+
+/// 通常のスロットとは別に装備できる補強増設の枠の数。
+const REINFORCEMENT_SLOTS: usize = 1;
+
+/// 秋月型駆逐艦の艦船ID (改装後を含む)。
+const AKIZUKI_CLASS_IDS: [u16; 8] = [
+    421, // 秋月
+    330, // 秋月改
+    422, // 照月
+    346, // 照月改
+    423, // 初月
+    357, // 初月改
+    537, // 涼月
+    538, // 涼月改
+];
+
+/// 大和型戦艦の艦船ID (改装後を含む)。
+const YAMATO_CLASS_IDS: [u16; 7] = [
+    131, // 大和
+    136, // 大和改
+    911, // 大和改二
+    916, // 大和改二重
+    143, // 武蔵
+    148, // 武蔵改
+    546, // 武蔵改二
+];
 
 /// 艦娘や深海棲艦の情報を表す不変の構造体。
 /// 子に艦船固有ID、名前、艦種ID、艦種名、ステータス、装備のリストを持つ。
@@ -18,11 +54,49 @@ pub struct Ship {
     name: String,
     ship_type_id: Option<u16>,
     ship_type_name: Option<String>,
+    /// 深海棲艦の階級。未指定の場合は ID と艦名から推定される。
+    #[serde(default)]
+    abyssal_class: Option<AbyssalClass>,
     status: ShipStatus,
     equips: Vec<Equipment>,
+    /// 装備の条件を満たすシナジー規則。入力には含まれず、計算式の定数の規則から導出される。
+    #[serde(skip)]
+    synergies: Vec<SynergyRule>,
 }
 
 impl Ship {
+    /// 戦闘記録などから得たステータスのみを持つ深海棲艦を作る。
+    /// `params` は `[火力, 雷装, 対空, 装甲]` で、装甲は `armor` で上書きする。
+    pub fn abyssal(id: u16, name: String, max_hp: u16, params: [u16; 4], armor: u16) -> Self {
+        Self {
+            id,
+            name,
+            ship_type_id: None,
+            ship_type_name: None,
+            abyssal_class: None,
+            status: ShipStatus {
+                max_hp,
+                now_hp: max_hp,
+                firepower: params[0],
+                armor,
+                torpedo: params[1],
+                anti_aircraft: params[2],
+                condition: 49,
+                evasion: None,
+                airplane_slots: None,
+                anti_submarine_warfare: None,
+                speed: None,
+                scouting: None,
+                range: None,
+                luck: None,
+                fuel: None,
+                ammo: None,
+            },
+            equips: Vec::new(),
+            synergies: Vec::new(),
+        }
+    }
+
     // status getters
     /// 艦の全回復時HPを取得する。
     pub fn max_hp(&self) -> u16 {
@@ -34,6 +108,31 @@ impl Ship {
         self.status.now_hp
     }
 
+    /// 戦意 (コンディション値) を取得する。
+    pub fn condition(&self) -> u16 {
+        self.status.condition
+    }
+
+    /// 残り燃料の割合を取得する。未設定の場合は1.0を返す。
+    pub fn fuel(&self) -> f64 {
+        self.status.fuel.unwrap_or(1.0)
+    }
+
+    /// 残り弾薬の割合を取得する。未設定の場合は1.0を返す。
+    pub fn ammo(&self) -> f64 {
+        self.status.ammo.unwrap_or(1.0)
+    }
+
+    /// 装備のリストを取得する。
+    pub fn equips(&self) -> &[Equipment] {
+        &self.equips
+    }
+
+    /// 各スロットの搭載機数を取得する。未設定の場合は空のスライスを返す。
+    pub fn airplane_slots(&self) -> &[u16] {
+        self.status.airplane_slots.as_deref().unwrap_or(&[])
+    }
+
     /// 火力ステータスを取得する。
     /// この値には装備の火力が加算されているが、艦娘固有の装備ボーナスや改修ボーナスは含まれない。
     /// 以下のゲッターも同様。
@@ -41,6 +140,12 @@ impl Ship {
         self.status.firepower
     }
 
+    /// 装備を除いた素の火力ステータスを取得する。
+    pub fn naked_firepower(&self) -> u16 {
+        let equipment: u16 = self.equips.iter().map(|e| e.firepower()).sum();
+        self.firepower().saturating_sub(equipment)
+    }
+
     /// 装甲ステータスを取得する。
     pub fn armor(&self) -> u16 {
         self.status.armor
@@ -51,6 +156,58 @@ impl Ship {
         self.status.torpedo
     }
 
+    /// 対空ステータスを取得する。
+    pub fn anti_aircraft(&self) -> u16 {
+        self.status.anti_aircraft
+    }
+
+    /// 装備を除いた素の対空ステータスを取得する。
+    pub fn naked_anti_aircraft(&self) -> u16 {
+        let equipment: u16 = self.equips.iter().map(|e| e.anti_aircraft()).sum();
+        self.anti_aircraft().saturating_sub(equipment)
+    }
+
+    /// 対潜ステータスを取得する。未設定の場合は0を返す。
+    pub fn anti_submarine_warfare(&self) -> u16 {
+        self.status.anti_submarine_warfare.unwrap_or(0)
+    }
+
+    /// 装備による対潜ステータスの合計を取得する。
+    pub fn equipment_anti_submarine_warfare(&self) -> u16 {
+        self.equips.iter().map(|e| e.anti_submarine_warfare()).sum()
+    }
+
+    /// 装備を除いた素の対潜ステータスを取得する。
+    pub fn naked_anti_submarine_warfare(&self) -> u16 {
+        self.anti_submarine_warfare()
+            .saturating_sub(self.equipment_anti_submarine_warfare())
+    }
+
+    /// 索敵ステータスを取得する。未設定の場合は0を返す。
+    pub fn scouting(&self) -> u16 {
+        self.status.scouting.unwrap_or(0)
+    }
+
+    /// 回避ステータスを取得する。未設定の場合は0を返す。
+    pub fn evasion(&self) -> u16 {
+        self.status.evasion.unwrap_or(0)
+    }
+
+    /// 運ステータスを取得する。未設定の場合は0を返す。
+    pub fn luck(&self) -> u16 {
+        self.status.luck.unwrap_or(0)
+    }
+
+    /// 装備による命中補正の合計を取得する。
+    pub fn equipment_aiming(&self) -> u16 {
+        self.equips.iter().map(|e| e.aiming()).sum()
+    }
+
+    /// 装備ごとの改修ボーナス `bonus` (`Equipment::firepower_improvement_bonus` など) の合計を取得する。
+    pub fn improvement_bonus(&self, bonus: impl Fn(&Equipment) -> f64) -> f64 {
+        self.equips.iter().map(bonus).sum()
+    }
+
     /// 爆装ステータスを取得する。
     pub fn bombing(&self) -> u16 {
         self.equips.iter().map(|e| e.bombing()).sum()
@@ -69,21 +226,93 @@ impl Ship {
     }
 
     // attributes getters
+    /// 艦船固有IDを取得する。
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
     /// 艦名 (日本語) を取得する。
     pub fn name(&self) -> String {
         self.name.clone()
     }
+    /// 艦名を指定された言語の表記に置き換える。
+    /// マスターデータに該当する艦がない場合は、ID表記を除いて元の艦名を維持する。
+    pub fn localize_name(&mut self, master: Option<&MasterData>, locale: &Locale) {
+        let name = match locale {
+            Locale::Ja => None,
+            Locale::Id => Some(format!("#{}", self.id)),
+            Locale::En => master.and_then(|m| m.ship_name(self.id, locale)),
+        };
+        if let Some(name) = name {
+            // 階級は艦名から推定するため、改名前に確定させておく
+            self.abyssal_class = self.abyssal_class();
+            self.name = name;
+        }
+    }
     /// 艦種IDを取得する。未設定の場合は0を返す。
     pub fn ship_type_id(&self) -> u16 {
         self.ship_type_id.unwrap_or(0)
     }
 
+    /// 深海棲艦かどうかを判定する。
+    pub fn is_abyssal(&self) -> bool {
+        self.abyssal_class().is_some()
+    }
+
+    /// 深海棲艦の階級を取得する。艦娘の場合は `None` を返す。
+    /// 入力で明示されていればその値を、そうでなければ ID と艦名から推定した値を返す。
+    pub fn abyssal_class(&self) -> Option<AbyssalClass> {
+        self.abyssal_class
+            .clone()
+            .or_else(|| AbyssalClass::infer(self.id, &self.name))
+    }
+
     /// 戦艦系 (低速戦艦、高速戦艦、航空戦艦、超弩級戦艦) かどうかを判定する。
     pub fn is_battleship_class(&self) -> bool {
         let id = self.ship_type_id();
         matches!(id, 8 | 9 | 10 | 12)
     }
 
+    /// 秋月型駆逐艦かどうかを判定する。
+    pub fn is_akizuki_class(&self) -> bool {
+        !self.is_abyssal() && AKIZUKI_CLASS_IDS.contains(&self.id)
+    }
+
+    /// 大和型戦艦かどうかを判定する。
+    pub fn is_yamato_class(&self) -> bool {
+        !self.is_abyssal() && YAMATO_CLASS_IDS.contains(&self.id)
+    }
+
+    /// 軽巡系 (軽巡洋艦、重雷装巡洋艦、練習巡洋艦) かどうかを判定する。
+    pub fn is_light_cruiser_class(&self) -> bool {
+        let id = self.ship_type_id();
+        matches!(id, 3 | 4 | 21)
+    }
+
+    /// 重巡系 (重巡洋艦、航空巡洋艦) かどうかを判定する。
+    pub fn is_heavy_cruiser_class(&self) -> bool {
+        let id = self.ship_type_id();
+        matches!(id, 5 | 6)
+    }
+
+    /// 潜水艦系 (潜水艦、潜水空母) かどうかを判定する。
+    pub fn is_submarine(&self) -> bool {
+        let id = self.ship_type_id();
+        matches!(id, 13 | 14)
+    }
+
+    /// 陸上型 (砲台・飛行場姫など) かどうかを判定する。
+    /// 速力が 0 (陸上) の深海棲艦を陸上型とみなす。速力が未設定の場合は false を返す。
+    pub fn is_installation(&self) -> bool {
+        self.is_abyssal() && self.status.speed == Some(0)
+    }
+
+    /// 空母系 (軽空母、正規空母、装甲空母) かどうかを判定する。
+    pub fn is_carrier_class(&self) -> bool {
+        let id = self.ship_type_id();
+        matches!(id, 7 | 11 | 18)
+    }
+
     /// 攻撃可能な航空機を装備しているかどうかを判定する。
     /// 空母系の艦種であっても、攻撃可能な航空機を装備していなければ false を返す。
     /// 逆に、速吸改のような非空母系艦種であっても、攻撃可能な航空機を装備していれば true を返す。
@@ -91,26 +320,310 @@ impl Ship {
         self.equips.iter().any(|e| e.is_attack_aircraft())
     }
 
-    pub fn damaged_level(&self, snapshot: &ShipSnapshot) -> crate::battle::DamagedLevel {
-        let max_hp = self.max_hp();
-        let now_hp = snapshot.hp();
-        let ratio = now_hp as f64 / max_hp as f64;
-        if now_hp == 0 {
-            crate::battle::DamagedLevel::Sunk
-        } else if ratio <= 0.25 {
-            crate::battle::DamagedLevel::Heavy
-        } else if ratio <= 0.5 {
-            crate::battle::DamagedLevel::Moderate
-        } else if ratio <= 0.75 {
-            crate::battle::DamagedLevel::Minor
-        } else {
-            crate::battle::DamagedLevel::NoDamage
+    /// 陸上型を攻撃できる攻撃機を装備しているかどうかを判定する。
+    pub fn has_installation_attack_aircraft(&self) -> bool {
+        self.equips
+            .iter()
+            .any(|e| e.is_attack_aircraft() && e.can_attack_installation())
+    }
+
+    /// 陸上型を攻撃できる装備のみを対象とした爆装の合計を取得する。
+    pub fn installation_bombing(&self) -> u16 {
+        self.equips
+            .iter()
+            .filter(|e| e.can_attack_installation())
+            .map(|e| e.bombing())
+            .sum()
+    }
+
+    /// 弾着観測射撃の発動条件となる水上機 (水上偵察機・水上爆撃機) を、搭載機が残っている状態で装備しているかどうかを判定する。
+    pub fn has_spotting_plane(&self, snapshot: &ShipSnapshot) -> bool {
+        self.equips
+            .iter()
+            .zip(snapshot.slots())
+            .any(|(e, count)| e.category().enables_artillery_spotting() && *count > 0)
+    }
+
+    /// 搭載機が残っている夜間戦闘機・夜間攻撃機を装備しているかどうかを判定する。
+    pub fn has_night_aircraft(&self, snapshot: &ShipSnapshot) -> bool {
+        self.equips
+            .iter()
+            .zip(snapshot.slots())
+            .any(|(e, count)| e.is_night_aircraft() && *count > 0)
+    }
+
+    /// 指定された種別に該当する装備の数を数える。
+    pub fn count_equips(&self, filter: impl Fn(&EquipCategory) -> bool) -> usize {
+        self.equips.iter().filter(|e| filter(&e.category())).count()
+    }
+
+    /// 先制雷撃を行える甲標的を装備しているかどうかを判定する。
+    pub fn has_midget_submarine(&self) -> bool {
+        self.equips
+            .iter()
+            .any(|e| e.category() == EquipCategory::MidgetSubmarine)
+    }
+
+    /// 照明弾を装備しているかどうかを判定する。
+    pub fn has_star_shell(&self) -> bool {
+        self.equips
+            .iter()
+            .any(|e| e.category() == EquipCategory::StarShell)
+    }
+
+    /// 探照灯・大型探照灯を装備しているかどうかを判定する。
+    pub fn has_searchlight(&self) -> bool {
+        self.equips.iter().any(|e| e.category().is_searchlight())
+    }
+
+    /// 指定された種別に該当し、対潜値を持つ航空機を装備しているかどうかを判定する。
+    pub fn has_asw_aircraft(&self, filter: impl Fn(&EquipCategory) -> bool) -> bool {
+        self.equips
+            .iter()
+            .any(|e| filter(&e.category()) && e.anti_submarine_warfare() > 0)
+    }
+
+    /// 航空攻撃に参加する航空機 (水上爆撃機を含む) を装備しているかどうかを判定する。
+    pub fn has_airstrike_aircraft(&self) -> bool {
+        self.equips
+            .iter()
+            .any(|e| e.category().participates_in_airstrike())
+    }
+
+    /// 陸上型に対する航空攻撃に参加できる航空機を装備しているかどうかを判定する。
+    /// 対地攻撃できない艦上爆撃機は除外される。
+    pub fn has_installation_airstrike_aircraft(&self) -> bool {
+        self.equips
+            .iter()
+            .any(|e| e.category().participates_in_airstrike() && e.can_attack_installation())
+    }
+
+    /// 艦の制空値を計算する。
+    /// 各スロットについて `floor(対空 × √搭載数)` を合計する。熟練度ボーナスは含まない。
+    /// 搭載数が未設定の場合は0とみなす。
+    pub fn fighter_power(&self) -> u32 {
+        self.fighter_power_with_slots(self.airplane_slots())
+    }
+
+    /// 各スロットの搭載数を `slots` として艦の制空値を計算する。撃墜された後の制空値の計算に使う。
+    pub fn fighter_power_with_slots(&self, slots: &[u16]) -> u32 {
+        self.equips
+            .iter()
+            .zip(slots.iter())
+            .filter(|(e, _)| e.category().counts_for_air_power())
+            .map(|(e, slot)| (e.anti_aircraft() as f64 * (*slot as f64).sqrt()).floor() as u32)
+            .sum()
+    }
+
+    pub fn damaged_level(&self, snapshot: &ShipSnapshot) -> DamagedLevel {
+        snapshot.damaged_level().clone()
+    }
+
+    /// 装備ボーナス表に基づき、可視の装備ボーナスをステータスに加算する。
+    /// 入力されたステータスには装備ボーナスが含まれていないことを前提とする。
+    pub fn apply_equipment_bonus(&mut self, master: &MasterData) {
+        let equipment_ids = self.equips.iter().map(|e| e.id()).collect::<Vec<_>>();
+        let bonus = master.equipment_bonus(self.id, self.ship_type_id(), &equipment_ids);
+        self.add_stats(&bonus);
+    }
+
+    /// シナジー規則 `rules` のうち、装備の条件を満たすものを並び順のまま保持する。
+    pub fn apply_synergies(&mut self, rules: &[SynergyRule]) {
+        self.synergies = rules
+            .iter()
+            .filter(|rule| rule.matches(&self.equips))
+            .cloned()
+            .collect();
+    }
+
+    /// `target` を攻撃する際の、装備のシナジーによる補正を取得する。`apply_synergies` の前は補正なし。
+    pub fn synergy(&self, target: &Ship) -> SynergyBonus {
+        SynergyBonus::of(&self.synergies, target)
+    }
+
+    /// 各ステータスに `bonus` を加算する。入力にないステータスは未設定のまま残す。
+    pub fn add_stats(&mut self, bonus: &StatBonus) {
+        if *bonus == StatBonus::default() {
+            return;
+        }
+        let add = |stat: u16, delta: i16| stat.saturating_add_signed(delta);
+        let status = &mut self.status;
+        status.firepower = add(status.firepower, bonus.firepower);
+        status.torpedo = add(status.torpedo, bonus.torpedo);
+        status.armor = add(status.armor, bonus.armor);
+        status.anti_aircraft = add(status.anti_aircraft, bonus.anti_aircraft);
+        status.evasion = status.evasion.map(|v| add(v, bonus.evasion));
+        status.anti_submarine_warfare = status
+            .anti_submarine_warfare
+            .map(|v| add(v, bonus.anti_submarine_warfare));
+        status.scouting = status.scouting.map(|v| add(v, bonus.scouting));
+    }
+
+    /// 入力されたステータスが、マスターデータの初期値から最大値に装備の値を加えた範囲に収まるか確認する。
+    /// 装備の改修や装備ボーナスの分を見込み、`STAT_TOLERANCE` だけ範囲を広げて判定する。
+    pub fn check_stats(&self, master: &MasterShip) -> Result<(), ErrorReport> {
+        let equipment = |stat: fn(&Equipment) -> u16| self.equips.iter().map(stat).sum::<u16>();
+        let ranges = master.stats();
+        let status = &self.status;
+        let checks = [
+            ("maxHp", Some(status.max_hp), ranges.hp, 0),
+            (
+                "firepower",
+                Some(status.firepower),
+                ranges.firepower,
+                equipment(Equipment::firepower),
+            ),
+            (
+                "torpedo",
+                Some(status.torpedo),
+                ranges.torpedo,
+                equipment(Equipment::torpedo),
+            ),
+            (
+                "antiAircraft",
+                Some(status.anti_aircraft),
+                ranges.anti_aircraft,
+                equipment(Equipment::anti_aircraft),
+            ),
+            (
+                "armor",
+                Some(status.armor),
+                ranges.armor,
+                equipment(Equipment::armor),
+            ),
+            ("luck", status.luck, ranges.luck, 0),
+        ];
+        let implausible = checks
+            .iter()
+            .filter_map(|(name, value, range, equipment)| {
+                let (value, [base, max]) = (value.as_ref()?, range.as_ref()?);
+                let min = base.saturating_sub(STAT_TOLERANCE);
+                let max = max + equipment + STAT_TOLERANCE;
+                (!(min..=max).contains(value))
+                    .then(|| format!("{} {} (expected {}..={})", name, value, min, max))
+            })
+            .collect::<Vec<_>>();
+        if implausible.is_empty() {
+            return Ok(());
+        }
+        Err(ErrorReport::new(
+            ErrorCode::ShipStatImplausible,
+            format!(
+                "Ship stats are inconsistent with master data: {} (#{}): {}",
+                self.name,
+                self.id,
+                implausible.join(", ")
+            ),
+        ))
+    }
+
+    /// 装備の数が、スロット数に補強増設の枠を加えた数を超えていないか確認する。
+    /// スロットごとの搭載数 (`airplaneSlots`) が未設定の場合はスロット数が分からないため確認しない。
+    pub fn check_equipment_slots(&self) -> Result<(), ErrorReport> {
+        let slots = match &self.status.airplane_slots {
+            Some(slots) => slots.len() + REINFORCEMENT_SLOTS,
+            None => return Ok(()),
+        };
+        if self.equips.len() <= slots {
+            return Ok(());
+        }
+        Err(ErrorReport::new(
+            ErrorCode::EquipmentExceedsSlots,
+            format!(
+                "Ship has more equipment than slots: {} (#{}): {} > {}",
+                self.name,
+                self.id,
+                self.equips.len(),
+                slots
+            ),
+        ))
+    }
+
+    /// 装備の改修値 (★) が上限を超えていないか確認する。
+    pub fn check_improvements(&self) -> Result<(), ErrorReport> {
+        let invalid = self
+            .equips
+            .iter()
+            .enumerate()
+            .filter_map(|(i, e)| {
+                let improvement = e.improvement_out_of_range()?;
+                Some(format!("equips[{}] +{}", i, improvement))
+            })
+            .collect::<Vec<_>>();
+        if invalid.is_empty() {
+            return Ok(());
         }
+        Err(ErrorReport::new(
+            ErrorCode::EquipmentImprovementOutOfRange,
+            format!(
+                "Equipment improvement exceeds +10: {} (#{}): {}",
+                self.name,
+                self.id,
+                invalid.join(", ")
+            ),
+        ))
+    }
+
+    /// 入力になかったためにゲッターが既定値を補うステータスを列挙する。
+    /// `path` はこの艦の入力上の位置で、各項目の位置はその下に続けて表す。
+    pub fn applied_defaults(&self, path: &str) -> Vec<AppliedDefault> {
+        let status = &self.status;
+        let mut defaults = Vec::new();
+        if status.range.is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.status.range", path),
+                self.range(),
+            ));
+        }
+        if status.evasion.is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.status.evasion", path),
+                self.evasion(),
+            ));
+        }
+        if status.luck.is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.status.luck", path),
+                self.luck(),
+            ));
+        }
+        if status.scouting.is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.status.scouting", path),
+                self.scouting(),
+            ));
+        }
+        if status.anti_submarine_warfare.is_none() {
+            defaults.push(AppliedDefault::new(
+                format!("{}.status.antiSubmarineWarfare", path),
+                self.anti_submarine_warfare(),
+            ));
+        }
+        defaults.extend(
+            self.equips
+                .iter()
+                .enumerate()
+                .filter(|(_, equipment)| !equipment.has_status())
+                .map(|(i, _)| {
+                    AppliedDefault::new(
+                        format!("{}.equips[{}].status", path, i),
+                        serde_json::Map::new(),
+                    )
+                }),
+        );
+        defaults
     }
 
     /// ShipSnapshot の情報を適用し、艦船の状態を更新する。
+    /// 戦闘後の状態を次の戦闘に持ち越せるよう、HP以外の変化する値も反映する。
     pub fn apply_snapshot(&mut self, snapshot: &ShipSnapshot) {
         self.status.now_hp = snapshot.hp();
+        self.status.condition = snapshot.morale();
+        self.status.fuel = Some(snapshot.fuel());
+        self.status.ammo = Some(snapshot.ammo());
+        if self.status.airplane_slots.is_some() {
+            self.status.airplane_slots = Some(snapshot.slots().to_vec());
+        }
     }
 }
 
@@ -134,4 +647,79 @@ struct ShipStatus {
     pub scouting: Option<u16>,
     pub range: Option<Range>,
     pub luck: Option<u16>,
+    /// 残り燃料の割合 (0.0〜1.0)。未設定の場合は満タンとみなす。
+    pub fuel: Option<f64>,
+    /// 残り弾薬の割合 (0.0〜1.0)。未設定の場合は満タンとみなす。
+    pub ammo: Option<f64>,
+}
+
+#[cfg(test)]
+impl Ship {
+    /// テスト用の艦を組み立てる。指定しないステータスは駆逐艦相当の既定値とする。
+    pub fn test_builder() -> ShipBuilder {
+        let ship = serde_json::from_value(serde_json::json!({
+            "id": 1, "name": "test", "shipTypeId": 2,
+            "status": {
+                "maxHp": 30, "nowHp": 30, "firepower": 10, "armor": 10,
+                "torpedo": 0, "antiAircraft": 30, "condition": 49
+            },
+            "equips": []
+        }))
+        .unwrap();
+        ShipBuilder { ship }
+    }
+}
+
+/// テスト用の艦の組み立て。`Ship::test_builder` から作る。
+#[cfg(test)]
+pub struct ShipBuilder {
+    ship: Ship,
+}
+
+#[cfg(test)]
+impl ShipBuilder {
+    pub fn id(mut self, id: u16) -> Self {
+        self.ship.id = id;
+        self
+    }
+
+    pub fn ship_type(mut self, ship_type_id: u16) -> Self {
+        self.ship.ship_type_id = Some(ship_type_id);
+        self
+    }
+
+    /// 最大HPと現在HPを設定する。
+    pub fn hp(mut self, max_hp: u16, now_hp: u16) -> Self {
+        self.ship.status.max_hp = max_hp;
+        self.ship.status.now_hp = now_hp;
+        self
+    }
+
+    pub fn condition(mut self, condition: u16) -> Self {
+        self.ship.status.condition = condition;
+        self
+    }
+
+    pub fn luck(mut self, luck: u16) -> Self {
+        self.ship.status.luck = Some(luck);
+        self
+    }
+
+    pub fn airplane_slots(mut self, slots: &[u16]) -> Self {
+        self.ship.status.airplane_slots = Some(slots.to_vec());
+        self
+    }
+
+    /// 装備を入力と同じ形式の JSON で設定する。
+    pub fn equips(mut self, equips: impl IntoIterator<Item = serde_json::Value>) -> Self {
+        self.ship.equips = equips
+            .into_iter()
+            .map(|e| serde_json::from_value(e).unwrap())
+            .collect();
+        self
+    }
+
+    pub fn build(self) -> Ship {
+        self.ship
+    }
 }
@@ -0,0 +1,65 @@
+use serde::{Deserialize, Serialize};
+
+use crate::diagnostics::{ErrorCode, ErrorReport};
+use crate::fleet::ship::Ship;
+
+/// 1艦隊に編成できる艦の数。
+const MAX_SHIPS: usize = 6;
+/// 支援艦隊に必要な駆逐艦の数。
+const REQUIRED_DESTROYERS: usize = 2;
+/// 駆逐艦の艦種ID。
+const DESTROYER: u16 = 2;
+
+/// 支援艦隊 (前衛支援・決戦支援) を受け取る構造体。
+/// 子に艦娘のリストと、どのマスで支援を行うかのフラグを持つ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SupportFleet {
+    ships: Vec<Ship>,
+    /// `true` の場合は決戦支援としてボスマスでのみ、`false` の場合は前衛支援として道中のマスでのみ支援を行う。
+    #[serde(default)]
+    boss: bool,
+}
+
+impl SupportFleet {
+    pub fn ships(&self) -> &[Ship] {
+        &self.ships
+    }
+
+    /// 指定されたマスで支援を行うかどうかを判定する。
+    pub fn fires_on(&self, boss_node: bool) -> bool {
+        self.boss == boss_node
+    }
+
+    /// フロントエンドから受けとったデータの妥当性を検証する。
+    /// 修正不能な例外 (エラー情報を返す)
+    /// - 艦隊が空、または 6 隻を超えている
+    /// - 駆逐艦が 2 隻未満 (支援が発動しない)
+    pub fn validate(&self) -> Result<(), ErrorReport> {
+        if self.ships.is_empty() || self.ships.len() > MAX_SHIPS {
+            return Err(ErrorReport::new(
+                ErrorCode::SupportFleetInvalid,
+                format!(
+                    "Support fleet must have 1 to {} ships, but has {}",
+                    MAX_SHIPS,
+                    self.ships.len()
+                ),
+            ));
+        }
+        let destroyers = self
+            .ships
+            .iter()
+            .filter(|s| s.ship_type_id() == DESTROYER)
+            .count();
+        if destroyers < REQUIRED_DESTROYERS {
+            return Err(ErrorReport::new(
+                ErrorCode::SupportFleetInvalid,
+                format!(
+                    "Support fleet must have at least {} destroyers, but has {}",
+                    REQUIRED_DESTROYERS, destroyers
+                ),
+            ));
+        }
+        Ok(())
+    }
+}
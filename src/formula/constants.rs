@@ -0,0 +1,248 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{BattleDirection, DamagedLevel, Scalar};
+use crate::fleet::Formation;
+use crate::formula::SynergyRule;
+
+/// 戦闘の計算式で使う定数をまとめた構造体。
+/// 実行時に JSON から読み込めるため、ゲームの仕様変更に wasm を再ビルドせずに追従できる。
+/// 省略された項目はコンパイル時の既定値で補完される。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormulaConstants {
+    /// 交戦形態ごとの攻撃力補正
+    pub direction_factors: DirectionFactors,
+    /// 陣形ごとの攻撃力・命中・回避の補正
+    pub formation_factors: FormationFactors,
+    /// 損傷状態ごとの攻撃力補正
+    pub damaged_level_factors: DamagedLevelFactors,
+    /// 昼戦砲撃のキャップ
+    pub day_artillery_cap: f64,
+    /// 夜戦のキャップ
+    pub night_cap: f64,
+    /// 対潜攻撃のキャップ
+    pub asw_cap: f64,
+    /// 雷撃戦のキャップ
+    pub torpedo_cap: f64,
+    /// 航空攻撃のキャップ
+    pub air_strike_cap: f64,
+    /// 基地航空隊の航空攻撃のキャップ
+    pub land_base_cap: f64,
+    /// 雷撃戦での損傷状態ごとの攻撃力補正
+    pub torpedo_damaged_level_factors: DamagedLevelFactors,
+    /// 轟沈ストッパー発動時の割合ダメージの係数
+    pub stopper: DamageCoefficients,
+    /// カスダメの割合ダメージの係数
+    pub scratch_damage: DamageCoefficients,
+    /// 装備の組み合わせによる補正 (シナジー) の規則。並び順に判定する
+    pub synergies: Vec<SynergyRule>,
+}
+
+impl Default for FormulaConstants {
+    fn default() -> Self {
+        Self {
+            direction_factors: DirectionFactors::default(),
+            formation_factors: FormationFactors::default(),
+            damaged_level_factors: DamagedLevelFactors::default(),
+            day_artillery_cap: 220.0,
+            night_cap: 360.0,
+            asw_cap: 170.0,
+            torpedo_cap: 180.0,
+            air_strike_cap: 170.0,
+            land_base_cap: 220.0,
+            torpedo_damaged_level_factors: DamagedLevelFactors {
+                no_damage: 1.0,
+                minor: 1.0,
+                moderate: 0.8,
+                heavy: 0.0,
+            },
+            stopper: DamageCoefficients {
+                base: 0.5,
+                random: 0.3,
+            },
+            scratch_damage: DamageCoefficients {
+                base: 0.06,
+                random: 0.08,
+            },
+            synergies: SynergyRule::defaults(),
+        }
+    }
+}
+
+impl FormulaConstants {
+    /// 交戦形態による攻撃力補正を取得する。
+    pub fn direction_factor(&self, direction: &BattleDirection) -> f64 {
+        let f = &self.direction_factors;
+        match direction {
+            BattleDirection::Same => f.same,
+            BattleDirection::Against => f.against,
+            BattleDirection::TAdvantage => f.t_advantage,
+            BattleDirection::TDisadvantage => f.t_disadvantage,
+        }
+    }
+
+    /// 陣形による補正を取得する。
+    /// 警戒陣では、艦隊内の位置 `ship_idx` が前半 (艦数 `fleet_len` の半分未満) か後半かで補正が異なる。
+    pub fn formation_factor(
+        &self,
+        formation: &Formation,
+        ship_idx: usize,
+        fleet_len: usize,
+    ) -> &FormationFactor {
+        let f = &self.formation_factors;
+        match formation {
+            Formation::LineAhead => &f.line_ahead,
+            Formation::DoubleLine => &f.double_line,
+            Formation::Diamond => &f.diamond,
+            Formation::Echelon => &f.echelon,
+            Formation::LineAbreast => &f.line_abreast,
+            Formation::Vanguard if ship_idx * 2 < fleet_len => &f.vanguard_front,
+            Formation::Vanguard => &f.vanguard_rear,
+        }
+    }
+
+    /// 損傷状態による攻撃力補正を取得する。撃沈された艦は攻撃しないため 0 とする。
+    pub fn damaged_level_factor(&self, damaged_level: &DamagedLevel) -> f64 {
+        Self::factor_for(&self.damaged_level_factors, damaged_level)
+    }
+
+    /// 雷撃戦での損傷状態による攻撃力補正を取得する。
+    pub fn torpedo_damaged_level_factor(&self, damaged_level: &DamagedLevel) -> f64 {
+        Self::factor_for(&self.torpedo_damaged_level_factors, damaged_level)
+    }
+
+    fn factor_for(f: &DamagedLevelFactors, damaged_level: &DamagedLevel) -> f64 {
+        match damaged_level {
+            DamagedLevel::NoDamage => f.no_damage,
+            DamagedLevel::Minor => f.minor,
+            DamagedLevel::Moderate => f.moderate,
+            DamagedLevel::Heavy => f.heavy,
+            DamagedLevel::Sunk => 0.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DirectionFactors {
+    pub same: f64,
+    pub against: f64,
+    pub t_advantage: f64,
+    pub t_disadvantage: f64,
+}
+
+impl Default for DirectionFactors {
+    fn default() -> Self {
+        Self {
+            same: 1.0,
+            against: 0.8,
+            t_advantage: 1.2,
+            t_disadvantage: 0.6,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormationFactors {
+    pub line_ahead: FormationFactor,
+    pub double_line: FormationFactor,
+    pub diamond: FormationFactor,
+    pub echelon: FormationFactor,
+    pub line_abreast: FormationFactor,
+    /// 警戒陣の前半の艦。砲撃の攻撃力が上がる代わりに回避が下がり、対潜攻撃は後半の艦に任せる。
+    pub vanguard_front: FormationFactor,
+    /// 警戒陣の後半の艦。砲撃の攻撃力が下がる代わりに回避が大きく上がり、対潜攻撃は下がらない。
+    pub vanguard_rear: FormationFactor,
+}
+
+impl Default for FormationFactors {
+    fn default() -> Self {
+        let factor =
+            |shelling, torpedo, anti_submarine, accuracy, evasion, anti_aircraft| FormationFactor {
+                shelling,
+                torpedo,
+                anti_submarine,
+                accuracy,
+                evasion,
+                anti_aircraft,
+            };
+        Self {
+            line_ahead: factor(1.0, 1.0, 0.45, 1.0, 1.0, 1.0),
+            double_line: factor(0.8, 0.8, 0.8, 1.2, 1.0, 1.2),
+            diamond: factor(0.5, 0.7, 1.2, 1.0, 1.1, 1.6),
+            echelon: factor(0.75, 0.6, 1.1, 1.2, 1.2, 1.0),
+            line_abreast: factor(0.6, 0.6, 1.3, 1.2, 1.3, 1.0),
+            vanguard_front: factor(1.1, 1.0, 0.6, 1.0, 0.8, 1.1),
+            vanguard_rear: factor(0.5, 1.0, 1.0, 1.0, 1.4, 1.1),
+        }
+    }
+}
+
+/// 1つの陣形の補正。攻撃力の補正はキャップ前にかかる。夜戦の攻撃力は陣形の補正を受けない。
+/// 既定値はすべて 1.0 (補正なし)。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct FormationFactor {
+    /// 昼砲撃戦の攻撃力補正
+    pub shelling: f64,
+    /// 雷撃戦の攻撃力補正
+    pub torpedo: f64,
+    /// 対潜攻撃の攻撃力補正
+    pub anti_submarine: f64,
+    /// 昼砲撃戦の命中補正。攻撃側の命中項にかかる
+    pub accuracy: f64,
+    /// 回避補正。攻撃を受ける側の回避項の計算で、艦の回避値にかかる
+    pub evasion: f64,
+    /// 艦隊防空値の補正
+    pub anti_aircraft: f64,
+}
+
+impl Default for FormationFactor {
+    fn default() -> Self {
+        Self {
+            shelling: 1.0,
+            torpedo: 1.0,
+            anti_submarine: 1.0,
+            accuracy: 1.0,
+            evasion: 1.0,
+            anti_aircraft: 1.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DamagedLevelFactors {
+    pub no_damage: f64,
+    pub minor: f64,
+    pub moderate: f64,
+    pub heavy: f64,
+}
+
+impl Default for DamagedLevelFactors {
+    fn default() -> Self {
+        Self {
+            no_damage: 1.0,
+            minor: 1.0,
+            moderate: 0.7,
+            heavy: 0.4,
+        }
+    }
+}
+
+/// 残りHPに対する割合ダメージの係数。
+/// ダメージは `HP × base + floor(HP × 乱数) × random` で計算される。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct DamageCoefficients {
+    pub base: f64,
+    pub random: f64,
+}
+
+impl DamageCoefficients {
+    /// 残りHP `hp` と `[0, 1)` の乱数 `r` から割合ダメージを計算する。
+    pub fn damage<N: Scalar>(&self, hp: N, r: N) -> N {
+        hp * N::from_f64(self.base) + (hp * r).floor() * N::from_f64(self.random)
+    }
+}
@@ -0,0 +1,69 @@
+//! 戦闘の計算式で使う定数の管理。
+//! ゲームの仕様変更の前後で結果を比較・再現できるよう、名前付きの定数セットを切り替えて使える。
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+mod constants;
+pub use constants::{
+    DamageCoefficients, DamagedLevelFactors, DirectionFactors, FormationFactor, FormationFactors,
+    FormulaConstants,
+};
+
+mod synergy;
+pub use synergy::{SynergyBonus, SynergyRequirement, SynergyRule, SynergyTarget};
+
+/// 組み込みの定数セットの名前。`SimulationOptions.formula_set` で指定する。
+/// 2017-11 のアップデートで昼戦キャップが 180、対潜キャップが 150 に、
+/// 2021 年のアップデートで昼戦キャップが 220、対潜キャップが 170 に引き上げられた。
+pub const BUILTIN_FORMULA_SETS: [&str; 2] = ["2017-11", "2021"];
+
+thread_local! {
+    /// 実行時に読み込まれた既定の定数。wasm はシングルスレッドで動作するため、スレッドローカルに保持する。
+    static CONSTANTS: RefCell<Option<Rc<FormulaConstants>>> = const { RefCell::new(None) };
+    /// 実行時に登録された名前付きの定数セット。同名の組み込みセットより優先される。
+    static SETS: RefCell<HashMap<String, Rc<FormulaConstants>>> = RefCell::new(HashMap::new());
+}
+
+/// 読み込んだ定数を既定の定数としてモジュール内に保持する。既に読み込み済みの場合は置き換える。
+pub fn set_constants(constants: FormulaConstants) {
+    CONSTANTS.with(|c| *c.borrow_mut() = Some(Rc::new(constants)));
+}
+
+/// 名前付きの定数セットを登録する。同名のセットが既にある場合は置き換える。
+pub fn register_set(name: String, constants: FormulaConstants) {
+    SETS.with(|s| s.borrow_mut().insert(name, Rc::new(constants)));
+}
+
+/// 既定の定数を取得する。読み込まれていない場合はコンパイル時の既定値 (最新の仕様) を返す。
+pub fn constants() -> Rc<FormulaConstants> {
+    CONSTANTS.with(|c| {
+        c.borrow_mut()
+            .get_or_insert_with(|| Rc::new(FormulaConstants::default()))
+            .clone()
+    })
+}
+
+/// 名前で指定された定数セットを取得する。`None` の場合は既定の定数を返す。
+/// 該当するセットがない場合は `None` を返す。
+pub fn constants_for(name: Option<&str>) -> Option<Rc<FormulaConstants>> {
+    let Some(name) = name else {
+        return Some(constants());
+    };
+    SETS.with(|s| s.borrow().get(name).cloned())
+        .or_else(|| builtin_set(name).map(Rc::new))
+}
+
+/// 組み込みの定数セットを取得する。
+fn builtin_set(name: &str) -> Option<FormulaConstants> {
+    match name {
+        "2017-11" => Some(FormulaConstants {
+            day_artillery_cap: 180.0,
+            night_cap: 300.0,
+            asw_cap: 150.0,
+            ..FormulaConstants::default()
+        }),
+        "2021" => Some(FormulaConstants::default()),
+        _ => None,
+    }
+}
@@ -0,0 +1,264 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fleet::{EquipCategory, Equipment, Ship};
+
+/// 装備の組み合わせによる補正 (シナジー) の規則。
+/// `requires` のすべての条件を満たす装備を積んだ艦が、`targets` に該当する艦を攻撃する際に `bonus` が適用される。
+/// 同じ `group` の規則は並び順で最初に条件を満たしたものだけが適用され、異なる規則の補正は重複する。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SynergyRule {
+    /// 規則の名前。設定を識別するためのもので、計算には使わない
+    pub name: String,
+    /// 排他的に適用する規則のグループ名。省略した場合は他の規則と常に重複する
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group: Option<String>,
+    pub requires: Vec<SynergyRequirement>,
+    /// 補正がかかる攻撃対象の種別。空の場合はすべての攻撃対象に補正がかかる
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub targets: Vec<SynergyTarget>,
+    pub bonus: SynergyBonus,
+}
+
+impl SynergyRule {
+    fn new(
+        name: &str,
+        group: Option<&str>,
+        requires: Vec<SynergyRequirement>,
+        targets: &[SynergyTarget],
+        bonus: SynergyBonus,
+    ) -> Self {
+        Self {
+            name: name.to_string(),
+            group: group.map(str::to_string),
+            requires,
+            targets: targets.to_vec(),
+            bonus,
+        }
+    }
+
+    /// `equips` がこの規則の装備の条件をすべて満たすかどうかを判定する。
+    pub fn matches(&self, equips: &[Equipment]) -> bool {
+        self.requires.iter().all(|r| r.matches(equips))
+    }
+
+    /// `target` を攻撃する際にこの規則の補正がかかるかどうかを判定する。
+    pub fn applies_to(&self, target: &Ship) -> bool {
+        self.targets.is_empty() || self.targets.iter().any(|t| t.matches(target))
+    }
+
+    /// 既定のシナジー規則。
+    /// 徹甲弾と中口径・大口径主砲の組み合わせは重装甲の艦と陸上型に対してのみ、補正の大きいものから1つだけ適用する。
+    pub fn defaults() -> Vec<SynergyRule> {
+        use EquipCategory::*;
+        let main_gun = SynergyRequirement::any(&[MediumCaliberMainGun, LargeCaliberMainGun]);
+        let radar = SynergyRequirement::any(&[SmallRadar, LargeRadar]);
+        let armor_piercing = SynergyRequirement::any(&[ArmorPiercingShell]);
+        let secondary = SynergyRequirement::any(&[SecondaryGun]);
+        let heavy = [
+            SynergyTarget::Battleship,
+            SynergyTarget::Carrier,
+            SynergyTarget::HeavyCruiser,
+            SynergyTarget::Installation,
+        ];
+        let shelling = |shelling| SynergyBonus {
+            shelling,
+            ..SynergyBonus::default()
+        };
+        vec![
+            SynergyRule::new(
+                "armor_piercing_main_secondary",
+                Some("armor_piercing"),
+                vec![armor_piercing.clone(), main_gun.clone(), secondary],
+                &heavy,
+                shelling(1.15),
+            ),
+            SynergyRule::new(
+                "armor_piercing_main_radar",
+                Some("armor_piercing"),
+                vec![armor_piercing.clone(), main_gun.clone(), radar.clone()],
+                &heavy,
+                shelling(1.1),
+            ),
+            SynergyRule::new(
+                "armor_piercing_main",
+                Some("armor_piercing"),
+                vec![armor_piercing, main_gun],
+                &heavy,
+                shelling(1.08),
+            ),
+            SynergyRule::new(
+                "sonar_depth_charge",
+                None,
+                vec![
+                    SynergyRequirement::any(&[Sonar]),
+                    SynergyRequirement::any(&[DepthCharge]),
+                ],
+                &[],
+                SynergyBonus {
+                    anti_submarine: 1.15,
+                    ..SynergyBonus::default()
+                },
+            ),
+            SynergyRule::new(
+                "torpedo_radar_night",
+                None,
+                vec![SynergyRequirement::any(&[Torpedo]), radar],
+                &[],
+                SynergyBonus {
+                    night_accuracy: 5.0,
+                    ..SynergyBonus::default()
+                },
+            ),
+        ]
+    }
+}
+
+/// シナジー規則の条件の1つ。`categories` のいずれかに該当する装備を `count` 個以上積んでいることを表す。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SynergyRequirement {
+    pub categories: Vec<EquipCategory>,
+    /// 必要な装備の数。省略時は 1
+    pub count: usize,
+}
+
+impl Default for SynergyRequirement {
+    fn default() -> Self {
+        Self {
+            categories: Vec::new(),
+            count: 1,
+        }
+    }
+}
+
+impl SynergyRequirement {
+    /// `categories` のいずれかに該当する装備を1個以上積んでいる条件。
+    fn any(categories: &[EquipCategory]) -> Self {
+        Self {
+            categories: categories.to_vec(),
+            ..Self::default()
+        }
+    }
+
+    fn matches(&self, equips: &[Equipment]) -> bool {
+        let count = equips
+            .iter()
+            .filter(|e| self.categories.contains(&e.category()))
+            .count();
+        count >= self.count
+    }
+}
+
+/// シナジーの補正がかかる攻撃対象の種別。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SynergyTarget {
+    /// 戦艦系
+    Battleship,
+    /// 空母系
+    Carrier,
+    /// 重巡系 (重巡洋艦、航空巡洋艦)
+    HeavyCruiser,
+    /// 陸上型
+    Installation,
+}
+
+impl SynergyTarget {
+    fn matches(&self, target: &Ship) -> bool {
+        match self {
+            SynergyTarget::Battleship => target.is_battleship_class(),
+            SynergyTarget::Carrier => target.is_carrier_class(),
+            SynergyTarget::HeavyCruiser => target.is_heavy_cruiser_class(),
+            SynergyTarget::Installation => target.is_installation(),
+        }
+    }
+}
+
+/// シナジーによる補正。既定値は補正なし (倍率 1.0、加算値 0.0)。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SynergyBonus {
+    /// 昼砲撃戦の攻撃力にキャップ後にかかる倍率
+    pub shelling: f64,
+    /// 対潜攻撃力にキャップ前にかかる倍率
+    pub anti_submarine: f64,
+    /// 昼戦の命中項への加算
+    pub accuracy: f64,
+    /// 夜戦の命中項への加算
+    pub night_accuracy: f64,
+}
+
+impl Default for SynergyBonus {
+    fn default() -> Self {
+        Self {
+            shelling: 1.0,
+            anti_submarine: 1.0,
+            accuracy: 0.0,
+            night_accuracy: 0.0,
+        }
+    }
+}
+
+impl SynergyBonus {
+    /// 装備の条件を満たす規則 `rules` のうち、`target` を攻撃する際に適用されるすべての補正を合成する。
+    /// 倍率は積、加算値は和をとる。
+    pub fn of(rules: &[SynergyRule], target: &Ship) -> Self {
+        let mut applied_groups = Vec::new();
+        let mut bonus = Self::default();
+        for rule in rules {
+            if rule
+                .group
+                .as_ref()
+                .is_some_and(|g| applied_groups.contains(&g))
+                || !rule.applies_to(target)
+            {
+                continue;
+            }
+            if let Some(group) = &rule.group {
+                applied_groups.push(group);
+            }
+            bonus.shelling *= rule.bonus.shelling;
+            bonus.anti_submarine *= rule.bonus.anti_submarine;
+            bonus.accuracy += rule.bonus.accuracy;
+            bonus.night_accuracy += rule.bonus.night_accuracy;
+        }
+        bonus
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ship(ship_type_id: u16, equip_type_ids: &[u16]) -> Ship {
+        let equips = equip_type_ids
+            .iter()
+            .map(|t| serde_json::json!({ "id": 1, "equipTypeId": [0, 0, t, 0, 0] }));
+        let mut ship = Ship::test_builder()
+            .ship_type(ship_type_id)
+            .equips(equips)
+            .build();
+        ship.apply_synergies(&SynergyRule::defaults());
+        ship
+    }
+
+    #[test]
+    fn armor_piercing_requires_heavy_target_and_gun() {
+        let (battleship, destroyer) = (ship(9, &[]), ship(2, &[]));
+        // 大口径主砲 + 徹甲弾 + 副砲
+        let actor = ship(9, &[3, 19, 4]);
+        assert_eq!(actor.synergy(&battleship).shelling, 1.15);
+        assert_eq!(actor.synergy(&destroyer).shelling, 1.0);
+        // 小口径主砲では補正がかからない
+        let actor = ship(9, &[1, 19]);
+        assert_eq!(actor.synergy(&battleship).shelling, 1.0);
+    }
+
+    #[test]
+    fn untargeted_rules_apply_to_any_target() {
+        let submarine = ship(13, &[]);
+        assert_eq!(ship(2, &[14, 15]).synergy(&submarine).anti_submarine, 1.15);
+        assert_eq!(ship(2, &[5, 12]).synergy(&submarine).night_accuracy, 5.0);
+    }
+}
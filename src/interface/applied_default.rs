@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+/// 入力に値がなかったため、シミュレーションが補った値の記録。
+/// 結果がどの程度推測に基づくかを利用者が判断できるよう、結果に添えて返す。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AppliedDefault {
+    /// 補った入力の位置 (`friend.ships[0].status.luck` など)
+    pub path: String,
+    /// 補った値
+    pub value: serde_json::Value,
+}
+
+impl AppliedDefault {
+    pub fn new(path: String, value: impl Serialize) -> Self {
+        Self {
+            path,
+            value: serde_json::to_value(value).unwrap_or_default(),
+        }
+    }
+}
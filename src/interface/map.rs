@@ -0,0 +1,73 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::NodeType;
+use crate::fleet::Formation;
+
+/// 海域マップの定義。
+/// 各マスの敵編成は、`EnemyFleet.node` がマス名と一致するものを出現候補として使う。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapDefinition {
+    /// 出撃開始地点のマス名
+    pub start: String,
+    pub nodes: Vec<MapNode>,
+    /// 大破した艦がいても進撃を続けるか。既定では大破艦が出た時点で撤退する。
+    #[serde(default)]
+    pub continue_on_heavy_damage: bool,
+    /// 連続出撃の設定。指定した場合、出撃後の戦意を次の出撃に持ち越す。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub campaign: Option<Campaign>,
+}
+
+/// 連続出撃の設定。
+/// 出撃のたびに HP・燃料・弾薬は元に戻すが、戦意は戦闘による増減と出撃間の自然回復だけを反映して引き継ぐ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Campaign {
+    /// 戦意を引き継いで続けて行う出撃の回数。この回数ごとに入力された戦意に戻す。
+    pub sorties: u32,
+    /// 出撃の間隔 (分)。この間に戦意が自然回復する。
+    #[serde(default)]
+    pub interval_minutes: u32,
+    /// 旗艦の明石による泊地修理を行うか。
+    /// 出撃間隔が20分以上あれば、小破以下の艦は高速修復材を使わずに修理される。
+    #[serde(default)]
+    pub anchorage_repair: bool,
+}
+
+/// 海域マップの1マス。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapNode {
+    /// マス名。`EnemyFleet.node` と対応する。
+    pub name: String,
+    /// ボスマスかどうか。ボスマスでの戦闘をもって出撃を終える。
+    #[serde(default)]
+    pub boss: bool,
+    /// マスの種類。実行するフェーズと、戦闘後の燃料・弾薬の消費量が決まる。
+    #[serde(default)]
+    pub node_type: NodeType,
+    /// このマスで選択する味方艦隊の陣形。省略した場合は艦隊に設定された陣形を使う。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formation: Option<Formation>,
+    /// このマスからの進路。空の場合は、このマスで出撃を終える。
+    #[serde(default)]
+    pub routes: Vec<Route>,
+}
+
+/// マス間の進路。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Route {
+    /// 進む先のマス名
+    pub to: String,
+    /// この進路に進む確率。同じマスの進路の確率の合計は 1 になることを想定する。
+    pub probability: f64,
+}
+
+impl MapDefinition {
+    /// マス名からマスの定義を取得する。
+    pub fn node(&self, name: &str) -> Option<&MapNode> {
+        self.nodes.iter().find(|n| n.name == name)
+    }
+}
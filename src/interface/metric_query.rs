@@ -0,0 +1,62 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{FleetSide, Phase};
+
+/// 集計モードで、戦闘ごとの値から任意の統計量を計算するための問い合わせ。
+///
+/// ```json
+/// { "side": "enemy", "ship": 0, "value": "remaining_hp",
+///   "statistic": { "type": "probability", "op": "le", "threshold": 0 } }
+/// ```
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricQuery {
+    /// 対象とする艦隊
+    pub side: FleetSide,
+    /// 対象とする艦の艦隊内でのインデックス。省略した場合は艦隊全体の合計。
+    /// 敵艦隊の場合は、その戦闘で選ばれた敵編成内でのインデックスを表す。
+    #[serde(default)]
+    pub ship: Option<usize>,
+    /// 対象とするフェーズ。ダメージ量にのみ適用され、省略した場合は全フェーズの合計。
+    #[serde(default)]
+    pub phase: Option<Phase>,
+    /// 戦闘ごとに取り出す値
+    pub value: MetricValue,
+    /// 取り出した値から計算する統計量
+    pub statistic: Statistic,
+}
+
+/// 戦闘ごとに取り出す値の種類。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum MetricValue {
+    /// 戦闘終了時の残りHP
+    RemainingHp,
+    /// 受けたダメージ
+    DamageTaken,
+    /// 与えたダメージ
+    DamageDealt,
+}
+
+/// 統計量の種類。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Statistic {
+    /// 平均値
+    Mean,
+    /// 分位数。`q` は 0.0〜1.0 で指定する。
+    Quantile { q: f64 },
+    /// 値が条件を満たす確率
+    Probability { op: Comparison, threshold: f64 },
+}
+
+/// 比較演算子。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Comparison {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
@@ -1,10 +1,62 @@
 /// フロントエンドとシミュレーションコア間のインターフェースを定義する。
 /// このモジュールで定義される構造体は、シリアライズ/デシリアライズ可能でなければならない。
 /// また、それらのメソッドは単なるゲッターに限定し、原則的にロジックを含めてはならない。
-use log::warn;
-use serde::{Deserialize, Serialize};
+///
+/// バックエンドや CLI から同じ型を使う場合は、`default-features = false` で依存すれば
+/// wasm-bindgen などのブラウザ向けの依存なしにこのモジュールを利用できる。
+mod applied_default;
+pub use applied_default::AppliedDefault;
+mod options;
+pub use options::{
+    BootstrapOptions, Compression, DesignatedEnemy, Locale, ReportDetail, SimulationOptions,
+};
+mod request;
+pub use request::{SimulationOutput, SimulationRequest};
+mod plan;
+pub use plan::{PlanCosts, PlanKnobs, PlanQuery, PlanTarget, StatDelta, TargetRank};
+mod run_config;
+pub use run_config::RunConfig;
+mod map;
+pub use map::{Campaign, MapDefinition, MapNode, Route};
+mod metric_query;
+pub use metric_query::{Comparison, MetricQuery, MetricValue, Statistic};
+mod scenario;
+pub use scenario::{Scenario, ScenarioFleet, ScenarioOutput, ScenarioOutputKind};
+mod schema;
+pub use schema::{resolve_schema_version, VersionedOutput, OLDEST_SCHEMA_VERSION, SCHEMA_VERSION};
+mod strict;
+pub use strict::{check_unknown_fields, deserialize_tracking_unknown};
 
 /// 戦闘結果をフロントエンドに返すための構造体。
 /// 戦闘の評価、敵編成の何番かを表すインデックス、各艦の戦闘後のスナップショットを持つ。
-pub use crate::battle::{BattleReport, BattleResult, ShipSnapshot};
-pub use crate::fleet::{EnemyFleet, Fleet, Formation, Range, Ship};
+pub use crate::aggregate::{
+    AggregateSummary, BinnedSeries, BootstrapIntervals, Charts, ColumnarReports,
+    ConfidenceInterval, DesignatedEnemyRates, DifferenceTest, EventRates, HpDistribution,
+    HpPercentiles, PhaseDamage, RankByDirection, RankByFinalForm, RankDistribution, RankRates,
+    ResourceUsage, SetupComparison, ShipDamageRates,
+};
+pub use crate::analysis::{
+    ArmorRoll, DamageCell, DamageTable, DefenseProbability, PhaseDamageTable, TimeToKill,
+};
+pub use crate::api_log::{ApiBattle, ApiHougeki, Divergence, ReplayReport};
+pub use crate::battle::{
+    ActionCounts, AswAttackKind, AttackLog, AttackType, BattleLog, BattleObserver, BattleReport,
+    BattleResult, BattleStats, DamagedLevel, FleetSide, LogEntry, NodeType, Phase, ShipRef,
+    ShipSnapshot,
+};
+pub use crate::diagnostics::{ErrorCode, ErrorKind, ErrorReport};
+pub use crate::fleet::{
+    AbyssalClass, CombinedFleet, CombinedFleetType, EnemyFleet, EquipCategory, Equipment, Fleet,
+    Formation, FriendlyFleet, FriendlyFleetTable, LandBase, LandBaseAction, Range, Ship, Squadron,
+    SupportFleet,
+};
+pub use crate::formula::{
+    DamageCoefficients, DamagedLevelFactors, DirectionFactors, FormationFactor, FormationFactors,
+    FormulaConstants, SynergyBonus, SynergyRequirement, SynergyRule, SynergyTarget,
+    BUILTIN_FORMULA_SETS,
+};
+pub use crate::master::{EquipmentBonusRule, MasterData, MasterShip, StatBonus, StatRanges};
+pub use crate::planner::{PlanCandidate, PlanResult};
+#[cfg(feature = "scripting")]
+pub use crate::scripting::ScriptHooks;
+pub use crate::sortie::{Consumption, MapSummary, NodeSummary};
@@ -0,0 +1,201 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{NodeType, Phase};
+use crate::fleet::{FriendlyFleetTable, LandBase, SupportFleet};
+use crate::interface::MetricQuery;
+
+/// シミュレーション全体の挙動を切り替えるオプションを受け取る構造体。
+/// フロントエンドから省略された項目はすべてデフォルト値で補完される。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SimulationOptions {
+    /// 入出力のスキーマバージョン。省略した場合は最も古いバージョン (1) とみなし、その形式で出力する。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    /// デバッグモード。
+    /// 有効な場合、戦闘中に引いた乱数をすべて用途ラベル付きで戦闘ログに記録し、
+    /// そのログを各 `BattleReport` に添付する。
+    /// `debug-log` フィーチャーが無効なビルドでは無視される。
+    pub debug: bool,
+    /// 集計モード。
+    /// 有効な場合、戦闘ごとの `BattleReport` の代わりに全試行の統計 `AggregateSummary` を返す。
+    pub aggregate: bool,
+    /// レポートやログに出力する艦名の言語。
+    /// `ja` 以外を指定した場合、マスターデータが読み込まれていればその表記に置き換える。
+    pub locale: Locale,
+    /// 戦闘ごとの `BattleReport` に含める内容。集計モードでは無視される。
+    pub report_detail: ReportDetail,
+    /// 出力の圧縮形式。
+    /// `none` 以外を指定した場合、出力は JSON を圧縮したバイト列 (`Uint8Array`) として返される。
+    pub compression: Compression,
+    /// 逐次出力モード。
+    /// 有効な場合、wasm ビルドでは各 `BattleReport` を生成されるたびに JS の配列へ追加し、
+    /// すべての結果を Rust 側に溜めてからシリアライズするのに比べてピーク時のメモリ使用量を抑える。
+    /// 集計モードや列指向出力モード、`compression` が指定された場合は無視される。
+    pub incremental: bool,
+    /// 列指向出力モード。
+    /// 有効な場合、戦闘ごとの `BattleReport` の配列の代わりに、戦闘評価や各艦のHPを
+    /// 項目ごとの配列に並べた `ColumnarReports` を返す。集計モードでは無視される。
+    pub columnar: bool,
+    /// 集計モードで、撃沈率などを個別に集計する敵艦。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub designated_enemy: Option<DesignatedEnemy>,
+    /// 集計モードで追加で計算する統計量の問い合わせ。結果は `AggregateSummary.metrics` に同じ順で格納される。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<MetricQuery>,
+    /// 集計モードで出力するグラフ用の分布の最大区間数。
+    pub chart_bins: usize,
+    /// 支援艦隊。編成条件を満たさない場合は警告を出し、支援を行わない。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub support_fleet: Option<SupportFleet>,
+    /// 基地航空隊。出撃に設定され、攻撃目標に戦闘するマス (`EnemyFleet.node`) を含むものが、航空戦の前に攻撃する。
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub land_bases: Vec<LandBase>,
+    /// 友軍艦隊の出現候補。夜戦の開始時に出現する艦隊を選び、味方艦隊の夜戦の前に敵艦隊を攻撃させる。
+    /// 友軍艦隊の損害は戦闘結果に含まれない。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub friendly_fleet: Option<FriendlyFleetTable>,
+    /// この出撃ですでにタッチ系特殊攻撃 (Nelson Touch、長門・陸奥・大和の一斉射) を発動したか。
+    /// 1回の出撃で1度しか発動しないため、有効な場合は発動判定を行わない。
+    /// 海域の出撃のシミュレーションでは、前の戦闘で発動した時点で自動的に有効になる。
+    pub touch_attack_used: bool,
+    /// 使用する計算式の定数セットの名前 (`"2017-11"`, `"2021"` または登録済みのセット名)。
+    /// 省略した場合は読み込まれた既定の定数、またはコンパイル時の既定値を使う。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub formula_set: Option<String>,
+    /// 戦闘を行うマスの種類。`night_only` の場合は、入力された現在HPから夜戦だけを行う。
+    pub node_type: NodeType,
+    /// 通常マスで、昼戦の後に夜戦を行うか。戦闘評価は夜戦後のHPで判定する。
+    /// `node_type` が `normal` 以外の場合は無視される。
+    pub night_battle: bool,
+    /// 単独で実行するフェーズ。指定した場合は、他のフェーズを行わずにこのフェーズだけを実行する。
+    /// 現在は `land_base`、`air_combat`、`opening_torpedo`、`first_artillery`、`second_artillery`、`closing_torpedo`、`night` に対応する。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phase: Option<Phase>,
+    /// 厳格モード。
+    /// 有効な場合、入力に未知のフィールド (`firePower` のような綴りの誤りを含む) があればエラーとする。
+    /// 無効な場合は警告を出し、そのフィールドを無視する。
+    pub strict: bool,
+    /// 集計モードで、派生指標の信頼区間をブートストラップ法で推定する設定。
+    /// 指定した場合、戦闘ごとの簡易な記録を保持し、集計の終了時に再標本化する。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bootstrap: Option<BootstrapOptions>,
+    /// 固定小数点数モード。
+    /// 有効な場合、攻撃力・防御力・ダメージの計算を浮動小数点数の代わりに最小単位 10^-6 の固定小数点数で行い、
+    /// wasm・x86・ARM のどのビルドでも同じ乱数から同じ結果が得られるようにする。
+    /// 端数の扱いがわずかに異なるため、無効な場合とは結果が一致しないことがある。
+    /// `scripting` フィーチャーの補正式は浮動小数点数で評価される。
+    pub fixed_point: bool,
+    /// 乱数のシード。
+    /// 指定した場合、同じ入力・オプション・シードの実行は、wasm とネイティブのどちらのビルドでも
+    /// 同じ戦闘の列と結果を再現する (`FORMULA_VERSION` が同じ間に限る)。
+    /// 浮動小数点数の端数まで一致させる場合は `fixed_point` も有効にする。
+    /// 省略した場合は実行ごとに OS の乱数からシードを得る。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+}
+
+impl Default for SimulationOptions {
+    fn default() -> Self {
+        Self {
+            schema_version: None,
+            debug: false,
+            aggregate: false,
+            locale: Locale::default(),
+            report_detail: ReportDetail::default(),
+            compression: Compression::default(),
+            incremental: false,
+            columnar: false,
+            designated_enemy: None,
+            metrics: Vec::new(),
+            chart_bins: 20,
+            support_fleet: None,
+            land_bases: Vec::new(),
+            friendly_fleet: None,
+            touch_attack_used: false,
+            formula_set: None,
+            node_type: NodeType::default(),
+            night_battle: false,
+            phase: None,
+            strict: false,
+            bootstrap: None,
+            fixed_point: false,
+            seed: None,
+        }
+    }
+}
+
+/// 個別に集計する敵艦の指定。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DesignatedEnemy {
+    /// 敵編成のインデックス
+    pub fleet_index: usize,
+    /// 敵編成内での艦のインデックス
+    pub ship_index: usize,
+    /// 戦闘終了時にこの値を超えるHPが残る確率を集計する。省略時は0。
+    #[serde(default)]
+    pub hp_threshold: u16,
+}
+
+/// ブートストラップ法による信頼区間の推定の設定。
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BootstrapOptions {
+    /// 再標本化の回数。省略時は1000。
+    #[serde(default = "BootstrapOptions::default_resamples")]
+    pub resamples: u32,
+    /// ゲージを削り切るのに必要な敵旗艦の撃沈回数。省略時は1。
+    #[serde(default = "BootstrapOptions::default_gauge_kills")]
+    pub gauge_kills: u32,
+}
+
+impl BootstrapOptions {
+    fn default_resamples() -> u32 {
+        1000
+    }
+    fn default_gauge_kills() -> u32 {
+        1
+    }
+}
+
+/// 出力の圧縮形式を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Compression {
+    /// 圧縮しない。
+    #[default]
+    None,
+    /// JSON を gzip 形式で圧縮する。ブラウザでは `DecompressionStream("gzip")` で展開できる。
+    Gzip,
+}
+
+/// `BattleReport` に含める艦隊情報の詳細度を表す列挙型。
+/// 艦隊全体を含めると戦闘ごとに同じ編成が複製されるため、試行回数が多い場合は省略するとよい。
+/// 省略した艦隊については、代わりに各艦のスナップショットを入力順に含める。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportDetail {
+    /// 味方・敵艦隊全体を含める。
+    #[default]
+    Full,
+    /// 敵艦隊を省略する。敵編成は `enemyIndex` で参照する。
+    OmitEnemyFleet,
+    /// 味方・敵艦隊の両方を省略し、スナップショットのみを含める。
+    SnapshotsOnly,
+    /// 味方・敵艦隊の両方を省略し、各艦を (艦隊, 位置, 艦船ID) の参照とスナップショットで表す。
+    ShipRefs,
+}
+
+/// 艦名などの表示に使う言語を表す列挙型。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum Locale {
+    /// 入力された日本語名をそのまま使う。
+    #[default]
+    Ja,
+    /// マスターデータの英語名を使う。
+    En,
+    /// `#1501` のような艦船固有IDに基づく表記を使う。マスターデータは不要。
+    Id,
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fleet::Formation;
+use crate::master::StatBonus;
+
+/// 目標の戦闘評価を満たす設定を探索する条件。
+/// `knobs` で指定した設定の組み合わせをそれぞれシミュレーションし、
+/// 目標を満たすもののうち `costs` による費用が最も小さいものを選ぶ。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanQuery {
+    pub target: PlanTarget,
+    #[serde(default)]
+    pub knobs: PlanKnobs,
+    #[serde(default)]
+    pub costs: PlanCosts,
+}
+
+/// 目標とする戦闘評価と、その発生率の下限。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanTarget {
+    /// この評価以上を目標とする
+    pub rank: TargetRank,
+    /// 発生率の下限 (0.0 - 1.0)
+    pub rate: f64,
+}
+
+/// 目標とする戦闘評価。いずれもその評価以上 (S なら完全勝利Sを含む) を表す。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TargetRank {
+    S,
+    A,
+    B,
+}
+
+/// 探索で変更する設定。省略した項目は入力のまま変えない。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlanKnobs {
+    /// 試す陣形。空の場合は入力の陣形のみを使う。
+    pub formations: Vec<Formation>,
+    /// 夜戦を行う場合と行わない場合の両方を試すか。
+    pub night_battle: bool,
+    /// 味方艦1隻のステータスの上げ幅。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stat_delta: Option<StatDelta>,
+}
+
+/// 味方艦1隻のステータスを段階的に上げる設定。
+/// 0 段階から `max_steps` 段階まで、`step` の倍数を加算した場合をそれぞれ試す。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct StatDelta {
+    /// 味方艦隊内の位置
+    pub ship_index: usize,
+    /// 1段階あたりの上げ幅
+    pub step: StatBonus,
+    pub max_steps: u16,
+}
+
+/// 設定ごとの費用。候補の費用は、使う設定の費用の合計とする。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PlanCosts {
+    /// 入力と異なる陣形を使う費用
+    pub formation_change: f64,
+    /// 夜戦を行う費用
+    pub night_battle: f64,
+    /// ステータスの上げ幅1段階あたりの費用
+    pub stat_step: f64,
+}
+
+impl Default for PlanCosts {
+    fn default() -> Self {
+        Self {
+            formation_change: 1.0,
+            night_battle: 1.0,
+            stat_step: 1.0,
+        }
+    }
+}
@@ -0,0 +1,90 @@
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::{AggregateSummary, ColumnarReports};
+use crate::battle::BattleReport;
+use crate::fleet::{EnemyFleet, Fleet};
+use crate::formula::FormulaConstants;
+use crate::interface::{
+    resolve_schema_version, SimulationOptions, VersionedOutput, SCHEMA_VERSION,
+};
+use crate::master::MasterData;
+
+/// シミュレーションの入力一式をまとめた構造体。
+/// 引数を個別に受け取れない環境 (WASI の標準入力など) で、1つの JSON として入力を受け取るために使う。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct SimulationRequest {
+    /// 入出力のスキーマバージョン。省略した場合は最も古いバージョン (1) とみなす。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schema_version: Option<u32>,
+    pub friend: Fleet,
+    pub enemies: Vec<EnemyFleet>,
+    pub count: u32,
+    #[serde(default)]
+    pub options: SimulationOptions,
+    /// 省略可能なマスターデータ。指定された場合はシミュレーション前に読み込まれる。
+    #[serde(default)]
+    pub master: Option<MasterData>,
+    /// 省略可能な計算式の定数。指定された場合はシミュレーション前に読み込まれる。
+    #[serde(default)]
+    pub formula_constants: Option<FormulaConstants>,
+}
+
+impl SimulationRequest {
+    /// スキーマバージョンを確認し、現在の形式に移行する。
+    /// 出力の形式は入力のバージョンに合わせるため、バージョンはオプションにも引き継ぐ。
+    pub fn migrate(mut self) -> Result<Self, String> {
+        let version = resolve_schema_version(self.schema_version)?;
+        let options_version =
+            resolve_schema_version(self.options.schema_version.or(Some(version)))?;
+        self.schema_version = Some(SCHEMA_VERSION);
+        self.options.schema_version = Some(options_version);
+        Ok(self)
+    }
+}
+
+/// シミュレーションの出力。集計モードかどうかと、スキーマバージョンで内容が異なる。
+#[derive(Serialize, Debug, Clone)]
+#[serde(untagged)]
+pub enum SimulationOutput {
+    /// 戦闘ごとの結果
+    Reports(Vec<BattleReport>),
+    /// 集計モードでの統計結果
+    Summary(Box<AggregateSummary>),
+    /// 列指向出力モードでの戦闘ごとの結果
+    Columns(Box<ColumnarReports>),
+    /// スキーマバージョン 2 以降の出力
+    Versioned(VersionedOutput),
+}
+
+impl SimulationOutput {
+    /// 集計モードの統計結果を取得する。戦闘ごとの結果の場合は `None` を返す。
+    pub fn summary(&self) -> Option<&AggregateSummary> {
+        match self {
+            SimulationOutput::Summary(summary) => Some(summary),
+            SimulationOutput::Versioned(versioned) => versioned.summary.as_deref(),
+            SimulationOutput::Reports(_) | SimulationOutput::Columns(_) => None,
+        }
+    }
+
+    /// 指定されたスキーマバージョンの形式に変換する。
+    pub fn into_schema(self, version: u32) -> Self {
+        if version < 2 {
+            return self;
+        }
+        let mut versioned = VersionedOutput {
+            schema_version: SCHEMA_VERSION,
+            reports: None,
+            config: None,
+            summary: None,
+            columns: None,
+        };
+        match self {
+            SimulationOutput::Reports(reports) => versioned.reports = Some(reports),
+            SimulationOutput::Summary(summary) => versioned.summary = Some(summary),
+            SimulationOutput::Columns(columns) => versioned.columns = Some(columns),
+            SimulationOutput::Versioned(_) => return self,
+        }
+        SimulationOutput::Versioned(versioned)
+    }
+}
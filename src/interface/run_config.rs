@@ -0,0 +1,21 @@
+use serde::{Deserialize, Serialize};
+
+use crate::interface::{AppliedDefault, SimulationOptions};
+
+/// 戦闘ごとの結果の出力に1回だけ添付する、実行時の設定の控え。
+/// 編成を編集した後でも、保存した結果がどの入力・設定から得られたものか判別できるようにする。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct RunConfig {
+    /// 入力 (味方艦隊・敵艦隊・オプション) のダイジェスト。
+    pub input_digest: String,
+    /// 入力になかったために既定値で補った値
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied_defaults: Vec<AppliedDefault>,
+    pub options: SimulationOptions,
+    /// 実行に使った乱数のシード。シードを指定しない実行では OS の乱数から引いた値で、
+    /// `options.seed` に指定すれば同じ結果を再現できる。
+    pub seed: u64,
+    /// 戦闘の計算式のバージョン。
+    pub formula_version: u32,
+}
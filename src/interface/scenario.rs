@@ -0,0 +1,68 @@
+use serde::{Deserialize, Serialize};
+
+use crate::fleet::{EnemyFleet, Fleet};
+use crate::formula::FormulaConstants;
+use crate::interface::SimulationOptions;
+use crate::master::MasterData;
+
+/// 複数の味方編成を同じ条件で比較する実行の設定を1つのファイルにまとめた構造体。
+/// 設定ファイルとして共有すれば、同じ比較を誰でも再実行できる。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct Scenario {
+    /// 比較する味方編成。すべて同じ敵編成・オプション・試行回数で実行する。
+    pub fleets: Vec<ScenarioFleet>,
+    pub enemies: Vec<EnemyFleet>,
+    /// 味方編成ごとの試行回数
+    pub count: u32,
+    #[serde(default)]
+    pub options: SimulationOptions,
+    /// 出力する内容。省略した場合は集計結果の JSON を標準出力に書き出す。
+    #[serde(default = "Scenario::default_outputs")]
+    pub outputs: Vec<ScenarioOutput>,
+    /// 省略可能なマスターデータ。指定された場合はシミュレーション前に読み込まれる。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master: Option<MasterData>,
+    /// 省略可能な計算式の定数。指定された場合はシミュレーション前に読み込まれる。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub formula_constants: Option<FormulaConstants>,
+}
+
+impl Scenario {
+    fn default_outputs() -> Vec<ScenarioOutput> {
+        vec![ScenarioOutput {
+            kind: ScenarioOutputKind::Summary,
+            path: None,
+        }]
+    }
+}
+
+/// 名前を付けた味方編成。出力ではこの名前で結果を区別する。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioFleet {
+    pub name: String,
+    pub fleet: Fleet,
+}
+
+/// シナリオの出力1つ分の指定。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ScenarioOutput {
+    pub kind: ScenarioOutputKind,
+    /// 書き出すファイル。省略した場合は標準出力に書き出す。
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+}
+
+/// シナリオの出力の種類。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScenarioOutputKind {
+    /// 味方編成の名前をキーとした集計結果の JSON
+    Summary,
+    /// 味方編成ごとの集計結果の Markdown 文書
+    Markdown,
+    /// 先頭の味方編成に対する、2番目以降の各編成の差の検定結果の JSON
+    Comparison,
+}
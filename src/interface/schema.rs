@@ -0,0 +1,43 @@
+use serde::Serialize;
+
+use crate::aggregate::{AggregateSummary, ColumnarReports};
+use crate::battle::BattleReport;
+use crate::interface::RunConfig;
+
+/// 現在の入出力のスキーマバージョン。
+///
+/// - 1: `schemaVersion` 導入前の形式。出力は `BattleReport` の配列か `AggregateSummary` そのもの。
+/// - 2: 出力を `{ schemaVersion, reports, config }`、`{ schemaVersion, summary }` または `{ schemaVersion, columns }` で包む。
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// 受け付ける最も古いスキーマバージョン。`schemaVersion` を省略した入力はこのバージョンとみなす。
+pub const OLDEST_SCHEMA_VERSION: u32 = 1;
+
+/// 入力のスキーマバージョンが受け付け可能か確認し、省略時の値を補ったバージョンを返す。
+pub fn resolve_schema_version(version: Option<u32>) -> Result<u32, String> {
+    let version = version.unwrap_or(OLDEST_SCHEMA_VERSION);
+    if (OLDEST_SCHEMA_VERSION..=SCHEMA_VERSION).contains(&version) {
+        Ok(version)
+    } else {
+        Err(format!(
+            "Unsupported schema version {} (supported: {}..={})",
+            version, OLDEST_SCHEMA_VERSION, SCHEMA_VERSION
+        ))
+    }
+}
+
+/// スキーマバージョン 2 以降の出力。出力の種類をキーで区別する。
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct VersionedOutput {
+    pub schema_version: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reports: Option<Vec<BattleReport>>,
+    /// `reports` を得た実行の設定の控え。
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub config: Option<Box<RunConfig>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub summary: Option<Box<AggregateSummary>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub columns: Option<Box<ColumnarReports>>,
+}
@@ -0,0 +1,34 @@
+use serde::{Deserialize, Deserializer};
+
+use crate::diagnostics::{ErrorCode, ErrorReport};
+
+/// 入力をデシリアライズし、型に存在しないため無視されたフィールドのパス (`ships.0.status.firePower` など) を併せて返す。
+pub fn deserialize_tracking_unknown<'de, T, D>(
+    deserializer: D,
+) -> Result<(T, Vec<String>), D::Error>
+where
+    T: Deserialize<'de>,
+    D: Deserializer<'de>,
+{
+    let mut unknown = Vec::new();
+    let value = serde_ignored::deserialize(deserializer, |path| unknown.push(path.to_string()))?;
+    Ok((value, unknown))
+}
+
+/// 無視されたフィールドを確認する。
+/// 厳格モードではエラーとし、そうでなければ警告を出して続行する。`input` は入力の名前で、メッセージに使う。
+pub fn check_unknown_fields(
+    input: &str,
+    unknown: &[String],
+    strict: bool,
+) -> Result<(), ErrorReport> {
+    if unknown.is_empty() {
+        return Ok(());
+    }
+    let message = format!("Unknown fields in {}: {}", input, unknown.join(", "));
+    if strict {
+        return Err(ErrorReport::new(ErrorCode::UnknownField, message));
+    }
+    warn!("{}", message);
+    Ok(())
+}
@@ -1,70 +1,609 @@
-use log::{debug, error, info};
-use wasm_bindgen::prelude::*;
+#[macro_use]
+mod logging;
 
+mod aggregate;
+mod analysis;
+mod api_log;
+mod batch;
+pub use batch::BatchSimulation;
 mod battle;
+mod compression;
+mod diagnostics;
+pub mod engine;
 
 mod fleet;
-mod interface;
+mod formula;
+pub mod interface;
+mod master;
+mod planner;
+mod profiling;
+mod rng;
+#[cfg(feature = "scripting")]
+mod scripting;
+mod sortie;
+
+#[cfg(feature = "web")]
 mod utils;
+#[cfg(feature = "web")]
+mod web;
 
 use crate::fleet::FleetLike;
 
-static INIT: std::sync::Once = std::sync::Once::new();
+/// 入力を検証・補完した上で、`count` 回の戦闘をシミュレーションする。
+/// wasm-bindgen に依存しない、すべてのターゲット共通のエントリーポイント。
+/// 出力は `options.schema_version` の形式で返す。バージョンの妥当性は呼び出し側で確認すること。
+pub fn run_simulation(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> interface::SimulationOutput {
+    let mut simulation = BatchSimulation::new(friend, enemy, count, options);
+    simulation.run_batch(count);
+    simulation.finish()
+}
+
+/// 入力された現在HPを戦闘開始時の状態として、夜戦のみを `count` 回シミュレーションする。
+/// 道中で夜戦に突入するかどうかの判断などに使う。`options.node_type` 以外のオプションは `run_simulation` と同じ。
+pub fn run_night_battle(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> interface::SimulationOutput {
+    let options = interface::SimulationOptions {
+        node_type: interface::NodeType::NightOnly,
+        ..options.clone()
+    };
+    run_simulation(friend, enemy, count, &options)
+}
 
-fn initialize() {
-    INIT.call_once(|| {
-        utils::set_panic_hook();
-        wasm_logger::init(wasm_logger::Config::default()); // ロガー初期化
-        info!("Logger initialized");
+/// `phase` だけを他のフェーズから切り離して `count` 回シミュレーションし、集計した統計を返す。
+/// 特定の仕組みの効果を安く調べるために使う。単独で実行できないフェーズの場合はエラーメッセージを返す。
+pub fn run_single_phase(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    phase: interface::Phase,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> Result<interface::SimulationOutput, String> {
+    if battle::SinglePhase::new(phase.clone()).is_none() {
+        return Err(format!("Phase cannot be simulated on its own: {:?}", phase));
+    }
+    let options = interface::SimulationOptions {
+        aggregate: true,
+        phase: Some(phase),
+        ..options.clone()
+    };
+    Ok(run_simulation(friend, enemy, count, &options))
+}
+
+/// 入力を検証・補完した上で `count` 回の戦闘をシミュレーションし、
+/// 実行の設定の控えを最初に1回 `on_config` に、戦闘ごとの結果を生成されるたびに `on_report` に渡す。
+/// 結果をまとめて保持しないため、呼び出し側で逐次シリアライズすればピーク時のメモリ使用量を抑えられる。
+/// `options.aggregate` は無視される。
+pub fn run_reports(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+    on_config: impl FnOnce(&interface::RunConfig),
+    mut on_report: impl FnMut(interface::BattleReport),
+) {
+    let input_digest = diagnostics::input_digest(&(&friend, &enemy, options));
+    diagnostics::begin(input_digest.clone());
+    let mut prepared = options.clone();
+    let applied_defaults = prepare_input(&mut friend, &mut enemy, &mut prepared);
+
+    let seed = rng::resolve_seed(options.seed);
+    on_config(&interface::RunConfig {
+        input_digest,
+        applied_defaults,
+        options: options.clone(),
+        seed,
+        formula_version: battle::FORMULA_VERSION,
     });
+    let options = &prepared;
+    let mut rng = rng::from_seed(Some(seed));
+    for i in 0..battle_count(&enemy, count) {
+        diagnostics::set_iteration(i);
+        let (enemy_index, selected_enemy) = select_random_enemy(&enemy, &mut rng);
+        let battle = battle_once(&friend, selected_enemy, options, rng::next_seed(&mut rng));
+        on_report(battle.into_battle_report(enemy_index, options.report_detail));
+    }
+    diagnostics::finish();
+}
+
+/// 入力を検証・補完した上で `count` 回の戦闘をシミュレーションし、
+/// 戦闘ごとの `BattleReport` を生成されるたびに1行の JSON (NDJSON) として `writer` に書き出す。
+/// 最初の行は実行の設定の控えを `{ "config": RunConfig }` として書き出す。
+/// 結果をメモリに溜めないため、数百万回の試行でもファイルや標準出力へ直接書き出せる。
+/// 書き込みに失敗した場合は、以降の結果を書き出さずにそのエラーを返す。
+pub fn write_ndjson(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+    mut writer: impl std::io::Write,
+) -> std::io::Result<()> {
+    let state = std::cell::RefCell::new((&mut writer, Ok(())));
+    run_reports(
+        friend,
+        enemy,
+        count,
+        options,
+        |config| write_ndjson_line(&state, &serde_json::json!({ "config": config })),
+        |report| write_ndjson_line(&state, &report),
+    );
+    state.into_inner().1?;
+    writer.flush()
+}
+
+/// `value` を1行の JSON として書き出す。以前の書き込みに失敗している場合は何もしない。
+fn write_ndjson_line<W: std::io::Write>(
+    state: &std::cell::RefCell<(W, std::io::Result<()>)>,
+    value: &impl serde::Serialize,
+) {
+    let (writer, written) = &mut *state.borrow_mut();
+    if written.is_err() {
+        return;
+    }
+    *written = serde_json::to_writer(&mut *writer, value)
+        .map_err(std::io::Error::from)
+        .and_then(|_| writer.write_all(b"\n"));
+}
+
+/// 入力を検証・補完した上で `count` 回の戦闘をシミュレーションし、戦闘評価の発生率のみを返す。
+/// 戦闘ごとの結果や統計を保持しないため、`run_simulation` の集計モードより軽量に動作する。
+pub fn run_rank_rates(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> interface::RankRates {
+    diagnostics::begin(diagnostics::input_digest(&(&friend, &enemy, options)));
+    let options = &mut options.clone();
+    prepare_input(&mut friend, &mut enemy, options);
+
+    let mut ranks = interface::RankDistribution::default();
+    let mut rng = rng::from_seed(options.seed);
+    for i in 0..battle_count(&enemy, count) {
+        diagnostics::set_iteration(i);
+        let (_, selected_enemy) = select_random_enemy(&enemy, &mut rng);
+        let battle = battle_once(&friend, selected_enemy, options, rng::next_seed(&mut rng));
+        ranks.record(&battle::BattleResult::calculate(&battle));
+    }
+    diagnostics::finish();
+    ranks.cumulative_rates()
+}
+
+/// 入力を検証・補完した上で、2つの味方編成 (A, B) でそれぞれ `count` 回の戦闘をシミュレーションし、
+/// S勝利以上の発生率と敵旗艦への平均ダメージの差を、信頼区間と p 値を添えて返す。
+/// 各試行では A と B が同じ敵編成と、同じシードの乱数で戦い、その組ごとの差から検定する。
+pub fn run_comparison(
+    mut friend_a: interface::Fleet,
+    mut friend_b: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> interface::SetupComparison {
+    diagnostics::begin(diagnostics::input_digest(&(
+        &friend_a, &friend_b, &enemy, options,
+    )));
+    let options = &mut options.clone();
+    prepare_input(&mut friend_a, &mut enemy, options);
+    // 敵編成とオプションは補完済みのため、B は味方艦隊だけを補完する
+    prepare_friend(&mut friend_b, &options.locale);
+
+    let mut counts = aggregate::SetupCounts::default();
+    let mut rng = rng::from_seed(options.seed);
+    for i in 0..battle_count(&enemy, count) {
+        diagnostics::set_iteration(i);
+        let (_, selected_enemy) = select_random_enemy(&enemy, &mut rng);
+        let seed = rng::next_seed(&mut rng);
+        counts.record(
+            &battle_once(&friend_a, selected_enemy, options, seed),
+            &battle_once(&friend_b, selected_enemy, options, seed),
+        );
+    }
+    diagnostics::finish();
+    interface::SetupComparison::new(&counts)
+}
+
+/// 入力を検証・補完した上で `count` 回の戦闘をシミュレーションし、
+/// 戦闘中の出来事を発生順に `observer` へ通知する。
+/// 結果の集計はすべて `observer` に委ねるため、独自の統計や可視化に使える。
+pub fn run_with_observer(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+    observer: &mut impl interface::BattleObserver,
+) {
+    diagnostics::begin(diagnostics::input_digest(&(&friend, &enemy, options)));
+    let options = &mut options.clone();
+    prepare_input(&mut friend, &mut enemy, options);
+
+    let mut rng = rng::from_seed(options.seed);
+    for i in 0..battle_count(&enemy, count) {
+        diagnostics::set_iteration(i);
+        let (enemy_index, selected_enemy) = select_random_enemy(&enemy, &mut rng);
+        let battle = battle_once(&friend, selected_enemy, options, rng::next_seed(&mut rng));
+        battle::notify(observer, &battle, enemy_index);
+    }
+    diagnostics::finish();
+}
+
+/// 入力を検証・補完した上で、海域マップ全体への出撃を `count` 回シミュレーションする。
+/// 各マスの敵編成は `enemy` のうち `node` がマス名と一致するものから選ばれ、
+/// 戦闘後の味方艦隊の状態は次のマスに持ち越される。
+/// `map.campaign` を指定した場合は、戦意も次の出撃に持ち越される。
+pub fn run_map_simulation(
+    mut friend: interface::Fleet,
+    map: &interface::MapDefinition,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> interface::MapSummary {
+    diagnostics::begin(diagnostics::input_digest(&(&friend, map, &enemy, options)));
+    let options = &mut options.clone();
+    let applied_defaults = prepare_input(&mut friend, &mut enemy, options);
+
+    let sortie = sortie::Sortie::new(map, &enemy);
+    let mut aggregator = sortie::MapAggregator::new(map);
+    sortie.run(&friend, count, options, &mut aggregator);
+    diagnostics::finish();
+    let mut summary = aggregator.summary();
+    summary.applied_defaults = applied_defaults;
+    summary
+}
+
+/// 入力を検証・補完した上で、味方艦と敵艦の組ごとの攻撃の期待値を解析的に計算する。
+/// 敵編成ごとに1つの表を返す。乱数を使わないため、編成の絞り込みなどで `run_simulation` の前段に使える。
+pub fn run_damage_table(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    options: &interface::SimulationOptions,
+) -> Vec<interface::DamageTable> {
+    diagnostics::begin(diagnostics::input_digest(&(&friend, &enemy, options)));
+    let options = &mut options.clone();
+    prepare_input(&mut friend, &mut enemy, options);
+
+    let constants =
+        formula::constants_for(options.formula_set.as_deref()).unwrap_or_else(formula::constants);
+    // 戦闘では `BattleSetup` が導出するシナジーを、解析でも同じ規則から導出する
+    friend.apply_synergies(&constants.synergies);
+    let tables = enemy
+        .iter()
+        .enumerate()
+        .map(|(i, e)| analysis::DamageTable::calculate(&friend, e, i, &constants))
+        .collect();
+    diagnostics::finish();
+    tables
+}
+
+/// 入力を検証・補完した上で、`query` の目標の戦闘評価を満たす最も費用の小さい設定を探索する。
+/// 陣形・夜戦の有無・味方艦1隻のステータスの組み合わせごとに `count` 回の戦闘を行う。
+pub fn run_plan(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+    query: &interface::PlanQuery,
+) -> interface::PlanResult {
+    diagnostics::begin(diagnostics::input_digest(&(
+        &friend, &enemy, options, query,
+    )));
+    let options = &mut options.clone();
+    prepare_input(&mut friend, &mut enemy, options);
+    let result = planner::plan(&friend, &enemy, count, options, query);
+    diagnostics::finish();
+    result
 }
 
-#[wasm_bindgen]
-pub fn simulate(friend_val: JsValue, enemy_val: JsValue, count: u32) -> Result<JsValue, JsValue> {
-    initialize();
+/// 入力を検証・補完した上で、`options.designated_enemy` で指定された敵艦を
+/// 味方艦隊が撃沈するまでに要する攻撃回数を解析的に推定する。
+/// 敵艦が指定されていない場合や、指定された敵艦が存在しない場合は `None` を返す。
+pub fn run_time_to_kill(
+    mut friend: interface::Fleet,
+    mut enemy: Vec<interface::EnemyFleet>,
+    options: &interface::SimulationOptions,
+) -> Option<interface::TimeToKill> {
+    diagnostics::begin(diagnostics::input_digest(&(&friend, &enemy, options)));
+    let options = &mut options.clone();
+    prepare_input(&mut friend, &mut enemy, options);
 
-    info!("Simulation started");
+    let constants =
+        formula::constants_for(options.formula_set.as_deref()).unwrap_or_else(formula::constants);
+    friend.apply_synergies(&constants.synergies);
+    let result = options.designated_enemy.as_ref().and_then(|designated| {
+        let enemy = enemy.get(designated.fleet_index)?;
+        analysis::TimeToKill::calculate(&friend, enemy, designated.ship_index, &constants)
+    });
+    diagnostics::finish();
+    result
+}
 
-    let mut friend = match serde_wasm_bindgen::from_value::<interface::Fleet>(friend_val) {
-        Ok(f) => f,
-        Err(err) => {
-            error!("Failed to parse friend fleet: {:?}", err);
-            return Err(
-                serde_wasm_bindgen::to_value(&Vec::<interface::BattleReport>::new()).unwrap(),
-            );
+/// 入力の検証と、マスターデータに基づく補完を行う。
+/// オプションに含まれる友軍艦隊の艦も、味方艦隊と同様に補完する。
+/// 入力になかったために既定値で補った値の一覧を返す。
+fn prepare_input(
+    friend: &mut interface::Fleet,
+    enemy: &mut [interface::EnemyFleet],
+    options: &mut interface::SimulationOptions,
+) -> Vec<interface::AppliedDefault> {
+    // 検証の際に陣形が補われるため、その前に記録する
+    let mut applied_defaults = friend.applied_defaults("friend");
+    for (i, e) in enemy.iter().enumerate() {
+        applied_defaults.extend(e.applied_defaults(&format!("enemies[{}]", i)));
+    }
+    prepare_friend(friend, &options.locale);
+
+    // 検証で見つかった問題は報告するが、シミュレーションは続行する
+    let mut errors = Vec::new();
+    errors.extend(enemy.iter_mut().filter_map(|e| e.validate().err()));
+    errors.extend(validate_probabilities(enemy));
+    if let Some(support) = &options.support_fleet {
+        errors.extend(support.validate().err());
+    }
+    // 基地航空隊の修正可能な問題は戦闘中にも上限を適用するため、ここでは報告だけを行う
+    for land_base in &options.land_bases {
+        errors.extend(land_base.clone().validate().err());
+    }
+    if let Some(friendly) = &options.friendly_fleet {
+        errors.extend(friendly.validate().err());
+    }
+    if let Some(name) = &options.formula_set {
+        if formula::constants_for(Some(name)).is_none() {
+            errors.push(interface::ErrorReport::new(
+                interface::ErrorCode::FormulaSetUnknown,
+                format!("Unknown formula set: {}", name),
+            ));
         }
-    };
-    let mut enemy = match serde_wasm_bindgen::from_value::<Vec<interface::EnemyFleet>>(enemy_val) {
-        Ok(e) => e,
-        Err(err) => {
-            error!("Failed to parse enemy fleets: {:?}", err);
-            return Err(
-                serde_wasm_bindgen::to_value(&Vec::<interface::BattleReport>::new()).unwrap(),
-            );
+    }
+    if let Some(phase) = &options.phase {
+        if battle::SinglePhase::new(phase.clone()).is_none() {
+            errors.push(interface::ErrorReport::new(
+                interface::ErrorCode::PhaseUnsupported,
+                format!("Phase cannot be simulated on its own: {:?}", phase),
+            ));
         }
-    };
+    }
+    // 改修値の上限を超える装備は、計算では上限の値とみなす
+    errors.extend(
+        enemy
+            .iter()
+            .flat_map(|e| e.ships())
+            .filter_map(|ship| ship.check_improvements().err()),
+    );
+    report_errors(errors);
 
-    friend.validate();
+    let master = master::master_data();
+    // 装備ボーナスは艦娘にのみ存在する
+    if let Some(friendly) = &mut options.friendly_fleet {
+        if let Some(master) = master.as_deref() {
+            friendly.apply_equipment_bonuses(master);
+        }
+        friendly.localize_names(master.as_deref(), &options.locale);
+    }
     enemy.iter_mut().for_each(|e| {
-        e.validate();
+        e.localize_names(master.as_deref(), &options.locale);
     });
 
-    let mut results = Vec::new();
+    debug!("=== Enemy fleets ===\n{:?}", enemy);
+    debug!("=== Applied defaults ===\n{:?}", applied_defaults);
+    applied_defaults
+}
+
+/// 味方艦隊だけの検証と、マスターデータに基づく補完を行う。
+/// 同じ敵編成・オプションで複数の味方艦隊を比べる場合は、2つ目以降の艦隊にはこれだけを行う。
+fn prepare_friend(friend: &mut interface::Fleet, locale: &interface::Locale) {
+    let master = master::master_data();
+    let mut errors = Vec::new();
+    errors.extend(friend.validate().err());
+    // マスターデータがある場合は、艦娘のステータスが改造段階として妥当な範囲か確認する
+    if let Some(master) = master.as_deref() {
+        errors.extend(friend.ships().iter().filter_map(|ship| {
+            let master_ship = master.ship(ship.id())?;
+            ship.check_stats(master_ship).err()
+        }));
+    }
+    errors.extend(
+        friend
+            .ships()
+            .iter()
+            .filter_map(|ship| ship.check_improvements().err()),
+    );
+    report_errors(errors);
 
+    if let Some(master) = master.as_deref() {
+        friend.apply_equipment_bonuses(master);
+    }
+    friend.localize_names(master.as_deref(), locale);
     debug!("=== Friend fleet ===\n{:?}", friend);
-    debug!("=== Enemy fleets ===\n{:?}", enemy);
+}
+
+/// 検証で見つかった問題をログと診断情報に報告する。
+fn report_errors(errors: Vec<interface::ErrorReport>) {
+    for error in errors {
+        warn!("{}", error.message);
+        diagnostics::report(error);
+    }
+}
 
-    for i in 0..count {
-        let logging = i < 1 || i % 100 == 0;
-        let (idx, selected_enemy) = select_random_enemy(&enemy);
-        let battle_result = battle_once(&friend, selected_enemy);
-        results.push(battle_result);
+/// シミュレーションを実行せずに入力を検証し、見つかった問題をすべて返す。
+/// フロントエンドで入力中にすぐ結果を示せるよう、マスターデータに依存しない論理的な矛盾だけを確認する。
+pub fn check_input(
+    friend: &interface::Fleet,
+    enemy: &[interface::EnemyFleet],
+) -> Vec<interface::ErrorReport> {
+    let mut errors = friend.check();
+    for fleet in enemy {
+        errors.extend(fleet.check());
+        // 鬼級・姫級の装甲が 0 なのは入力漏れとみなす
+        errors.extend(
+            fleet
+                .ships()
+                .iter()
+                .filter(|ship| ship.abyssal_class().is_some_and(|c| c.is_boss()))
+                .filter(|ship| ship.armor() == 0)
+                .map(|ship| {
+                    interface::ErrorReport::new(
+                        interface::ErrorCode::BossArmorZero,
+                        format!(
+                            "Boss armor is zero: {} (#{}) at node {}",
+                            ship.name(),
+                            ship.id(),
+                            fleet.node()
+                        ),
+                    )
+                }),
+        );
+    }
+    errors.extend(validate_probabilities(enemy));
+    errors
+}
+
+/// 敵艦隊の出現確率を検証し、見つかった問題をすべて返す。
+/// 出現確率はマスごとに合計 1 になる必要があるため、`node` ごとに合計を確認する。
+fn validate_probabilities(enemy: &[interface::EnemyFleet]) -> Vec<interface::ErrorReport> {
+    use interface::{ErrorCode, ErrorReport};
+    // 入力の丸め誤差を許容する
+    const TOLERANCE: f64 = 0.01;
+
+    if enemy.is_empty() {
+        return vec![ErrorReport::new(
+            ErrorCode::EnemyFleetsEmpty,
+            "No enemy fleets are given".to_string(),
+        )];
+    }
+    let mut errors = enemy
+        .iter()
+        .filter(|e| e.probability <= 0.0)
+        .map(|e| {
+            ErrorReport::new(
+                ErrorCode::ProbabilitySumInvalid,
+                format!(
+                    "Enemy fleet probability must be positive: {} at node {}",
+                    e.probability,
+                    e.node()
+                ),
+            )
+        })
+        .collect::<Vec<_>>();
+    if !errors.is_empty() {
+        return errors;
+    }
+    let mut sums = std::collections::BTreeMap::new();
+    for e in enemy {
+        *sums.entry(e.node()).or_insert(0.0) += e.probability;
     }
-    Ok(serde_wasm_bindgen::to_value(&results).unwrap())
+    errors.extend(
+        sums.iter()
+            .filter(|(_, sum)| (**sum - 1.0).abs() > TOLERANCE)
+            .map(|(node, sum)| {
+                ErrorReport::new(
+                    ErrorCode::ProbabilitySumInvalid,
+                    format!(
+                        "Enemy fleet probabilities at node {} sum to {}, not 1",
+                        node, sum
+                    ),
+                )
+            }),
+    );
+    errors
 }
 
-fn select_random_enemy(enemy_fleets: &[interface::EnemyFleet]) -> (usize, &interface::EnemyFleet) {
-    let r = rand::random::<f64>();
+/// 実際のゲームの戦闘 API (`api_req_sortie/battle`) のレスポンスを読み込み、
+/// 砲撃戦の各攻撃を `options.formula_set` の計算式で再計算して、観測されたダメージと比較する。
+/// レスポンスの形式が不正な場合はエラーメッセージを返す。
+pub fn compare_api_battle(
+    text: &str,
+    options: &interface::SimulationOptions,
+) -> Result<interface::ReplayReport, String> {
+    let battle = api_log::parse(text)?;
+    let constants =
+        formula::constants_for(options.formula_set.as_deref()).unwrap_or_else(formula::constants);
+    Ok(api_log::compare(&battle, &constants))
+}
+
+/// 同じマスで記録された戦闘 API のレスポンスから、敵編成の候補と出現確率を推定する。
+/// 戻り値はそのままシミュレーションの入力に使える。装甲の推定には `options.formula_set` の計算式を使う。
+pub fn derive_enemy_fleets(
+    texts: &[String],
+    area: u16,
+    map: u16,
+    node: &str,
+    options: &interface::SimulationOptions,
+) -> Result<Vec<interface::EnemyFleet>, String> {
+    let battles = texts
+        .iter()
+        .map(|text| api_log::parse(text))
+        .collect::<Result<Vec<_>, _>>()?;
+    let constants =
+        formula::constants_for(options.formula_set.as_deref()).unwrap_or_else(formula::constants);
+    Ok(api_log::derive_enemy_fleets(
+        &battles, area, map, node, &constants,
+    ))
+}
+
+/// 攻撃力補正・攻撃対象の重み付けのスクリプトを登録し、以降のシミュレーションで使えるようにする。
+/// スクリプトの構文に誤りがある場合はエラーメッセージを返し、登録済みのフックは変更しない。
+#[cfg(feature = "scripting")]
+pub fn register_script_hooks(hooks: interface::ScriptHooks) -> Result<(), String> {
+    scripting::set_hooks(hooks)
+}
+
+/// エラー情報を受け取る関数を登録する。
+/// 入力の検証で見つかった問題やパニックは、この関数に `ErrorReport` として渡される。
+pub fn set_error_reporter(reporter: impl Fn(&interface::ErrorReport) + 'static) {
+    diagnostics::set_reporter(Some(std::rc::Rc::new(reporter)));
+}
+
+/// シミュレーションの出力を JSON にし、指定された形式で圧縮したバイト列を返す。
+pub fn encode_output(
+    output: &interface::SimulationOutput,
+    compression: interface::Compression,
+) -> serde_json::Result<Vec<u8>> {
+    let json = serde_json::to_vec(output)?;
+    Ok(match compression {
+        interface::Compression::None => json,
+        interface::Compression::Gzip => compression::gzip(&json),
+    })
+}
+
+/// マスターデータを読み込み、以降のシミュレーションで使えるようにする。
+pub fn load_master_data(master: interface::MasterData) {
+    master::set_master_data(master);
+}
+
+/// 計算式で使う定数を読み込み、以降のシミュレーションで使えるようにする。
+/// 読み込まなかった場合は、コンパイル時の既定値が使われる。
+pub fn load_formula_constants(constants: interface::FormulaConstants) {
+    formula::set_constants(constants);
+}
+
+/// 名前付きの計算式の定数セットを登録する。
+/// `SimulationOptions.formula_set` に名前を指定すると、そのシミュレーションで使われる。
+pub fn register_formula_set(name: String, constants: interface::FormulaConstants) {
+    formula::register_set(name, constants);
+}
+
+/// 検証後の入力で行う戦闘の回数。敵編成がない場合は、検証でエラーを報告した上で戦闘を行わない。
+fn battle_count(enemy: &[interface::EnemyFleet], count: u32) -> u32 {
+    if enemy.is_empty() {
+        0
+    } else {
+        count
+    }
+}
+
+/// 出現確率に従って敵編成を1つ選ぶ。`enemy_fleets` は空でないこと (`battle_count` を参照)。
+fn select_random_enemy<'a>(
+    enemy_fleets: &'a [interface::EnemyFleet],
+    rng: &mut rng::SimRng,
+) -> (usize, &'a interface::EnemyFleet) {
+    let r = rng::unit(rng);
     let mut cumulative_probability = 0.0;
     for (i, enemy_fleet) in enemy_fleets.iter().enumerate() {
         cumulative_probability += enemy_fleet.probability;
@@ -75,16 +614,16 @@ fn select_random_enemy(enemy_fleets: &[interface::EnemyFleet]) -> (usize, &inter
     enemy_fleets
         .last()
         .map(|ef| (enemy_fleets.len() - 1, ef))
-        .unwrap()
+        .expect("enemy fleets must not be empty")
 }
 
 fn battle_once(
     friend: &interface::Fleet,
     enemy: &interface::EnemyFleet,
-) -> interface::BattleReport {
-    let mut battle = battle::Battle::new(friend, enemy);
-
-    battle.artillery_phase();
-
-    battle.into_battle_report()
+    options: &interface::SimulationOptions,
+    seed: u64,
+) -> battle::Battle {
+    let mut battle = battle::Battle::with_seed(friend, enemy, options, seed);
+    battle::PhasePipeline::new(options.node_type, options).run(&mut battle);
+    battle
 }
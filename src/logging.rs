@@ -0,0 +1,44 @@
+//! ログ出力マクロのファサード。
+//! `logging` フィーチャーが有効な場合は `log` クレートの同名マクロに委譲し、
+//! 無効な場合はメッセージの整形処理ごとコンパイル時に取り除く。
+//! 無効時も引数の型検査だけは行うため、ログにしか使われない変数が未使用警告になることはない。
+//! ビルド構成によっては使われないマクロがあるため、未使用警告は抑制している。
+#![allow(unused_macros)]
+
+#[cfg(feature = "logging")]
+macro_rules! debug {
+    ($($arg:tt)*) => { log::debug!($($arg)*) };
+}
+#[cfg(feature = "logging")]
+macro_rules! info {
+    ($($arg:tt)*) => { log::info!($($arg)*) };
+}
+#[cfg(feature = "logging")]
+macro_rules! warn {
+    ($($arg:tt)*) => { log::warn!($($arg)*) };
+}
+#[cfg(feature = "logging")]
+macro_rules! error {
+    ($($arg:tt)*) => { log::error!($($arg)*) };
+}
+
+#[cfg(not(feature = "logging"))]
+macro_rules! debug {
+    ($($arg:tt)*) => {{
+        if false {
+            let _ = format_args!($($arg)*);
+        }
+    }};
+}
+#[cfg(not(feature = "logging"))]
+macro_rules! info {
+    ($($arg:tt)*) => { debug!($($arg)*) };
+}
+#[cfg(not(feature = "logging"))]
+macro_rules! warn {
+    ($($arg:tt)*) => { debug!($($arg)*) };
+}
+#[cfg(not(feature = "logging"))]
+macro_rules! error {
+    ($($arg:tt)*) => { debug!($($arg)*) };
+}
@@ -0,0 +1,83 @@
+use serde::{Deserialize, Serialize};
+
+/// 装備ボーナス (特定の艦に特定の装備を載せた際に加算される可視ボーナス) の規則。
+/// `ship_ids`, `class_ids`, `ship_type_ids` のいずれかに該当する艦が、
+/// `equipment_ids` に含まれる装備を積んでいる場合に、1個につき `bonus` が加算される。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct EquipmentBonusRule {
+    /// 対象となる装備IDのリスト
+    equipment_ids: Vec<u16>,
+    /// 対象となる艦船固有IDのリスト
+    ship_ids: Vec<u16>,
+    /// 対象となる艦型IDのリスト (マスターデータの `classId` と照合する)
+    class_ids: Vec<u16>,
+    /// 対象となる艦種IDのリスト
+    ship_type_ids: Vec<u16>,
+    /// ボーナスが重複する装備数の上限。未指定の場合は無制限
+    count_cap: Option<u16>,
+    /// 装備1個あたりのボーナス
+    bonus: StatBonus,
+}
+
+impl EquipmentBonusRule {
+    /// この規則が対象の艦に適用されるかどうかを判定する。
+    pub fn applies_to(&self, ship_id: u16, class_id: Option<u16>, ship_type_id: u16) -> bool {
+        self.ship_ids.contains(&ship_id)
+            || class_id.is_some_and(|c| self.class_ids.contains(&c))
+            || self.ship_type_ids.contains(&ship_type_id)
+    }
+
+    /// 搭載している装備IDのリストから、この規則によるボーナスを計算する。
+    pub fn bonus_for(&self, equipment_ids: &[u16]) -> StatBonus {
+        let count = equipment_ids
+            .iter()
+            .filter(|id| self.equipment_ids.contains(id))
+            .count() as u16;
+        let count = self.count_cap.map_or(count, |cap| count.min(cap));
+        self.bonus.scaled(count as i16)
+    }
+}
+
+/// 装備ボーナスによって加算されるステータス。負の値も取り得る。
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatBonus {
+    pub firepower: i16,
+    pub torpedo: i16,
+    pub armor: i16,
+    pub anti_aircraft: i16,
+    pub evasion: i16,
+    pub anti_submarine_warfare: i16,
+    pub scouting: i16,
+}
+
+impl StatBonus {
+    /// 各ステータスを `n` 倍した新しいインスタンスを返す。
+    pub fn scaled(&self, n: i16) -> Self {
+        Self {
+            firepower: self.firepower * n,
+            torpedo: self.torpedo * n,
+            armor: self.armor * n,
+            anti_aircraft: self.anti_aircraft * n,
+            evasion: self.evasion * n,
+            anti_submarine_warfare: self.anti_submarine_warfare * n,
+            scouting: self.scouting * n,
+        }
+    }
+}
+
+impl std::ops::Add for StatBonus {
+    type Output = Self;
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            firepower: self.firepower + rhs.firepower,
+            torpedo: self.torpedo + rhs.torpedo,
+            armor: self.armor + rhs.armor,
+            anti_aircraft: self.anti_aircraft + rhs.anti_aircraft,
+            evasion: self.evasion + rhs.evasion,
+            anti_submarine_warfare: self.anti_submarine_warfare + rhs.anti_submarine_warfare,
+            scouting: self.scouting + rhs.scouting,
+        }
+    }
+}
@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use crate::interface::Locale;
+
+/// 艦船1隻分のマスターデータ。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MasterShip {
+    id: u16,
+    name: String,
+    name_en: Option<String>,
+    /// 艦型ID (夕雲型、長門型など)
+    class_id: Option<u16>,
+    /// ステータスの取り得る範囲。省略された項目は検証しない。
+    #[serde(flatten)]
+    stats: StatRanges,
+}
+
+/// 艦のステータスの `[初期値, 最大値]`。装備を含まない素の値で、
+/// 最大値は近代化改修とケッコンカッコカリによる上昇を含む。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct StatRanges {
+    pub hp: Option<[u16; 2]>,
+    pub firepower: Option<[u16; 2]>,
+    pub torpedo: Option<[u16; 2]>,
+    pub anti_aircraft: Option<[u16; 2]>,
+    pub armor: Option<[u16; 2]>,
+    pub luck: Option<[u16; 2]>,
+}
+
+impl MasterShip {
+    /// 艦船固有IDを取得する。
+    pub fn id(&self) -> u16 {
+        self.id
+    }
+
+    /// 艦型IDを取得する。
+    pub fn class_id(&self) -> Option<u16> {
+        self.class_id
+    }
+
+    /// ステータスの取り得る範囲を取得する。
+    pub fn stats(&self) -> &StatRanges {
+        &self.stats
+    }
+
+    /// 指定された言語での艦名を取得する。
+    /// 英語名が登録されていない場合は日本語名で代替する。
+    pub fn localized_name(&self, locale: &Locale) -> String {
+        match locale {
+            Locale::Ja => self.name.clone(),
+            Locale::En => self.name_en.clone().unwrap_or_else(|| self.name.clone()),
+            Locale::Id => format!("#{}", self.id),
+        }
+    }
+}
@@ -0,0 +1,66 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use serde::{Deserialize, Serialize};
+
+use crate::interface::Locale;
+
+mod equipment_bonus;
+pub use equipment_bonus::{EquipmentBonusRule, StatBonus};
+
+mod master_ship;
+pub use master_ship::{MasterShip, StatRanges};
+
+thread_local! {
+    /// 読み込み済みのマスターデータ。wasm はシングルスレッドで動作するため、スレッドローカルに保持する。
+    static MASTER_DATA: RefCell<Option<Rc<MasterData>>> = const { RefCell::new(None) };
+}
+
+/// 読み込んだマスターデータをモジュール内に保持する。既に読み込み済みの場合は置き換える。
+pub fn set_master_data(data: MasterData) {
+    MASTER_DATA.with(|m| *m.borrow_mut() = Some(Rc::new(data)));
+}
+
+/// 読み込み済みのマスターデータを取得する。未読み込みの場合は `None` を返す。
+pub fn master_data() -> Option<Rc<MasterData>> {
+    MASTER_DATA.with(|m| m.borrow().clone())
+}
+
+/// 艦船や装備の固有情報をまとめたマスターデータ。
+/// フロントエンドから一度だけ受け取り、以降のシミュレーションで ID から情報を引くために使う。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MasterData {
+    ships: Vec<MasterShip>,
+    /// 装備ボーナスの規則表。省略された場合、装備ボーナスは適用されない。
+    equipment_bonuses: Vec<EquipmentBonusRule>,
+}
+
+impl MasterData {
+    /// 艦船固有IDに対応する艦船のマスターデータを取得する。
+    pub fn ship(&self, id: u16) -> Option<&MasterShip> {
+        self.ships.iter().find(|s| s.id() == id)
+    }
+
+    /// 艦船固有IDに対応する艦名を、指定された言語で取得する。
+    /// マスターデータに該当する艦がない場合は `None` を返す。
+    pub fn ship_name(&self, id: u16, locale: &Locale) -> Option<String> {
+        self.ship(id).map(|s| s.localized_name(locale))
+    }
+
+    /// 艦と搭載装備の組み合わせに対して、装備ボーナス表から合計のボーナスを計算する。
+    pub fn equipment_bonus(
+        &self,
+        ship_id: u16,
+        ship_type_id: u16,
+        equipment_ids: &[u16],
+    ) -> StatBonus {
+        let class_id = self.ship(ship_id).and_then(|s| s.class_id());
+        self.equipment_bonuses
+            .iter()
+            .filter(|rule| rule.applies_to(ship_id, class_id, ship_type_id))
+            .fold(StatBonus::default(), |acc, rule| {
+                acc + rule.bonus_for(equipment_ids)
+            })
+    }
+}
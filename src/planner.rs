@@ -0,0 +1,190 @@
+//! 目標の戦闘評価を満たす、最も費用の小さい設定の探索。
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::{ConfidenceInterval, RankDistribution, RankRates, Z_95};
+use crate::battle::BattleResult;
+use crate::fleet::{EnemyFleet, Fleet, FleetLike, Formation};
+use crate::interface::{PlanQuery, SimulationOptions, TargetRank};
+use crate::rng;
+
+/// 探索する設定の組み合わせ1つと、その評価。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanCandidate {
+    pub formation: Formation,
+    pub night_battle: bool,
+    /// ステータスの上げ幅の段階数
+    pub stat_steps: u16,
+    pub cost: f64,
+    /// 戦闘評価の発生率。より安価な候補が目標を満たしたためにシミュレーションしなかった場合は `null`
+    pub rates: Option<RankRates>,
+    /// 目標とする評価以上の発生率と、正規近似による 95% 信頼区間
+    pub target_rate: Option<ConfidenceInterval>,
+    /// 発生率の推定値が目標の下限以上か
+    pub meets_target: bool,
+}
+
+/// 探索の結果。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct PlanResult {
+    /// 候補ごとの戦闘回数
+    pub battles: u32,
+    /// 目標を満たす候補のうち費用が最も小さいもの。費用が同じ場合は発生率の高いものを選ぶ。
+    /// 目標を満たす候補がない場合は `null`
+    pub best: Option<PlanCandidate>,
+    /// 費用の小さい順に並べたすべての候補
+    pub candidates: Vec<PlanCandidate>,
+}
+
+/// `query.knobs` の設定の組み合わせを費用の小さい順にシミュレーションし、目標を満たす最も安価な候補を探す。
+/// 目標を満たす候補が見つかった後は、それより費用の大きい候補はシミュレーションしない。
+/// すべての候補で同じシードの乱数を使うため、候補間の差は乱数のばらつきの影響を受けにくい。
+pub fn plan(
+    friend: &Fleet,
+    enemy: &[EnemyFleet],
+    count: u32,
+    options: &SimulationOptions,
+    query: &PlanQuery,
+) -> PlanResult {
+    let seed = options
+        .seed
+        .unwrap_or_else(|| rng::next_seed(&mut rng::from_seed(None)));
+    let mut candidates = candidates(friend, options, query);
+    candidates.sort_by(|a, b| a.cost.total_cmp(&b.cost));
+
+    let mut best_cost = None;
+    for candidate in &mut candidates {
+        if best_cost.is_some_and(|cost| candidate.cost > cost) {
+            break;
+        }
+        let rates = simulate(candidate, friend, enemy, count, options, query, seed);
+        let rate = match query.target.rank {
+            TargetRank::S => rates.s_or_better,
+            TargetRank::A => rates.a_or_better,
+            TargetRank::B => rates.b_or_better,
+        };
+        let se = if count > 0 {
+            (rate * (1.0 - rate) / count as f64).sqrt()
+        } else {
+            0.0
+        };
+        candidate.meets_target = count > 0 && rate >= query.target.rate;
+        candidate.rates = Some(rates);
+        candidate.target_rate = Some(ConfidenceInterval {
+            estimate: rate,
+            lower: (rate - Z_95 * se).max(0.0),
+            upper: (rate + Z_95 * se).min(1.0),
+        });
+        if candidate.meets_target {
+            best_cost.get_or_insert(candidate.cost);
+        }
+    }
+
+    let target_rate = |c: &PlanCandidate| c.target_rate.as_ref().map_or(0.0, |r| r.estimate);
+    let best = best_cost.and_then(|cost| {
+        candidates
+            .iter()
+            .filter(|c| c.meets_target && c.cost == cost)
+            .max_by(|a, b| target_rate(a).total_cmp(&target_rate(b)))
+            .cloned()
+    });
+    PlanResult {
+        battles: count,
+        best,
+        candidates,
+    }
+}
+
+/// 探索する設定の組み合わせをすべて列挙し、それぞれの費用を計算する。
+fn candidates(
+    friend: &Fleet,
+    options: &SimulationOptions,
+    query: &PlanQuery,
+) -> Vec<PlanCandidate> {
+    let knobs = &query.knobs;
+    let costs = &query.costs;
+
+    let current = friend.formation().unwrap_or_default();
+    let formations = if knobs.formations.is_empty() {
+        vec![current.clone()]
+    } else {
+        knobs.formations.clone()
+    };
+    let nights = if knobs.night_battle {
+        vec![false, true]
+    } else {
+        vec![options.night_battle]
+    };
+    let max_steps = match &knobs.stat_delta {
+        Some(delta) if delta.ship_index < friend.ships().len() => delta.max_steps,
+        Some(delta) => {
+            warn!("Stat delta ship index out of range: {}", delta.ship_index);
+            0
+        }
+        None => 0,
+    };
+
+    let mut candidates = Vec::new();
+    for formation in &formations {
+        for &night_battle in &nights {
+            for stat_steps in 0..=max_steps {
+                let mut cost = stat_steps as f64 * costs.stat_step;
+                if *formation != current {
+                    cost += costs.formation_change;
+                }
+                if night_battle {
+                    cost += costs.night_battle;
+                }
+                candidates.push(PlanCandidate {
+                    formation: formation.clone(),
+                    night_battle,
+                    stat_steps,
+                    cost,
+                    rates: None,
+                    target_rate: None,
+                    meets_target: false,
+                });
+            }
+        }
+    }
+    candidates
+}
+
+/// 候補の設定を適用した味方艦隊とオプションで `count` 回の戦闘を行い、戦闘評価の発生率を返す。
+fn simulate(
+    candidate: &PlanCandidate,
+    friend: &Fleet,
+    enemy: &[EnemyFleet],
+    count: u32,
+    options: &SimulationOptions,
+    query: &PlanQuery,
+    seed: u64,
+) -> RankRates {
+    let mut fleet = friend.clone();
+    fleet.set_formation(candidate.formation.clone());
+    if let Some(delta) = query
+        .knobs
+        .stat_delta
+        .as_ref()
+        .filter(|_| candidate.stat_steps > 0)
+    {
+        let mut ships = fleet.ships().to_vec();
+        ships[delta.ship_index].add_stats(&delta.step.scaled(candidate.stat_steps as i16));
+        fleet.set_ships(ships);
+    }
+    let options = SimulationOptions {
+        night_battle: candidate.night_battle,
+        ..options.clone()
+    };
+
+    let mut rng = rng::from_seed(Some(seed));
+    let mut ranks = RankDistribution::default();
+    for i in 0..crate::battle_count(enemy, count) {
+        crate::diagnostics::set_iteration(i);
+        let (_, selected_enemy) = crate::select_random_enemy(enemy, &mut rng);
+        let battle = crate::battle_once(&fleet, selected_enemy, &options, rng::next_seed(&mut rng));
+        ranks.record(&BattleResult::calculate(&battle));
+    }
+    ranks.cumulative_rates()
+}
@@ -0,0 +1,50 @@
+//! ブラウザの開発者ツールで処理時間を確認するための計測区間。
+//! `profiling` フィーチャーが有効な wasm ビルドでは、区間の開始・終了時に
+//! `performance.mark` を、終了時に `performance.measure` を呼び出す。
+//! それ以外のビルドでは何もしないゼロサイズの型になる。
+
+/// 計測区間。`enter` で開始し、drop されたときに終了する。
+///
+/// ```ignore
+/// let _span = Span::enter("artillery_phase");
+/// ```
+pub struct Span {
+    #[cfg(all(feature = "profiling", target_arch = "wasm32"))]
+    name: &'static str,
+}
+
+impl Span {
+    /// 計測区間を開始する。
+    pub fn enter(name: &'static str) -> Self {
+        #[cfg(all(feature = "profiling", target_arch = "wasm32"))]
+        {
+            if let Some(performance) = performance() {
+                let _ = performance.mark(&format!("{}:start", name));
+            }
+            Self { name }
+        }
+        #[cfg(not(all(feature = "profiling", target_arch = "wasm32")))]
+        {
+            let _ = name;
+            Self {}
+        }
+    }
+}
+
+#[cfg(all(feature = "profiling", target_arch = "wasm32"))]
+impl Drop for Span {
+    fn drop(&mut self) {
+        let Some(performance) = performance() else {
+            return;
+        };
+        let start = format!("{}:start", self.name);
+        let end = format!("{}:end", self.name);
+        let _ = performance.mark(&end);
+        let _ = performance.measure_with_start_mark_and_end_mark(self.name, &start, &end);
+    }
+}
+
+#[cfg(all(feature = "profiling", target_arch = "wasm32"))]
+fn performance() -> Option<web_sys::Performance> {
+    web_sys::window().and_then(|w| w.performance())
+}
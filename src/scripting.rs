@@ -0,0 +1,94 @@
+//! 式言語 (evalexpr) のスクリプトによるフック。
+//! イベント固有の仕様などを、エンジンに実装する前に利用者が試作できるようにする。
+//! `scripting` フィーチャーが有効な場合のみコンパイルされる。
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use evalexpr::{ContextWithMutableVariables, HashMapContext, Node, Value};
+use serde::{Deserialize, Serialize};
+
+thread_local! {
+    /// 登録済みのフック。wasm はシングルスレッドで動作するため、スレッドローカルに保持する。
+    static HOOKS: RefCell<Option<Rc<CompiledHooks>>> = const { RefCell::new(None) };
+}
+
+/// フロントエンドから受け取るフックのスクリプト。
+/// いずれも数値を返す式で、省略したフックは何もしない。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase", default)]
+pub struct ScriptHooks {
+    /// キャップ後攻撃力の補正。式の値が新しい攻撃力になる。
+    /// 変数: `power`, `actor_id`, `actor_ship_type`, `actor_is_friend`,
+    /// `target_id`, `target_ship_type`, `target_is_installation`, `target_is_submarine`
+    pub post_cap_modifier: Option<String>,
+    /// 攻撃対象の選ばれやすさの重み。候補ごとに評価し、値に比例した確率で選ぶ。
+    /// 変数: `actor_is_friend`, `target_index`, `target_id`, `target_ship_type`, `target_hp`, `target_max_hp`
+    pub target_weight: Option<String>,
+}
+
+struct CompiledHooks {
+    post_cap_modifier: Option<Node>,
+    target_weight: Option<Node>,
+}
+
+/// フックのスクリプトを構文解析し、以降の戦闘で使えるようにする。既に登録済みの場合は置き換える。
+pub fn set_hooks(hooks: ScriptHooks) -> Result<(), String> {
+    let compile = |source: Option<String>, name: &str| {
+        source
+            .map(|s| evalexpr::build_operator_tree(&s))
+            .transpose()
+            .map_err(|err| format!("Failed to compile {} script: {}", name, err))
+    };
+    let compiled = CompiledHooks {
+        post_cap_modifier: compile(hooks.post_cap_modifier, "post-cap modifier")?,
+        target_weight: compile(hooks.target_weight, "target weight")?,
+    };
+    HOOKS.with(|h| *h.borrow_mut() = Some(Rc::new(compiled)));
+    Ok(())
+}
+
+fn hooks() -> Option<Rc<CompiledHooks>> {
+    HOOKS.with(|h| h.borrow().clone())
+}
+
+/// 変数を設定した上でスクリプトを評価する。評価に失敗した場合は警告を出して `None` を返す。
+fn evaluate(node: &Node, variables: &[(&str, Value)]) -> Option<f64> {
+    let mut context = HashMapContext::new();
+    for (name, value) in variables {
+        context.set_value(name.to_string(), value.clone()).ok()?;
+    }
+    node.eval_number_with_context(&context)
+        .map_err(|err| warn!("Failed to evaluate script: {}", err))
+        .ok()
+}
+
+/// キャップ後攻撃力の補正フックを適用する。フックが未登録か評価に失敗した場合は `power` をそのまま返す。
+pub fn post_cap_modifier(power: f64, variables: &[(&str, Value)]) -> f64 {
+    let Some(hooks) = hooks() else {
+        return power;
+    };
+    let Some(node) = &hooks.post_cap_modifier else {
+        return power;
+    };
+    let mut variables = variables.to_vec();
+    variables.push(("power", Value::Float(power)));
+    evaluate(node, &variables).unwrap_or(power)
+}
+
+/// 攻撃対象の重み付けフックが登録されているかどうか。
+pub fn has_target_weight() -> bool {
+    hooks().is_some_and(|h| h.target_weight.is_some())
+}
+
+/// 攻撃対象の重み付けフックを評価する。フックが未登録か評価に失敗した場合は 1.0 を返す。
+/// 負の値は 0 とみなす。
+pub fn target_weight(variables: &[(&str, Value)]) -> f64 {
+    hooks()
+        .and_then(|h| {
+            h.target_weight
+                .as_ref()
+                .and_then(|node| evaluate(node, variables))
+        })
+        .unwrap_or(1.0)
+        .max(0.0)
+}
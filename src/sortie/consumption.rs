@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+use crate::battle::{ActionLog, Battle, NodeType, Phase};
+
+/// 燃料・弾薬の消費量 (最大値に対する割合)。
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Consumption {
+    pub fuel: f64,
+    pub ammo: f64,
+}
+
+impl Consumption {
+    /// 通常の昼戦 (ボスマスを含む)
+    const DAY_BATTLE: Self = Self {
+        fuel: 0.2,
+        ammo: 0.2,
+    };
+    /// 昼戦に続けて夜戦を行った場合に追加で消費する量
+    const NIGHT_AFTER_DAY: Self = Self {
+        fuel: 0.0,
+        ammo: 0.1,
+    };
+    /// 夜戦のみのマス
+    const NIGHT_ONLY: Self = Self {
+        fuel: 0.1,
+        ammo: 0.1,
+    };
+    /// 空襲マス
+    const AIR_RAID: Self = Self {
+        fuel: 0.06,
+        ammo: 0.04,
+    };
+
+    /// `node_type` のマスで行った戦闘1回の消費量。ボスマスの消費量は通常のマスと同じ。
+    pub fn of(node_type: NodeType, battle: &Battle) -> Self {
+        match node_type {
+            NodeType::Normal => {
+                let night = battle
+                    .log()
+                    .actions()
+                    .any(|a| matches!(a, ActionLog::PhaseStart(Phase::Night)));
+                if night {
+                    Self::DAY_BATTLE + Self::NIGHT_AFTER_DAY
+                } else {
+                    Self::DAY_BATTLE
+                }
+            }
+            NodeType::NightOnly => Self::NIGHT_ONLY,
+            NodeType::AirRaid => Self::AIR_RAID,
+        }
+    }
+}
+
+impl std::ops::Add for Consumption {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        Self {
+            fuel: self.fuel + rhs.fuel,
+            ammo: self.ammo + rhs.ammo,
+        }
+    }
+}
@@ -0,0 +1,330 @@
+//! 海域マップ全体の出撃 (ルート選択、道中戦、ボス戦) のシミュレーション。
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aggregate::RankDistribution;
+use crate::battle::{ActionLog, BattleResult, DamagedLevel};
+use crate::fleet::{EnemyFleet, Fleet, FleetLike};
+use crate::interface::{AppliedDefault, MapDefinition, MapNode, SimulationOptions};
+use crate::rng::{self, SimRng};
+
+mod consumption;
+pub use consumption::Consumption;
+
+mod morale;
+
+mod repair;
+pub use repair::needs_bucket;
+
+/// ルートの循環などで出撃が終わらない場合に備えた、1回の出撃で訪れるマス数の上限。
+const MAX_NODES_PER_SORTIE: usize = 64;
+
+/// 1回の出撃の流れを表す構造体。
+pub struct Sortie<'a> {
+    map: &'a MapDefinition,
+    /// マス名ごとの敵編成の出現候補。出現確率はマスごとに合計 1 になることを想定する。
+    pools: HashMap<&'a str, Vec<EnemyFleet>>,
+}
+
+impl<'a> Sortie<'a> {
+    pub fn new(map: &'a MapDefinition, enemies: &'a [EnemyFleet]) -> Self {
+        let mut pools: HashMap<&str, Vec<EnemyFleet>> = HashMap::new();
+        for enemy in enemies {
+            pools.entry(enemy.node()).or_default().push(enemy.clone());
+        }
+        Self { map, pools }
+    }
+
+    /// 出撃を `count` 回行い、結果を `aggregator` に記録する。
+    /// 連続出撃の設定がない場合は、毎回 `friend` の状態から出撃する。
+    pub fn run(
+        &self,
+        friend: &Fleet,
+        count: u32,
+        options: &SimulationOptions,
+        aggregator: &mut MapAggregator,
+    ) {
+        let mut rng = rng::from_seed(options.seed);
+        let Some(campaign) = &self.map.campaign else {
+            for i in 0..count {
+                crate::diagnostics::set_iteration(i);
+                self.run_once(friend, options, aggregator, &mut rng);
+            }
+            return;
+        };
+        let mut fleet = friend.clone();
+        for i in 0..count {
+            crate::diagnostics::set_iteration(i);
+            if campaign.sorties == 0 || i % campaign.sorties == 0 {
+                fleet = friend.clone();
+            }
+            aggregator.record_start_condition(&fleet);
+            let after = self.run_once(&fleet, options, aggregator, &mut rng);
+            aggregator.record_buckets(repair::buckets_needed(&after, campaign));
+            fleet = morale::next_sortie_fleet(friend, &after, campaign);
+        }
+    }
+
+    /// 出撃を1回行って結果を記録し、出撃を終えた時点の味方艦隊を返す。
+    fn run_once(
+        &self,
+        friend: &Fleet,
+        options: &SimulationOptions,
+        aggregator: &mut MapAggregator,
+        rng: &mut SimRng,
+    ) -> Fleet {
+        aggregator.sorties += 1;
+        let fleet = self.advance(friend, options, aggregator, rng);
+        aggregator.record_consumption(friend, &fleet);
+        fleet
+    }
+
+    /// 出撃開始地点からマスを順に進み、出撃を終えた時点の味方艦隊を返す。
+    fn advance(
+        &self,
+        friend: &Fleet,
+        options: &SimulationOptions,
+        aggregator: &mut MapAggregator,
+        rng: &mut SimRng,
+    ) -> Fleet {
+        let mut fleet = friend.clone();
+        let mut current = self.map.start.as_str();
+        // タッチ系特殊攻撃は1回の出撃で1度しか発動しない
+        let mut touch_attack_used = options.touch_attack_used;
+
+        for _ in 0..MAX_NODES_PER_SORTIE {
+            let Some(node) = self.map.node(current) else {
+                warn!("Unknown node: {}", current);
+                return fleet;
+            };
+            let stats = aggregator.node_mut(current);
+            stats.visits += 1;
+
+            if let Some(pool) = self.pools.get(current).filter(|p| !p.is_empty()) {
+                if let Some(formation) = node.formation.clone().or_else(|| friend.formation()) {
+                    fleet.set_formation(formation);
+                }
+                let (_, enemy) = crate::select_random_enemy(pool, rng);
+                let node_options = SimulationOptions {
+                    node_type: node.node_type,
+                    touch_attack_used,
+                    ..options.clone()
+                };
+                let battle = crate::battle_once(&fleet, enemy, &node_options, rng::next_seed(rng));
+                touch_attack_used |= battle.log().actions().any(|action| {
+                    matches!(
+                        action,
+                        ActionLog::SpecialAttackRoll { special_attack, triggered: true, .. }
+                            if special_attack.is_touch()
+                    )
+                });
+                let result = BattleResult::calculate(&battle);
+                stats.ranks.record(&result);
+                let heavily_damaged = battle
+                    .log()
+                    .friend_snapshots
+                    .iter()
+                    .any(|s| *s.damaged_level() >= DamagedLevel::Heavy);
+                // 戦闘後の燃料・弾薬の消費は、次の戦闘の弾薬不足による補正に反映される
+                let consumption = Consumption::of(node.node_type, &battle);
+                let mut snapshots = battle.log().friend_snapshots.clone();
+                for snapshot in &mut snapshots {
+                    snapshot.consume(consumption.fuel, consumption.ammo);
+                }
+                if self.map.campaign.is_some() {
+                    morale::apply_battle(&battle, &mut snapshots);
+                }
+                fleet = fleet.apply_snapshot(&snapshots);
+
+                if node.boss {
+                    aggregator.boss_reached += 1.0;
+                    aggregator.boss_ranks.record(&result);
+                    return fleet;
+                }
+
+                if heavily_damaged && !self.map.continue_on_heavy_damage {
+                    aggregator.node_mut(current).retreated += 1.0;
+                    aggregator.retreated += 1.0;
+                    return fleet;
+                }
+            }
+
+            let Some(next) = Self::choose_route(node, rng) else {
+                return fleet;
+            };
+            current = next;
+        }
+        warn!("Sortie exceeded {} nodes", MAX_NODES_PER_SORTIE);
+        fleet
+    }
+
+    /// 進路の確率に従って次のマスを選ぶ。進路がない場合は `None` を返す。
+    fn choose_route<'n>(node: &'n MapNode, rng: &mut SimRng) -> Option<&'n str> {
+        let r = rng::unit(rng);
+        let mut cumulative = 0.0;
+        for route in &node.routes {
+            cumulative += route.probability;
+            if r <= cumulative {
+                return Some(&route.to);
+            }
+        }
+        node.routes.last().map(|route| route.to.as_str())
+    }
+}
+
+/// 出撃の結果を逐次集計する構造体。
+#[derive(Debug, Default)]
+pub struct MapAggregator {
+    sorties: u32,
+    boss_reached: f64,
+    retreated: f64,
+    boss_ranks: RankDistribution,
+    nodes: Vec<NodeSummary>,
+    /// 味方艦ごとの燃料・弾薬の消費量の合計
+    consumption: Vec<Consumption>,
+    /// 連続出撃での、味方艦ごとの出撃開始時の戦意の合計
+    start_condition: Vec<f64>,
+    /// 連続出撃で使った高速修復材の合計
+    buckets: Option<f64>,
+}
+
+impl MapAggregator {
+    pub fn new(map: &MapDefinition) -> Self {
+        Self {
+            nodes: map
+                .nodes
+                .iter()
+                .map(|n| NodeSummary {
+                    name: n.name.clone(),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    /// 出撃開始時と終了時の味方艦隊から、出撃1回分の燃料・弾薬の消費量を計上する。
+    fn record_consumption(&mut self, before: &Fleet, after: &Fleet) {
+        if self.consumption.is_empty() {
+            self.consumption = vec![Consumption::default(); before.ships().len()];
+        }
+        for (total, (before, after)) in self
+            .consumption
+            .iter_mut()
+            .zip(before.ships().iter().zip(after.ships()))
+        {
+            *total = *total
+                + Consumption {
+                    fuel: before.fuel() - after.fuel(),
+                    ammo: before.ammo() - after.ammo(),
+                };
+        }
+    }
+
+    /// 連続出撃で、出撃開始時の味方艦の戦意を計上する。
+    fn record_start_condition(&mut self, fleet: &Fleet) {
+        if self.start_condition.is_empty() {
+            self.start_condition = vec![0.0; fleet.ships().len()];
+        }
+        for (total, ship) in self.start_condition.iter_mut().zip(fleet.ships()) {
+            *total += ship.condition() as f64;
+        }
+    }
+
+    /// 連続出撃で、出撃後の修理に使った高速修復材の数を計上する。
+    fn record_buckets(&mut self, buckets: u32) {
+        *self.buckets.get_or_insert(0.0) += buckets as f64;
+    }
+
+    fn node_mut(&mut self, name: &str) -> &mut NodeSummary {
+        let idx = match self.nodes.iter().position(|n| n.name == name) {
+            Some(idx) => idx,
+            None => {
+                self.nodes.push(NodeSummary {
+                    name: name.to_string(),
+                    ..Default::default()
+                });
+                self.nodes.len() - 1
+            }
+        };
+        &mut self.nodes[idx]
+    }
+
+    /// これまでに集計した結果から `MapSummary` を作成する。
+    pub fn summary(&self) -> MapSummary {
+        let factor = if self.sorties == 0 {
+            0.0
+        } else {
+            1.0 / self.sorties as f64
+        };
+        MapSummary {
+            sorties: self.sorties,
+            boss_reached: self.boss_reached * factor,
+            retreated: self.retreated * factor,
+            boss_ranks: self.boss_ranks.normalized(),
+            nodes: self
+                .nodes
+                .iter()
+                .map(|n| NodeSummary {
+                    name: n.name.clone(),
+                    visits: n.visits,
+                    ranks: n.ranks.normalized(),
+                    retreated: n.retreated * factor,
+                })
+                .collect(),
+            average_consumption: self
+                .consumption
+                .iter()
+                .map(|c| Consumption {
+                    fuel: c.fuel * factor,
+                    ammo: c.ammo * factor,
+                })
+                .collect(),
+            average_start_condition: self.start_condition.iter().map(|c| c * factor).collect(),
+            average_buckets: self.buckets.map(|b| b * factor),
+            applied_defaults: Vec::new(),
+        }
+    }
+}
+
+/// 海域マップ全体のシミュレーション結果。
+#[derive(Serialize, Deserialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MapSummary {
+    /// 出撃の回数
+    pub sorties: u32,
+    /// ボスマスに到達して戦闘した確率
+    pub boss_reached: f64,
+    /// 道中で大破撤退した確率
+    pub retreated: f64,
+    /// ボス戦の戦闘評価の分布 (ボス戦に到達した出撃のみを母数とする)
+    pub boss_ranks: RankDistribution,
+    /// マスごとの結果 (マップ定義の順)
+    pub nodes: Vec<NodeSummary>,
+    /// 味方艦ごとの、出撃1回あたりの燃料・弾薬の消費量 (最大値に対する割合)
+    pub average_consumption: Vec<Consumption>,
+    /// 連続出撃での、味方艦ごとの出撃開始時の平均の戦意
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub average_start_condition: Vec<f64>,
+    /// 連続出撃での、出撃1回あたりの高速修復材の使用数
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub average_buckets: Option<f64>,
+    /// 入力になかったために既定値で補った値
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub applied_defaults: Vec<AppliedDefault>,
+}
+
+/// マスごとのシミュレーション結果。
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct NodeSummary {
+    /// マス名
+    pub name: String,
+    /// このマスを訪れた回数
+    pub visits: u32,
+    /// このマスでの戦闘評価の分布 (このマスで戦闘した回数を母数とする)
+    pub ranks: RankDistribution,
+    /// このマスの戦闘後に大破撤退した確率 (出撃回数を母数とする)
+    pub retreated: f64,
+}
@@ -0,0 +1,86 @@
+use crate::battle::{ActionLog, Battle, Phase, ShipSnapshot};
+use crate::fleet::{Fleet, FleetLike};
+use crate::interface::Campaign;
+
+/// 戦闘1回ごとに全艦の戦意が下がる量
+const BATTLE: i16 = -3;
+/// 昼戦に続いて夜戦を行った場合に追加で下がる量
+const NIGHT_AFTER_DAY: i16 = -2;
+/// 旗艦の戦意が上がる量
+const FLAGSHIP: i16 = 3;
+/// MVP に選ばれた艦の戦意が上がる量
+const MVP: i16 = 10;
+
+/// 自然回復で戻る戦意の上限
+const RECOVERY_CAP: u16 = 49;
+/// 自然回復の間隔 (分)
+const RECOVERY_INTERVAL_MINUTES: u32 = 3;
+/// 自然回復1回あたりの戦意の回復量
+const RECOVERY_PER_INTERVAL: u32 = 3;
+
+/// 戦闘1回分の戦意の増減を、戦闘後の味方艦のスナップショットに反映する。
+/// MVP は敵艦隊に与えたダメージの合計が最も大きい艦とし、誰もダメージを与えていない場合は旗艦とする。
+pub fn apply_battle(battle: &Battle, snapshots: &mut [ShipSnapshot]) {
+    let mut damage_dealt = vec![0u32; snapshots.len()];
+    let mut day = false;
+    let mut night = false;
+    for action in battle.log().actions() {
+        match action {
+            ActionLog::PhaseStart(Phase::Night) => night = true,
+            ActionLog::PhaseStart(_) => day = true,
+            ActionLog::Attack(attack) if attack.to_enemy && !attack.is_land_base_attack() => {
+                if let Some(total) = damage_dealt.get_mut(attack.actor_idx) {
+                    *total += attack.applied_damage as u32;
+                }
+            }
+            _ => {}
+        }
+    }
+    // 同点の場合は艦隊内で前にいる艦を選ぶ
+    let mvp = damage_dealt
+        .iter()
+        .enumerate()
+        .rev()
+        .max_by_key(|(_, damage)| **damage)
+        .map(|(i, _)| i);
+
+    for (i, snapshot) in snapshots.iter_mut().enumerate() {
+        let mut delta = BATTLE;
+        if day && night {
+            delta += NIGHT_AFTER_DAY;
+        }
+        if i == 0 {
+            delta += FLAGSHIP;
+        }
+        if Some(i) == mvp {
+            delta += MVP;
+        }
+        snapshot.change_morale(delta);
+    }
+}
+
+/// `minutes` 分の自然回復を行った後の戦意。上限を上回っている戦意は変化しない。
+pub fn recover(condition: u16, minutes: u32) -> u16 {
+    if condition >= RECOVERY_CAP {
+        return condition;
+    }
+    let recovered = (minutes / RECOVERY_INTERVAL_MINUTES) * RECOVERY_PER_INTERVAL;
+    (condition as u32 + recovered).min(RECOVERY_CAP as u32) as u16
+}
+
+/// 連続出撃で次の出撃に使う味方艦隊。
+/// HP・燃料・弾薬は出撃間に入渠・補給したものとして `initial` に戻し、戦意だけを `after` から引き継ぐ。
+pub fn next_sortie_fleet(initial: &Fleet, after: &Fleet, campaign: &Campaign) -> Fleet {
+    let snapshots: Vec<ShipSnapshot> = initial
+        .ships()
+        .iter()
+        .zip(after.ships())
+        .map(|(ship, after)| {
+            let mut snapshot = ShipSnapshot::from(ship);
+            let condition = recover(after.condition(), campaign.interval_minutes);
+            snapshot.change_morale(condition as i16 - ship.condition() as i16);
+            snapshot
+        })
+        .collect();
+    initial.apply_snapshot(&snapshots)
+}
@@ -0,0 +1,31 @@
+use crate::battle::DamagedLevel;
+use crate::fleet::{Fleet, FleetLike};
+use crate::interface::Campaign;
+
+/// 泊地修理が始まるまでに必要な時間 (分)
+const ANCHORAGE_REPAIR_MINUTES: u32 = 20;
+
+/// 出撃を終えた味方艦隊を次の出撃までに修理するために使う高速修復材の数。
+pub fn buckets_needed(after: &Fleet, campaign: &Campaign) -> u32 {
+    let anchorage =
+        campaign.anchorage_repair && campaign.interval_minutes >= ANCHORAGE_REPAIR_MINUTES;
+    after
+        .ships()
+        .iter()
+        .filter(|ship| needs_bucket(ship.hp(), ship.max_hp(), anchorage))
+        .count() as u32
+}
+
+/// 戦闘後の艦の修理に高速修復材を使うかどうか。
+/// 損傷した艦はすべて高速修復材で修理するものとし、`anchorage` (泊地修理) が有効であれば、
+/// 小破以下の艦は高速修復材を使わずに修理する。撃沈された艦は数えない。
+pub fn needs_bucket(hp: u16, max_hp: u16, anchorage: bool) -> bool {
+    if hp >= max_hp {
+        return false;
+    }
+    match DamagedLevel::from_hp(hp, max_hp) {
+        DamagedLevel::Sunk => false,
+        DamagedLevel::NoDamage | DamagedLevel::Minor => !anchorage,
+        DamagedLevel::Moderate | DamagedLevel::Heavy => true,
+    }
+}
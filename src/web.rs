@@ -0,0 +1,720 @@
+//! ブラウザ向けの wasm-bindgen エクスポートを定義する。
+//! `web` フィーチャーが有効な場合のみコンパイルされる。
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+use wasm_bindgen_futures::JsFuture;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::interface::{ErrorCode, ErrorReport};
+use crate::profiling::Span;
+use crate::{diagnostics, interface, master, utils};
+
+static INIT: std::sync::Once = std::sync::Once::new();
+
+fn initialize() {
+    INIT.call_once(|| {
+        utils::set_panic_hook();
+        // 既存のフック (コンソールへの出力) の後に、登録されたコールバックへ通知する
+        let console_hook = std::panic::take_hook();
+        std::panic::set_hook(Box::new(move |info| {
+            console_hook(info);
+            diagnostics::report(ErrorReport::new(ErrorCode::Panic, info.to_string()));
+        }));
+        #[cfg(feature = "logging")]
+        wasm_logger::init(wasm_logger::Config::default()); // ロガー初期化
+        info!("Logger initialized");
+    });
+}
+
+#[wasm_bindgen]
+pub fn simulate(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    info!("Simulation started");
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    Ok(simulate_parsed(friend, enemy, count, &options))
+}
+
+/// 入力された現在HPを戦闘開始時の状態として、夜戦のみをシミュレーションする。
+/// `options.nodeType` は無視され、戻り値の形式は `simulate` と同じ。
+#[wasm_bindgen]
+pub fn simulate_night_battle(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let options = interface::SimulationOptions {
+        node_type: interface::NodeType::NightOnly,
+        ..options
+    };
+    Ok(simulate_parsed(friend, enemy, count, &options))
+}
+
+/// `phase` (`"first_artillery"`、`"night"` など) だけを他のフェーズから切り離してシミュレーションする。
+/// 戻り値は集計モードの `simulate` と同じ形式。
+#[wasm_bindgen]
+pub fn simulate_phase(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    phase: &str,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let unsupported = |message: String| {
+        error!("{}", message);
+        diagnostics::report(ErrorReport::new(
+            ErrorCode::PhaseUnsupported,
+            message.clone(),
+        ));
+        JsValue::from_str(&message)
+    };
+    let phase = serde_json::from_value::<interface::Phase>(serde_json::Value::from(phase))
+        .map_err(|err| unsupported(format!("Unknown phase: {}", err)))?;
+    let output = {
+        let _span = Span::enter("simulate");
+        crate::run_single_phase(friend, enemy, phase, count, &options).map_err(unsupported)?
+    };
+    let _span = Span::enter("serialize");
+    Ok(serde_wasm_bindgen::to_value(&output).unwrap())
+}
+
+/// 読み込み済みの入力でシミュレーションし、オプションに応じた形式で結果を返す。
+fn simulate_parsed(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> JsValue {
+    if options.incremental
+        && !options.aggregate
+        && !options.columnar
+        && options.compression == interface::Compression::None
+    {
+        return simulate_incremental(friend, enemy, count, options);
+    }
+
+    let output = {
+        let _span = Span::enter("simulate");
+        crate::run_simulation(friend, enemy, count, options)
+    };
+    serialize_output(&output, options)
+}
+
+/// シミュレーションの出力を、`options.compression` に応じて JS の値または圧縮したバイト列に変換する。
+fn serialize_output(
+    output: &interface::SimulationOutput,
+    options: &interface::SimulationOptions,
+) -> JsValue {
+    let _span = Span::enter("serialize");
+    if options.compression != interface::Compression::None {
+        let bytes = crate::encode_output(output, options.compression).unwrap();
+        return js_sys::Uint8Array::from(bytes.as_slice()).into();
+    }
+    serde_wasm_bindgen::to_value(output).unwrap()
+}
+
+/// `simulate` の非同期版。`batch_size` 回 (省略時は 1000 回) の戦闘ごとにイベントループへ制御を返すため、
+/// Web Worker を使わずにメインスレッドで長時間実行しても画面の操作が止まらない。
+/// `on_progress` を指定した場合は、各バッチの後に終えた戦闘の回数を引数として呼び出す。
+/// 戻り値は `simulate` と同じ形式の値を返す Promise。`options.incremental` は無視される。
+#[wasm_bindgen]
+pub async fn simulate_async(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+    batch_size: Option<u32>,
+    on_progress: Option<js_sys::Function>,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let batch_size = batch_size.unwrap_or(DEFAULT_BATCH_SIZE).max(1);
+    let mut simulation = crate::BatchSimulation::new(friend, enemy, count, &options);
+    loop {
+        let finished = {
+            let _span = Span::enter("simulate");
+            simulation.run_batch(batch_size)
+        };
+        if let Some(callback) = &on_progress {
+            callback.call1(&JsValue::NULL, &JsValue::from(simulation.completed()))?;
+        }
+        if finished {
+            break;
+        }
+        yield_to_event_loop().await?;
+    }
+    Ok(serialize_output(&simulation.finish(), &options))
+}
+
+/// `simulate_async` で、イベントループへ制御を返すまでに行う戦闘の回数の既定値
+const DEFAULT_BATCH_SIZE: u32 = 1000;
+
+/// `setTimeout` で次のタスクまで待ち、描画やユーザー操作の処理を先に行わせる。
+async fn yield_to_event_loop() -> Result<(), JsValue> {
+    let promise = js_sys::Promise::new(&mut |resolve, _| {
+        let global = js_sys::global();
+        let _ = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+            window.set_timeout_with_callback(&resolve)
+        } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+            worker.set_timeout_with_callback(&resolve)
+        } else {
+            resolve.call0(&JsValue::NULL).map(|_| 0)
+        };
+    });
+    JsFuture::from(promise).await.map(|_| ())
+}
+
+/// 各 `BattleReport` を生成されるたびにシリアライズし、JS の配列に追加する。
+/// スキーマバージョン 2 以降では、その配列を実行の設定の控えとともに `{ schemaVersion, reports, config }` で包んで返す。
+fn simulate_incremental(
+    friend: interface::Fleet,
+    enemy: Vec<interface::EnemyFleet>,
+    count: u32,
+    options: &interface::SimulationOptions,
+) -> JsValue {
+    let reports = js_sys::Array::new();
+    let mut config = JsValue::UNDEFINED;
+    {
+        let _span = Span::enter("simulate");
+        crate::run_reports(
+            friend,
+            enemy,
+            count,
+            options,
+            |c| config = serde_wasm_bindgen::to_value(c).unwrap(),
+            |report| {
+                reports.push(&serde_wasm_bindgen::to_value(&report).unwrap());
+            },
+        );
+    }
+
+    let schema_version = options
+        .schema_version
+        .unwrap_or(interface::OLDEST_SCHEMA_VERSION);
+    if schema_version < 2 {
+        return reports.into();
+    }
+    let output = js_sys::Object::new();
+    let _ = js_sys::Reflect::set(
+        &output,
+        &JsValue::from_str("schemaVersion"),
+        &JsValue::from(interface::SCHEMA_VERSION),
+    );
+    let _ = js_sys::Reflect::set(&output, &JsValue::from_str("reports"), &reports);
+    let _ = js_sys::Reflect::set(&output, &JsValue::from_str("config"), &config);
+    output.into()
+}
+
+/// 戦闘評価の発生率のみを計算する。
+/// 戦闘ごとの結果や統計を一切返さないため、編成の最適化ループなどで繰り返し呼び出す用途に向く。
+/// 戻り値は `{ sOrBetter, aOrBetter, bOrBetter }` 形式のオブジェクト。
+#[wasm_bindgen]
+pub fn simulate_rank_rates(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let rates = {
+        let _span = Span::enter("simulate");
+        crate::run_rank_rates(friend, enemy, count, &options)
+    };
+    Ok(serde_wasm_bindgen::to_value(&rates).unwrap())
+}
+
+/// 2つの味方編成 (A, B) の結果を比較し、差が偶然のばらつきで説明できるかを検定する。
+/// 戻り値は `SetupComparison` 形式のオブジェクト。
+#[wasm_bindgen]
+pub fn compare_setups(
+    friend_a_val: JsValue,
+    friend_b_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend_b, _, _) = parse_input(friend_b_val, enemy_val.clone(), options_val.clone())?;
+    let (friend_a, enemy, options) = parse_input(friend_a_val, enemy_val, options_val)?;
+    let comparison = {
+        let _span = Span::enter("simulate");
+        crate::run_comparison(friend_a, friend_b, enemy, count, &options)
+    };
+    Ok(serde_wasm_bindgen::to_value(&comparison).unwrap())
+}
+
+/// 味方艦と敵艦の組ごとの攻撃の期待値 (ダメージ、命中率、撃沈率) を解析的に計算する。
+/// 戻り値は敵編成ごとの `DamageTable` の配列。
+#[wasm_bindgen]
+pub fn damage_table(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let tables = {
+        let _span = Span::enter("analyze");
+        crate::run_damage_table(friend, enemy, &options)
+    };
+    Ok(serde_wasm_bindgen::to_value(&tables).unwrap())
+}
+
+/// `options.designatedEnemy` で指定された敵艦を撃沈するまでに要する攻撃回数を解析的に推定する。
+/// 戻り値は `TimeToKill` 形式のオブジェクト。敵艦が指定されていない場合は `null` を返す。
+#[wasm_bindgen]
+pub fn time_to_kill(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let result = {
+        let _span = Span::enter("analyze");
+        crate::run_time_to_kill(friend, enemy, &options)
+    };
+    Ok(serde_wasm_bindgen::to_value(&result).unwrap())
+}
+
+/// 目標の戦闘評価 (例: S勝利以上 90%) を満たす、最も費用の小さい設定を探索する。
+/// `query_val` は `PlanQuery` 形式のオブジェクト。戻り値は `PlanResult` 形式のオブジェクト。
+#[wasm_bindgen]
+pub fn plan_rank_target(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+    query_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let invalid_query = |report: ErrorReport| {
+        error!("{}", report.message);
+        let message = JsValue::from_str(&report.message);
+        diagnostics::report(report);
+        message
+    };
+    let (query, unknown) = interface::deserialize_tracking_unknown::<interface::PlanQuery, _>(
+        serde_wasm_bindgen::Deserializer::from(query_val),
+    )
+    .map_err(|err| {
+        invalid_query(ErrorReport::new(
+            ErrorCode::PlanQueryParseFailed,
+            format!("Failed to parse plan query: {}", err),
+        ))
+    })?;
+    interface::check_unknown_fields("plan query", &unknown, options.strict)
+        .map_err(invalid_query)?;
+
+    let result = {
+        let _span = Span::enter("simulate");
+        crate::run_plan(friend, enemy, count, &options, &query)
+    };
+    Ok(serde_wasm_bindgen::to_value(&result).unwrap())
+}
+
+/// 海域マップ全体への出撃 (ルート選択、道中戦、ボス戦) をシミュレーションする。
+/// `enemy_val` には全マスの敵編成をまとめて渡し、各編成の `node` で出現するマスを指定する。
+/// 戻り値は `MapSummary` 形式のオブジェクト。
+#[wasm_bindgen]
+pub fn simulate_map(
+    friend_val: JsValue,
+    map_val: JsValue,
+    enemy_val: JsValue,
+    count: u32,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let (friend, enemy, options) = parse_input(friend_val, enemy_val, options_val)?;
+    let invalid_map = |report: ErrorReport| {
+        error!("{}", report.message);
+        let message = JsValue::from_str(&report.message);
+        diagnostics::report(report);
+        message
+    };
+    let (map, unknown) = interface::deserialize_tracking_unknown::<interface::MapDefinition, _>(
+        serde_wasm_bindgen::Deserializer::from(map_val),
+    )
+    .map_err(|err| {
+        invalid_map(ErrorReport::new(
+            ErrorCode::MapParseFailed,
+            format!("Failed to parse map definition: {}", err),
+        ))
+    })?;
+    interface::check_unknown_fields("map definition", &unknown, options.strict)
+        .map_err(invalid_map)?;
+
+    let summary = {
+        let _span = Span::enter("simulate");
+        crate::run_map_simulation(friend, &map, enemy, count, &options)
+    };
+    Ok(serde_wasm_bindgen::to_value(&summary).unwrap())
+}
+
+/// シミュレーションの入力をデシリアライズする。
+/// 失敗した場合はエラーを報告し、空の結果を `Err` として返す。
+fn parse_input(
+    friend_val: JsValue,
+    enemy_val: JsValue,
+    options_val: JsValue,
+) -> Result<
+    (
+        interface::Fleet,
+        Vec<interface::EnemyFleet>,
+        interface::SimulationOptions,
+    ),
+    JsValue,
+> {
+    let _span = Span::enter("deserialize");
+    let invalid_input = |code: ErrorCode, message: String| {
+        error!("{}", message);
+        diagnostics::report(ErrorReport::new(code, message));
+        serde_wasm_bindgen::to_value(&Vec::<interface::BattleReport>::new()).unwrap()
+    };
+
+    // 未知のフィールドの扱いはオプションの厳格モードで決まるため、すべて読み込んでから確認する
+    let (friend, friend_unknown) = interface::deserialize_tracking_unknown::<interface::Fleet, _>(
+        serde_wasm_bindgen::Deserializer::from(friend_val),
+    )
+    .map_err(|err| {
+        invalid_input(
+            ErrorCode::FriendFleetParseFailed,
+            format!("Failed to parse friend fleet: {}", err),
+        )
+    })?;
+    let (enemy, enemy_unknown) = interface::deserialize_tracking_unknown::<
+        Vec<interface::EnemyFleet>,
+        _,
+    >(serde_wasm_bindgen::Deserializer::from(enemy_val))
+    .map_err(|err| {
+        invalid_input(
+            ErrorCode::EnemyFleetsParseFailed,
+            format!("Failed to parse enemy fleets: {}", err),
+        )
+    })?;
+
+    // オプションは省略可能。未指定 (undefined) の場合はデフォルト値を使う。
+    let (options, options_unknown) = interface::deserialize_tracking_unknown::<
+        Option<interface::SimulationOptions>,
+        _,
+    >(serde_wasm_bindgen::Deserializer::from(options_val))
+    .map_err(|err| {
+        invalid_input(
+            ErrorCode::OptionsParseFailed,
+            format!("Failed to parse simulation options: {}", err),
+        )
+    })?;
+    let options = options.unwrap_or_default();
+
+    for (input, unknown) in [
+        ("friend fleet", friend_unknown),
+        ("enemy fleets", enemy_unknown),
+        ("simulation options", options_unknown),
+    ] {
+        interface::check_unknown_fields(input, &unknown, options.strict).map_err(|report| {
+            error!("{}", report.message);
+            diagnostics::report(report);
+            serde_wasm_bindgen::to_value(&Vec::<interface::BattleReport>::new()).unwrap()
+        })?;
+    }
+
+    interface::resolve_schema_version(options.schema_version)
+        .map_err(|message| invalid_input(ErrorCode::SchemaVersionUnsupported, message))?;
+
+    Ok((friend, enemy, options))
+}
+
+/// 艦船・装備のマスターデータを読み込み、モジュール内に保持する。
+/// 一度読み込めば、以降の `simulate` 呼び出しで艦名の多言語表示などに使われる。
+#[wasm_bindgen]
+pub fn load_master_data(master_val: JsValue) -> Result<(), JsValue> {
+    initialize();
+
+    let master =
+        serde_wasm_bindgen::from_value::<interface::MasterData>(master_val).map_err(|err| {
+            error!("Failed to parse master data: {:?}", err);
+            diagnostics::report(ErrorReport::new(
+                ErrorCode::MasterDataParseFailed,
+                format!("Failed to parse master data: {}", err),
+            ));
+            JsValue::from_str(&err.to_string())
+        })?;
+    master::set_master_data(master);
+    info!("Master data loaded");
+    Ok(())
+}
+
+thread_local! {
+    /// `init_master_data` でマスターデータを取得した URL。同じ URL からの再取得を省くために使う。
+    static MASTER_DATA_URL: RefCell<Option<String>> = const { RefCell::new(None) };
+}
+
+/// 艦船・装備のマスターデータを URL から取得するか、JSON のバイト列から読み込み、モジュール内に保持する。
+/// `source` が文字列の場合は URL とみなして取得し、同じ URL から読み込み済みであれば再取得しない。
+/// `Uint8Array` または `ArrayBuffer` の場合は、JSON のバイト列として読み込む。
+/// ページの読み込み時に一度呼び出しておけば、以降の呼び出しごとにマスターデータを渡す必要はない。
+#[wasm_bindgen]
+pub async fn init_master_data(source: JsValue) -> Result<(), JsValue> {
+    initialize();
+
+    let fail = |code: ErrorCode, message: String| {
+        error!("{}", message);
+        diagnostics::report(ErrorReport::new(code, message.clone()));
+        JsValue::from_str(&message)
+    };
+
+    let url = source.as_string();
+    let bytes = match &url {
+        Some(url) => {
+            let loaded = MASTER_DATA_URL.with(|u| u.borrow().as_deref() == Some(url.as_str()));
+            if loaded && master::master_data().is_some() {
+                return Ok(());
+            }
+            fetch_bytes(url).await.map_err(|err| {
+                fail(
+                    ErrorCode::MasterDataFetchFailed,
+                    format!("Failed to fetch master data from {}: {:?}", url, err),
+                )
+            })?
+        }
+        None if source.is_instance_of::<js_sys::Uint8Array>()
+            || source.is_instance_of::<js_sys::ArrayBuffer>() =>
+        {
+            js_sys::Uint8Array::new(&source).to_vec()
+        }
+        None => {
+            return Err(fail(
+                ErrorCode::MasterDataParseFailed,
+                "Master data source must be a URL, Uint8Array or ArrayBuffer".to_string(),
+            ))
+        }
+    };
+
+    let master = serde_json::from_slice::<interface::MasterData>(&bytes).map_err(|err| {
+        fail(
+            ErrorCode::MasterDataParseFailed,
+            format!("Failed to parse master data: {}", err),
+        )
+    })?;
+    master::set_master_data(master);
+    MASTER_DATA_URL.with(|u| *u.borrow_mut() = url);
+    info!("Master data loaded");
+    Ok(())
+}
+
+/// `fetch` で URL の内容をバイト列として取得する。ウィンドウと Web Worker のどちらでも動作する。
+async fn fetch_bytes(url: &str) -> Result<Vec<u8>, JsValue> {
+    let global = js_sys::global();
+    let promise = if let Some(window) = global.dyn_ref::<web_sys::Window>() {
+        window.fetch_with_str(url)
+    } else if let Some(worker) = global.dyn_ref::<web_sys::WorkerGlobalScope>() {
+        worker.fetch_with_str(url)
+    } else {
+        return Err(JsValue::from_str("fetch is not available"));
+    };
+    let response: web_sys::Response = JsFuture::from(promise).await?.dyn_into()?;
+    if !response.ok() {
+        return Err(JsValue::from_str(&format!("HTTP {}", response.status())));
+    }
+    let buffer = JsFuture::from(response.array_buffer()?).await?;
+    Ok(js_sys::Uint8Array::new(&buffer).to_vec())
+}
+
+/// 装甲 `armor` の艦の防御力 (装甲乱数を含む) の分布を返す。
+/// 戻り値は `ArmorRoll` 形式のオブジェクト。ダメージの範囲は `floor(攻撃力 - max)` から `floor(攻撃力 - min)` になる。
+#[wasm_bindgen]
+pub fn armor_roll_distribution(armor: u16) -> JsValue {
+    serde_wasm_bindgen::to_value(&interface::ArmorRoll::new(armor)).unwrap()
+}
+
+/// 戦闘開始時と終了時の各艦のHPから、シミュレーションと同じ基準で戦闘評価を判定する。
+/// 戻り値は `"SS"`、`"S"`、…、`"E"` のいずれか。
+#[wasm_bindgen]
+pub fn calculate_rank(
+    friend_initial: &[u16],
+    friend_final: &[u16],
+    enemy_initial: &[u16],
+    enemy_final: &[u16],
+) -> JsValue {
+    let result =
+        interface::BattleResult::from_hp(friend_initial, friend_final, enemy_initial, enemy_final);
+    serde_wasm_bindgen::to_value(&result).unwrap()
+}
+
+/// シミュレーションを実行せずに味方艦隊と敵艦隊の入力を検証する。
+/// 戻り値は見つかった問題 (`ErrorReport`) の配列で、問題がなければ空の配列を返す。
+/// 読み込みに失敗した場合も、その失敗を表す `ErrorReport` を要素として返す。
+#[wasm_bindgen]
+pub fn check_input(friend_val: JsValue, enemy_val: JsValue) -> JsValue {
+    initialize();
+
+    let friend = serde_wasm_bindgen::from_value::<interface::Fleet>(friend_val).map_err(|err| {
+        ErrorReport::new(
+            ErrorCode::FriendFleetParseFailed,
+            format!("Failed to parse friend fleet: {}", err),
+        )
+    });
+    let enemy =
+        serde_wasm_bindgen::from_value::<Vec<interface::EnemyFleet>>(enemy_val).map_err(|err| {
+            ErrorReport::new(
+                ErrorCode::EnemyFleetsParseFailed,
+                format!("Failed to parse enemy fleets: {}", err),
+            )
+        });
+    let reports = match (friend, enemy) {
+        (Ok(friend), Ok(enemy)) => crate::check_input(&friend, &enemy),
+        (friend, enemy) => friend.err().into_iter().chain(enemy.err()).collect(),
+    };
+    serde_wasm_bindgen::to_value(&reports).unwrap()
+}
+
+/// 実際のゲームの戦闘 API のレスポンス (JSON 文字列) を計算式で再計算し、観測されたダメージと比較する。
+/// 戻り値は `ReplayReport` 形式のオブジェクト。
+#[wasm_bindgen]
+pub fn compare_api_battle(text: &str, options_val: JsValue) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let fail = |code: ErrorCode, message: String| {
+        error!("{}", message);
+        diagnostics::report(ErrorReport::new(code, message.clone()));
+        JsValue::from_str(&message)
+    };
+    let options =
+        serde_wasm_bindgen::from_value::<Option<interface::SimulationOptions>>(options_val)
+            .map_err(|err| {
+                fail(
+                    ErrorCode::OptionsParseFailed,
+                    format!("Failed to parse simulation options: {}", err),
+                )
+            })?
+            .unwrap_or_default();
+    let report = crate::compare_api_battle(text, &options)
+        .map_err(|message| fail(ErrorCode::ApiLogParseFailed, message))?;
+    Ok(serde_wasm_bindgen::to_value(&report).unwrap())
+}
+
+/// 同じマスで記録された戦闘 API のレスポンス (JSON 文字列の配列) から、敵編成の候補と出現確率を推定する。
+/// 戻り値は `simulate` の `enemy_val` にそのまま渡せる `EnemyFleet` の配列。
+#[wasm_bindgen]
+pub fn derive_enemy_fleets(
+    texts_val: JsValue,
+    area: u16,
+    map: u16,
+    node: &str,
+    options_val: JsValue,
+) -> Result<JsValue, JsValue> {
+    initialize();
+
+    let fail = |code: ErrorCode, message: String| {
+        error!("{}", message);
+        diagnostics::report(ErrorReport::new(code, message.clone()));
+        JsValue::from_str(&message)
+    };
+    let texts = serde_wasm_bindgen::from_value::<Vec<String>>(texts_val).map_err(|err| {
+        fail(
+            ErrorCode::ApiLogParseFailed,
+            format!("Failed to parse battle API logs: {}", err),
+        )
+    })?;
+    let options =
+        serde_wasm_bindgen::from_value::<Option<interface::SimulationOptions>>(options_val)
+            .map_err(|err| {
+                fail(
+                    ErrorCode::OptionsParseFailed,
+                    format!("Failed to parse simulation options: {}", err),
+                )
+            })?
+            .unwrap_or_default();
+    let fleets = crate::derive_enemy_fleets(&texts, area, map, node, &options)
+        .map_err(|message| fail(ErrorCode::ApiLogParseFailed, message))?;
+    Ok(serde_wasm_bindgen::to_value(&fleets).unwrap())
+}
+
+/// 攻撃力補正・攻撃対象の重み付けのスクリプトを登録する。
+/// `{ postCapModifier, targetWeight }` 形式のオブジェクトを受け取り、各値は数値を返す式とする。
+#[cfg(feature = "scripting")]
+#[wasm_bindgen]
+pub fn register_script_hooks(hooks_val: JsValue) -> Result<(), JsValue> {
+    initialize();
+
+    serde_wasm_bindgen::from_value::<interface::ScriptHooks>(hooks_val)
+        .map_err(|err| err.to_string())
+        .and_then(crate::register_script_hooks)
+        .map_err(|message| {
+            error!("{}", message);
+            diagnostics::report(ErrorReport::new(
+                ErrorCode::ScriptCompileFailed,
+                message.clone(),
+            ));
+            JsValue::from_str(&message)
+        })?;
+    info!("Script hooks registered");
+    Ok(())
+}
+
+/// 計算式で使う定数 (交戦形態補正、キャップ値など) を読み込む。
+/// `name` を省略した場合は既定の定数として、以降の `simulate` 呼び出しでコンパイル時の既定値の代わりに使われる。
+/// `name` を指定した場合は名前付きの定数セットとして登録し、`options.formulaSet` で選択できるようにする。
+/// 省略した項目は既定値で補完される。
+#[wasm_bindgen]
+pub fn load_formula_constants(constants_val: JsValue, name: Option<String>) -> Result<(), JsValue> {
+    initialize();
+
+    let constants = serde_wasm_bindgen::from_value::<interface::FormulaConstants>(constants_val)
+        .map_err(|err| {
+            let message = format!("Failed to parse formula constants: {}", err);
+            error!("{}", message);
+            diagnostics::report(ErrorReport::new(
+                ErrorCode::FormulaConstantsParseFailed,
+                message.clone(),
+            ));
+            JsValue::from_str(&message)
+        })?;
+    match name {
+        Some(name) => crate::register_formula_set(name, constants),
+        None => crate::load_formula_constants(constants),
+    }
+    info!("Formula constants loaded");
+    Ok(())
+}
+
+/// パニックや入力エラーが起きたときに呼び出されるコールバックを登録する。
+/// コールバックは `{ kind, code, message, inputDigest, iteration }` 形式のオブジェクトを 1 つ受け取る。
+/// `code` は `FLEET_EMPTY` のような固定の識別子で、メッセージの翻訳に使える。
+/// `null` や `undefined` を渡すと登録を解除する。
+#[wasm_bindgen]
+pub fn set_error_callback(callback: Option<js_sys::Function>) {
+    initialize();
+    let reporter = callback.map(|callback| {
+        Rc::new(move |report: &ErrorReport| {
+            if let Ok(value) = serde_wasm_bindgen::to_value(report) {
+                let _ = callback.call1(&JsValue::NULL, &value);
+            }
+        }) as diagnostics::Reporter
+    });
+    diagnostics::set_reporter(reporter);
+}
@@ -0,0 +1,78 @@
+//! シードを指定した実行の再現性のテスト。
+//! 同じテストを wasm (`wasm-pack test`) とネイティブ (`cargo test`) の両方で実行し、
+//! 基準値と一致することで、プラットフォームによらず同じ結果になることを確かめる。
+
+#[cfg(target_arch = "wasm32")]
+use wasm_bindgen_test::wasm_bindgen_test;
+
+use sim_core::engine::{ActionLog, Battle, NodeType, PhasePipeline};
+use sim_core::interface::{EnemyFleet, Fleet, SimulationOptions};
+
+fn ship(id: u32, firepower: u16, armor: u16, hp: u16) -> serde_json::Value {
+    serde_json::json!({
+        "id": id, "name": "test", "shipTypeId": 9,
+        "status": {
+            "maxHp": hp, "nowHp": hp, "firepower": firepower, "armor": armor,
+            "torpedo": 0, "antiAircraft": 50, "condition": 49, "range": "long"
+        },
+        "equips": []
+    })
+}
+
+fn fleets() -> (Fleet, Vec<EnemyFleet>) {
+    let friend = serde_json::from_value(serde_json::json!({
+        "ships": [ship(1, 90, 80, 80), ship(2, 60, 50, 60)],
+        "formation": "line_ahead"
+    }))
+    .unwrap();
+    let enemy = |node: &str, probability: f64, firepower: u16| {
+        serde_json::from_value(serde_json::json!({
+            "area": 1, "map": 1, "node": node, "probability": probability,
+            "ships": [ship(1501, firepower, 70, 70), ship(1502, 50, 40, 40)],
+            "formation": "line_ahead"
+        }))
+        .unwrap()
+    };
+    (friend, vec![enemy("A", 0.5, 80), enemy("A", 0.5, 100)])
+}
+
+fn seeded(seed: u64) -> SimulationOptions {
+    SimulationOptions {
+        seed: Some(seed),
+        ..SimulationOptions::default()
+    }
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn same_seed_reproduces_the_same_reports() {
+    let run = |seed| {
+        let (friend, enemy) = fleets();
+        serde_json::to_string(&sim_core::run_simulation(friend, enemy, 20, &seeded(seed))).unwrap()
+    };
+    assert_eq!(run(7), run(7));
+    assert_ne!(run(7), run(8));
+}
+
+#[cfg_attr(not(target_arch = "wasm32"), test)]
+#[cfg_attr(target_arch = "wasm32", wasm_bindgen_test)]
+fn seeded_battle_matches_reference() {
+    // 乱数生成器や標本抽出の手順、計算式を変えるとこの値が変わる
+    let (friend, enemy) = fleets();
+    let options = SimulationOptions {
+        fixed_point: true,
+        ..SimulationOptions::default()
+    };
+    let mut battle = Battle::with_seed(&friend, &enemy[0], &options, 42);
+    PhasePipeline::new(NodeType::Normal, &options).run(&mut battle);
+
+    let damages: Vec<u16> = battle
+        .log()
+        .actions()
+        .filter_map(|action| match action {
+            ActionLog::Attack(attack) => Some(attack.applied_damage),
+            _ => None,
+        })
+        .collect();
+    assert_eq!(damages, vec![40, 0, 10, 42, 28, 8]);
+}